@@ -0,0 +1,72 @@
+"""
+Export word frequency tables for the app's keyword extraction.
+
+Writes one `<lang>.tsv` per wordfreq language to src-tauri/resources/wordfreq,
+holding the most frequent words and their wordfreq frequencies, so the app
+ranks keywords with the same IDF as eval-rake-idf.py.
+
+With --jieba-dict, writes zh.tsv from jieba's dict.txt instead (counts
+divided by their total), for when wordfreq is not at hand.
+
+Run:  python poc/export-wordfreq.py [--lang en --lang de ...]
+      python poc/export-wordfreq.py --jieba-dict path/to/jieba/dict.txt
+Deps: pip install wordfreq
+"""
+
+import argparse
+from pathlib import Path
+
+DIR = Path(__file__).parent
+OUTPUT = DIR / "../src-tauri/resources/wordfreq"
+
+TOP_N = 50_000
+
+
+def write_table(lang: str, rows: list[tuple[str, float]], source: str):
+    OUTPUT.mkdir(parents=True, exist_ok=True)
+    path = OUTPUT / f"{lang}.tsv"
+    with path.open("w", encoding="utf-8", newline="\n") as f:
+        f.write(f"# {source}\n")
+        for word, freq in rows:
+            f.write(f"{word}\t{freq:.3g}\n")
+    print(f"{path.name}: {len(rows)} words")
+
+
+def export_wordfreq(langs: list[str]):
+    from wordfreq import available_languages, top_n_list, word_frequency
+
+    for lang in langs or sorted(available_languages()):
+        rows = []
+        for word in top_n_list(lang, TOP_N):
+            freq = word_frequency(word, lang)
+            if freq > 0 and "\t" not in word:
+                rows.append((word, freq))
+        write_table(lang, rows, f"wordfreq, top {TOP_N} words")
+
+
+def export_jieba(dict_path: Path):
+    counts: dict[str, int] = {}
+    for line in dict_path.read_text("utf-8").splitlines():
+        parts = line.split()
+        if len(parts) >= 2:
+            counts[parts[0].lower()] = counts.get(parts[0].lower(), 0) + int(parts[1])
+    total = sum(counts.values())
+    top = sorted(counts.items(), key=lambda kv: (-kv[1], kv[0]))[:TOP_N]
+    rows = [(word, count / total) for word, count in top]
+    write_table("zh", rows, f"jieba dict.txt (MIT), top {TOP_N} words")
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__)
+    parser.add_argument("--lang", action="append", default=[])
+    parser.add_argument("--jieba-dict", type=Path)
+    args = parser.parse_args()
+
+    if args.jieba_dict:
+        export_jieba(args.jieba_dict)
+    else:
+        export_wordfreq(args.lang)
+
+
+if __name__ == "__main__":
+    main()
@@ -1,34 +1,44 @@
 #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
 use crate::apple_intelligence;
 use crate::audio_feedback::{play_feedback_sound, play_feedback_sound_blocking, SoundType};
-use crate::managers::audio::AudioRecordingManager;
+use crate::audio_toolkit::audio::{decode_audio_file, is_audio_file};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::context::{CaptureContext, ContextManager};
+use crate::dictation_queue::{show_queue_status, Clip, DictationQueue};
+use crate::llm_client::CancelToken;
+use crate::managers::audio::{create_segmenter, create_vad, vad_model_path, AudioRecordingManager};
+use crate::managers::history::{
+    HistoryManager, HistoryRevision, NewHistoryEntry, NewHistoryRevision,
+};
+use crate::managers::notes::{NoteInfo, NoteManager};
+use crate::managers::segments::transcribe_audio;
 use crate::managers::transcription::TranscriptionManager;
-use crate::settings::{get_settings, AppSettings, APPLE_INTELLIGENCE_PROVIDER_ID};
+use crate::notes::{parse_voice_override, project_folder};
+use crate::palette::PaletteEntry;
+use crate::prompt_template::{self, PromptVariables};
+use crate::secrets;
+use crate::settings::{
+    get_settings, AppSettings, PostProcessProvider, APPLE_INTELLIGENCE_PROVIDER_ID,
+};
 use crate::shortcut;
 use crate::tray::{change_tray_icon, TrayIconState};
 use crate::utils::{
-    self, show_processing_overlay, show_recording_overlay, show_transcribing_overlay,
+    self, emit_post_process_preview, emit_post_process_source, show_processing_overlay,
+    show_recording_overlay, show_transcribing_overlay,
 };
-use crate::TranscriptionCoordinator;
+use chrono::{DateTime, Local, SecondsFormat, Utc};
 use ferrous_opencc::{config::BuiltinConfig, OpenCC};
 use log::{debug, error, warn};
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::Instant;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
 use tauri::AppHandle;
+use tauri::Emitter;
 use tauri::Manager;
-
-/// Drop guard that notifies the [`TranscriptionCoordinator`] when the
-/// transcription pipeline finishes — whether it completes normally or panics.
-struct FinishGuard(AppHandle);
-impl Drop for FinishGuard {
-    fn drop(&mut self) {
-        if let Some(c) = self.0.try_state::<TranscriptionCoordinator>() {
-            c.notify_processing_finished();
-        }
-    }
-}
+use tauri_plugin_clipboard_manager::ClipboardExt;
 
 // Shortcut Action Trait
 pub trait ShortcutAction: Send + Sync {
@@ -39,6 +49,9 @@ pub trait ShortcutAction: Send + Sync {
 // Transcribe Action
 struct TranscribeAction {
     post_process: bool,
+    /// Append the text to the last or configured note instead of filing a
+    /// new note and pasting
+    append: bool,
 }
 
 /// Field name for structured output JSON schema
@@ -50,77 +63,295 @@ fn strip_invisible_chars(s: &str) -> String {
 }
 
 /// Build a system prompt from the user's prompt template.
-/// Removes `${output}` placeholder since the transcription is sent as the user message.
-fn build_system_prompt(prompt_template: &str) -> String {
-    prompt_template.replace("${output}", "").trim().to_string()
+/// Leaves `${output}` empty since the transcription is sent as the user message.
+fn build_system_prompt(prompt_template: &str, variables: &PromptVariables) -> String {
+    let mut variables = variables.clone();
+    variables.set("output", "");
+    prompt_template::render(prompt_template, &variables)
+        .trim()
+        .to_string()
 }
 
-async fn post_process_transcription(settings: &AppSettings, transcription: &str) -> Option<String> {
-    let provider = match settings.active_post_process_provider().cloned() {
-        Some(provider) => provider,
-        None => {
-            debug!("Post-processing enabled but no provider is selected");
-            return None;
+/// The prompt for providers sent everything in one message, with the
+/// transcription as `${output}`
+fn build_prompt(prompt_template: &str, variables: &PromptVariables, transcription: &str) -> String {
+    let mut variables = variables.clone();
+    variables.set("output", transcription);
+    prompt_template::render(prompt_template, &variables)
+}
+
+/// The template of the selected post-processing prompt, if there is one
+fn selected_prompt(settings: &AppSettings) -> Option<&str> {
+    let selected_prompt_id = settings.post_process_selected_prompt_id.as_ref()?;
+    settings
+        .post_process_prompts
+        .iter()
+        .find(|prompt| &prompt.id == selected_prompt_id)
+        .map(|prompt| prompt.prompt.as_str())
+}
+
+/// Values for the selected prompt's template variables, other than
+/// `${output}`. The clipboard and selected text are read at recording start
+/// by [`capture_prompt_inputs`].
+fn prompt_variables(
+    app: &AppHandle,
+    settings: &AppSettings,
+    capture_context: Option<&CaptureContext>,
+    clipboard: Option<String>,
+    selected_text: Option<String>,
+) -> PromptVariables {
+    let mut variables = PromptVariables::default();
+    let now = Local::now();
+    variables.set("date", now.format("%Y-%m-%d").to_string());
+    variables.set("time", now.format("%H:%M").to_string());
+    variables.set("language", settings.selected_language.clone());
+    variables.set("custom_words", settings.custom_words.join(", "));
+    if let Some(context) = capture_context {
+        for name in ["app_name", "window_title"] {
+            if let Some(value) = context.get(name) {
+                variables.set(name, value);
+            }
         }
+    }
+    if let Some(previous) = app
+        .try_state::<DictationQueue>()
+        .and_then(|queue| queue.last_text())
+    {
+        variables.set("previous_transcription", previous);
+    }
+
+    if let Some(clipboard) = clipboard {
+        variables.set("clipboard", clipboard);
+    }
+    if let Some(selected) = selected_text {
+        variables.set("selected_text", selected);
+    }
+    variables
+}
+
+/// The selected text being read for a recording, for `${selected_text}`
+pub type PendingSelection = mpsc::Receiver<Result<String, String>>;
+
+/// What the prompt uses from the moment a recording started.
+#[derive(Default)]
+pub struct PromptInputs {
+    /// For `${clipboard}`
+    pub clipboard: Option<String>,
+    /// For `${selected_text}`
+    pub selection: Option<PendingSelection>,
+}
+
+/// Prompt inputs taken for recordings, by binding
+static PENDING_INPUTS: Lazy<Mutex<HashMap<String, PromptInputs>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Take the clipboard and start reading the selected text, as far as the
+/// selected prompt uses them, for the recording `binding_id` just started
+/// while the window it was made in still has focus.
+fn capture_prompt_inputs(app: &AppHandle, settings: &AppSettings, binding_id: &str) {
+    let template = selected_prompt(settings).unwrap_or_default();
+    // Before the selection is read, which goes through the clipboard
+    let inputs = PromptInputs {
+        clipboard: read_clipboard(app, template),
+        selection: prompt_template::uses(template, "selected_text")
+            .then(|| capture_selection(app))
+            .flatten(),
     };
+    if let Ok(mut pending) = PENDING_INPUTS.lock() {
+        pending.insert(binding_id.to_string(), inputs);
+    }
+}
 
-    let model = settings
-        .post_process_models
-        .get(&provider.id)
-        .cloned()
-        .unwrap_or_default();
+/// The clipboard text, if `template` uses it
+fn read_clipboard(app: &AppHandle, template: &str) -> Option<String> {
+    prompt_template::uses(template, "clipboard")
+        .then(|| app.clipboard().read_text().unwrap_or_default())
+}
 
-    if model.trim().is_empty() {
-        debug!(
-            "Post-processing skipped because provider '{}' has no model configured",
-            provider.id
-        );
-        return None;
+/// Start reading the selected text. Reading it sends keystrokes, which
+/// belongs on the main thread with the pastes.
+fn capture_selection(app: &AppHandle) -> Option<PendingSelection> {
+    let (tx, rx) = mpsc::channel();
+    let ah = app.clone();
+    match app.run_on_main_thread(move || {
+        let _ = tx.send(utils::read_selected_text(&ah));
+    }) {
+        Ok(()) => Some(rx),
+        Err(e) => {
+            error!("Failed to run selection read on main thread: {:?}", e);
+            None
+        }
     }
+}
 
-    let selected_prompt_id = match &settings.post_process_selected_prompt_id {
-        Some(id) => id.clone(),
-        None => {
-            debug!("Post-processing skipped because no prompt is selected");
-            return None;
+/// What [`capture_prompt_inputs`] took for `binding_id`
+pub fn take_prompt_inputs(binding_id: &str) -> PromptInputs {
+    PENDING_INPUTS
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.remove(binding_id))
+        .unwrap_or_default()
+}
+
+/// Wait for a selection being read. Off the main thread only, since that
+/// is where it is read.
+fn wait_for_selection(selection: PendingSelection) -> Option<String> {
+    match selection.recv_timeout(Duration::from_secs(2)) {
+        Ok(Ok(selected)) => Some(selected),
+        Ok(Err(e)) => {
+            warn!("Failed to read the selected text: {}", e);
+            None
+        }
+        Err(_) => {
+            warn!("Reading the selected text did not finish");
+            None
         }
+    }
+}
+
+/// A provider and model to post-process with, and the prompt and key
+struct PostProcessTarget {
+    provider: PostProcessProvider,
+    model: String,
+    prompt: String,
+    api_key: String,
+}
+
+impl PostProcessTarget {
+    /// How the overlay names the target
+    fn label(&self) -> String {
+        if self.provider.id == APPLE_INTELLIGENCE_PROVIDER_ID {
+            self.provider.label.clone()
+        } else {
+            format!("{} · {}", self.provider.label, self.model)
+        }
+    }
+}
+
+/// The selected provider followed by the fallbacks, in the order they are
+/// tried. Targets that are not fully set up are left out.
+fn post_process_targets(settings: &AppSettings) -> Vec<PostProcessTarget> {
+    let Some(selected_prompt_id) = &settings.post_process_selected_prompt_id else {
+        debug!("Post-processing skipped because no prompt is selected");
+        return Vec::new();
     };
 
-    let prompt = match settings
-        .post_process_prompts
-        .iter()
-        .find(|prompt| prompt.id == selected_prompt_id)
-    {
-        Some(prompt) => prompt.prompt.clone(),
+    let prompt = match selected_prompt(settings) {
+        Some(prompt) => prompt.to_string(),
         None => {
             debug!(
                 "Post-processing skipped because prompt '{}' was not found",
                 selected_prompt_id
             );
-            return None;
+            return Vec::new();
         }
     };
 
     if prompt.trim().is_empty() {
         debug!("Post-processing skipped because the selected prompt is empty");
+        return Vec::new();
+    }
+
+    let selected = (settings.post_process_provider_id.as_str(), "");
+    let fallbacks = settings
+        .post_process_fallbacks
+        .iter()
+        .map(|fallback| (fallback.provider_id.as_str(), fallback.model.as_str()));
+
+    let mut targets = Vec::new();
+    for (provider_id, model) in std::iter::once(selected).chain(fallbacks) {
+        let Some(provider) = settings.post_process_provider(provider_id).cloned() else {
+            debug!("Post-processing provider '{}' not found", provider_id);
+            continue;
+        };
+
+        let model = if model.is_empty() {
+            settings
+                .post_process_models
+                .get(&provider.id)
+                .cloned()
+                .unwrap_or_default()
+        } else {
+            model.to_string()
+        };
+
+        if model.trim().is_empty() {
+            debug!(
+                "Post-processing skips provider '{}' because it has no model configured",
+                provider.id
+            );
+            continue;
+        }
+
+        let api_key = settings
+            .post_process_api_keys
+            .get(&provider.id)
+            .map(|reference| secrets::resolve(reference))
+            .unwrap_or_default();
+
+        targets.push(PostProcessTarget {
+            provider,
+            model,
+            prompt: prompt.clone(),
+            api_key,
+        });
+    }
+    targets
+}
+
+/// Post-processed text and the provider and model that produced it
+struct PostProcessed {
+    text: String,
+    source: String,
+}
+
+/// Post-process with the selected provider, then with each fallback in turn
+/// until one succeeds. `on_attempt` is told the label of each one tried.
+/// `None` means all of them failed, so the transcription is kept.
+async fn post_process_transcription(
+    settings: &AppSettings,
+    transcription: &str,
+    variables: &PromptVariables,
+    mut on_attempt: impl FnMut(&str),
+) -> Option<PostProcessed> {
+    let targets = post_process_targets(settings);
+    if targets.is_empty() {
+        debug!("Post-processing enabled but no provider is set up");
         return None;
     }
 
+    for target in targets {
+        let source = target.label();
+        on_attempt(&source);
+        if let Some(text) = post_process_with(target, transcription, variables).await {
+            return Some(PostProcessed { text, source });
+        }
+    }
+    warn!("Every post-processing provider failed; keeping the original transcription");
+    None
+}
+
+async fn post_process_with(
+    target: PostProcessTarget,
+    transcription: &str,
+    variables: &PromptVariables,
+) -> Option<String> {
+    let PostProcessTarget {
+        provider,
+        model,
+        prompt,
+        api_key,
+    } = target;
+
     debug!(
         "Starting LLM post-processing with provider '{}' (model: {})",
         provider.id, model
     );
 
-    let api_key = settings
-        .post_process_api_keys
-        .get(&provider.id)
-        .cloned()
-        .unwrap_or_default();
-
     if provider.supports_structured_output {
         debug!("Using structured outputs for provider '{}'", provider.id);
 
-        let system_prompt = build_system_prompt(&prompt);
+        let system_prompt = build_system_prompt(&prompt, variables);
         let user_content = transcription.to_string();
 
         // Handle Apple Intelligence separately since it uses native Swift APIs
@@ -232,8 +463,8 @@ async fn post_process_transcription(settings: &AppSettings, transcription: &str)
         }
     }
 
-    // Legacy mode: Replace ${output} variable in the prompt with the actual text
-    let processed_prompt = prompt.replace("${output}", transcription);
+    // Legacy mode: the transcription goes into the prompt as ${output}
+    let processed_prompt = build_prompt(&prompt, variables, transcription);
     debug!("Processed prompt length: {} chars", processed_prompt.len());
 
     match crate::llm_client::send_chat_completion(&provider, api_key, &model, processed_prompt)
@@ -263,6 +494,140 @@ async fn post_process_transcription(settings: &AppSettings, transcription: &str)
     }
 }
 
+/// Token of the reply being streamed, for [`cancel_post_processing`]
+static ACTIVE_STREAM: Mutex<Option<CancelToken>> = Mutex::new(None);
+
+/// Abort the LLM reply being streamed, if any. Its dictation keeps the raw
+/// transcription.
+pub fn cancel_post_processing() {
+    if let Some(token) = ACTIVE_STREAM.lock().ok().and_then(|active| active.clone()) {
+        debug!("Cancelling streamed post-processing");
+        token.cancel();
+    }
+}
+
+/// Outcome of [`stream_post_process`]
+struct StreamedText {
+    /// The post-processed text, or `None` to keep the transcription
+    processed: Option<PostProcessed>,
+    /// Some of the reply was typed into the focused app
+    typed: bool,
+}
+
+/// Post-process with the reply streamed into the overlay and, when
+/// `type_into_app`, typed at the cursor as it arrives. Falls back like
+/// [`post_process_transcription`], but not once text has been typed or the
+/// stream was cancelled. The prompt is sent the legacy way, with the
+/// transcription as `${output}`, since structured output cannot be shown before it
+/// is complete. Apple Intelligence has no stream and is post-processed whole.
+async fn stream_post_process(
+    app: &AppHandle,
+    settings: &AppSettings,
+    transcription: &str,
+    variables: &PromptVariables,
+    type_into_app: bool,
+) -> StreamedText {
+    let targets = post_process_targets(settings);
+    if targets.is_empty() {
+        debug!("Post-processing enabled but no provider is set up");
+    }
+
+    let mut typed = false;
+    for target in targets {
+        let source = target.label();
+        emit_post_process_source(app, Some(&source));
+        if target.provider.id == APPLE_INTELLIGENCE_PROVIDER_ID {
+            if let Some(text) = post_process_with(target, transcription, variables).await {
+                return StreamedText {
+                    processed: Some(PostProcessed { text, source }),
+                    typed,
+                };
+            }
+            continue;
+        }
+
+        debug!(
+            "Streaming LLM post-processing with provider '{}' (model: {})",
+            target.provider.id, target.model
+        );
+        let cancel = CancelToken::new();
+        if let Ok(mut active) = ACTIVE_STREAM.lock() {
+            *active = Some(cancel.clone());
+        }
+
+        let prompt = build_prompt(&target.prompt, variables, transcription);
+        let mut streamed = String::new();
+        let result = crate::llm_client::stream_chat_completion(
+            &target.provider,
+            target.api_key,
+            &target.model,
+            prompt,
+            &cancel,
+            |delta| {
+                let delta = strip_invisible_chars(delta);
+                streamed.push_str(&delta);
+                emit_post_process_preview(app, &streamed);
+                if type_into_app {
+                    typed = true;
+                    // The main thread runs these in order, like pastes
+                    let ah = app.clone();
+                    app.run_on_main_thread(move || {
+                        if let Err(e) = utils::type_text(&delta, &ah) {
+                            error!("Failed to type streamed text: {}", e);
+                        }
+                    })
+                    .unwrap_or_else(|e| error!("Failed to run typing on main thread: {:?}", e));
+                }
+            },
+        )
+        .await;
+
+        if let Ok(mut active) = ACTIVE_STREAM.lock() {
+            *active = None;
+        }
+
+        match result {
+            Ok(Some(_)) => {
+                debug!(
+                    "Streamed post-processing succeeded for provider '{}'. Output length: {} chars",
+                    target.provider.id,
+                    streamed.len()
+                );
+                return StreamedText {
+                    processed: Some(PostProcessed {
+                        text: streamed,
+                        source,
+                    }),
+                    typed,
+                };
+            }
+            Ok(None) => error!("LLM API response has no content"),
+            Err(_) if cancel.is_cancelled() => {
+                debug!("Streamed post-processing cancelled");
+                return StreamedText {
+                    processed: None,
+                    typed,
+                };
+            }
+            Err(e) => error!(
+                "Streamed post-processing failed for provider '{}': {}",
+                target.provider.id, e
+            ),
+        }
+        if typed {
+            // What was typed cannot be taken back for another provider's reply
+            break;
+        }
+        emit_post_process_preview(app, "");
+    }
+
+    warn!("Streamed post-processing failed; keeping the original transcription");
+    StreamedText {
+        processed: None,
+        typed,
+    }
+}
+
 async fn maybe_convert_chinese_variant(
     settings: &AppSettings,
     transcription: &str,
@@ -307,11 +672,213 @@ async fn maybe_convert_chinese_variant(
     }
 }
 
+/// Only one re-transcription swaps engines at a time.
+static RETRANSCRIBE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Run the recording saved with a history entry through `model_id` and store
+/// the result as a new revision of that entry. The text goes through the same
+/// custom words, filtering, Chinese conversion and, when `post_process` is
+/// set, LLM post-processing as a live dictation. The model that was loaded
+/// before is restored afterwards.
+pub async fn retranscribe_recording(
+    app: &AppHandle,
+    entry_id: i64,
+    model_id: &str,
+    post_process: bool,
+) -> Result<HistoryRevision, String> {
+    if app.state::<Arc<AudioRecordingManager>>().is_recording() {
+        return Err("Cannot re-transcribe while recording".to_string());
+    }
+    // Swapping models would change the model queued dictations transcribe with
+    if app.state::<DictationQueue>().pending() > 0 {
+        return Err("Cannot re-transcribe while dictations are processing".to_string());
+    }
+
+    let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
+    let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
+    let samples = hm.load_recording(entry_id).map_err(|e| e.to_string())?;
+
+    let model = model_id.to_string();
+    let (transcription, transcription_ms) = tauri::async_runtime::spawn_blocking(move || {
+        let _lock = RETRANSCRIBE_LOCK
+            .lock()
+            .map_err(|_| "Re-transcription lock poisoned".to_string())?;
+        let previous_model = tm.get_current_model();
+        if previous_model.as_deref() != Some(model.as_str()) {
+            tm.load_model(&model).map_err(|e| e.to_string())?;
+        }
+
+        let transcription_time = Instant::now();
+        let result = tm.transcribe(samples);
+        let transcription_ms = transcription_time.elapsed().as_millis() as u64;
+
+        if tm.get_current_model() != previous_model {
+            let restored = match &previous_model {
+                Some(previous) => tm.load_model(previous),
+                None => tm.unload_model(),
+            };
+            if let Err(e) = restored {
+                warn!("Failed to restore model after re-transcription: {}", e);
+            }
+        }
+        result
+            .map(|text| (text, transcription_ms))
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Re-transcription task failed: {}", e))??;
+
+    let settings = get_settings(app);
+    let mut final_text = transcription.clone();
+    if let Some(converted_text) = maybe_convert_chinese_variant(&settings, &transcription).await {
+        final_text = converted_text;
+    }
+    let post_process_time = Instant::now();
+    if post_process {
+        let clipboard = read_clipboard(app, selected_prompt(&settings).unwrap_or_default());
+        let variables = prompt_variables(app, &settings, None, clipboard, None);
+        if let Some(processed) =
+            post_process_transcription(&settings, &final_text, &variables, |_| {}).await
+        {
+            final_text = processed.text;
+        }
+    }
+    let post_processing_ms = post_process.then(|| post_process_time.elapsed().as_millis() as u64);
+
+    hm.add_revision(
+        entry_id,
+        NewHistoryRevision {
+            model_id: model_id.to_string(),
+            processed_text: (final_text != transcription).then_some(final_text),
+            raw_text: transcription,
+            transcription_ms,
+            post_processing_ms,
+        },
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Transcribe an audio file (a voice memo, say) into a new note. The audio
+/// goes through the same VAD, segmenting, custom words and filtering as a
+/// recording; the note and history entry are dated by the file's
+/// modification time.
+pub async fn transcribe_audio_file(
+    app: &AppHandle,
+    path: PathBuf,
+    post_process: bool,
+) -> Result<NoteInfo, String> {
+    if !is_audio_file(&path) {
+        return Err(format!("Not a supported audio file: {}", path.display()));
+    }
+    debug!("Transcribing audio file {:?}", path);
+
+    let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
+    let vad_path = vad_model_path(app).map_err(|e| e.to_string())?;
+    let file = path.clone();
+    let worker_tm = Arc::clone(&tm);
+    let (samples, transcript, transcription_ms) = tauri::async_runtime::spawn_blocking(move || {
+        let samples = decode_audio_file(&file)
+            .map_err(|e| format!("Failed to decode {}: {}", file.display(), e))?;
+        let mut vad = create_vad(&vad_path).map_err(|e| e.to_string())?;
+        // Re-transcription must not swap the model halfway through the file
+        let _lock = RETRANSCRIBE_LOCK
+            .lock()
+            .map_err(|_| "Re-transcription lock poisoned".to_string())?;
+        worker_tm.initiate_model_load();
+        let transcription_time = Instant::now();
+        let transcript =
+            transcribe_audio(&worker_tm, &samples, Some(&mut vad), &create_segmenter())
+                .map_err(|e| e.to_string())?;
+        Ok::<_, String>((
+            samples,
+            transcript,
+            transcription_time.elapsed().as_millis() as u64,
+        ))
+    })
+    .await
+    .map_err(|e| format!("File transcription task failed: {}", e))??;
+
+    let transcription = transcript.text;
+    if transcription.is_empty() {
+        return Err(format!("No speech found in {}", path.display()));
+    }
+
+    let settings = get_settings(app);
+    let mut final_text = transcription.clone();
+    if let Some(converted_text) = maybe_convert_chinese_variant(&settings, &transcription).await {
+        final_text = converted_text;
+    }
+    let post_process_time = Instant::now();
+    if post_process {
+        let clipboard = read_clipboard(app, selected_prompt(&settings).unwrap_or_default());
+        let variables = prompt_variables(app, &settings, None, clipboard, None);
+        if let Some(processed) =
+            post_process_transcription(&settings, &final_text, &variables, |_| {}).await
+        {
+            final_text = processed.text;
+        }
+    }
+    let post_processing_ms = post_process.then(|| post_process_time.elapsed().as_millis() as u64);
+
+    let mut context = CaptureContext::default();
+    if let Some(name) = path.file_name() {
+        context.set("source", name.to_string_lossy());
+    }
+    if let Ok(modified) = std::fs::metadata(&path).and_then(|m| m.modified()) {
+        context.set(
+            "captured_at",
+            DateTime::<Local>::from(modified).to_rfc3339_opts(SecondsFormat::Secs, false),
+        );
+    }
+    let note = app
+        .state::<Arc<NoteManager>>()
+        .save_transcription(&final_text, Some(&context), None)
+        .map_err(|e| format!("Failed to save transcription as note: {}", e))?;
+
+    if settings.history_enabled {
+        let entry = NewHistoryEntry {
+            processed_text: (final_text != transcription).then(|| final_text.clone()),
+            raw_text: transcription,
+            model_id: tm.get_current_model(),
+            audio_duration_ms: samples.len() as u64 * 1000 / WHISPER_SAMPLE_RATE as u64,
+            transcription_ms,
+            post_processing_ms,
+            note_id: Some(note.id.clone()),
+            segments: transcript.segments,
+        };
+        let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
+        if let Err(e) = hm.add_entry(entry, Some(&samples[..])).await {
+            error!("Failed to add history entry: {}", e);
+        }
+    }
+
+    Ok(note)
+}
+
+/// Transcribe audio files dropped onto the window, one after another.
+pub fn transcribe_dropped_files(app: &AppHandle, paths: Vec<PathBuf>) {
+    let paths: Vec<PathBuf> = paths.into_iter().filter(|p| is_audio_file(p)).collect();
+    if paths.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for path in paths {
+            if let Err(e) = transcribe_audio_file(&app, path.clone(), false).await {
+                error!("Failed to transcribe {:?}: {}", path, e);
+                let _ = app.emit("file-transcription-failed", (path, e));
+            }
+        }
+    });
+}
+
 impl ShortcutAction for TranscribeAction {
     fn start(&self, app: &AppHandle, binding_id: &str, _shortcut_str: &str) {
         let start_time = Instant::now();
         debug!("TranscribeAction::start called for binding: {}", binding_id);
 
+        let settings = get_settings(app);
+
         // Load model in the background
         let tm = app.state::<Arc<TranscriptionManager>>();
         tm.initiate_model_load();
@@ -323,7 +890,6 @@ impl ShortcutAction for TranscribeAction {
         let rm = app.state::<Arc<AudioRecordingManager>>();
 
         // Get the microphone mode to determine audio feedback timing
-        let settings = get_settings(app);
         let is_always_on = settings.always_on_microphone;
         debug!("Microphone mode - always_on: {}", is_always_on);
 
@@ -367,6 +933,17 @@ impl ShortcutAction for TranscribeAction {
         }
 
         if recording_started {
+            // Collected in the background once the microphone is running; the
+            // overlay does not take focus, so the focused window is still the user's
+            if settings.capture_context {
+                app.state::<Arc<ContextManager>>().capture(&binding_id);
+            }
+            // A cancelled recording may have left some behind
+            take_prompt_inputs(&binding_id);
+            if self.post_process {
+                capture_prompt_inputs(app, &settings, &binding_id);
+            }
+
             // Dynamically register the cancel shortcut in a separate task to avoid deadlock
             shortcut::register_cancel_shortcut(app);
         }
@@ -384,9 +961,7 @@ impl ShortcutAction for TranscribeAction {
         let stop_time = Instant::now();
         debug!("TranscribeAction::stop called for binding: {}", binding_id);
 
-        let ah = app.clone();
-        let rm = Arc::clone(&app.state::<Arc<AudioRecordingManager>>());
-        let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
+        let rm = app.state::<Arc<AudioRecordingManager>>();
 
         change_tray_icon(app, TrayIconState::Transcribing);
         show_transcribing_overlay(app);
@@ -397,94 +972,34 @@ impl ShortcutAction for TranscribeAction {
         // Play audio feedback for recording stop
         play_feedback_sound(app, SoundType::Stop);
 
-        let binding_id = binding_id.to_string(); // Clone binding_id for the async task
-        let post_process = self.post_process;
-
-        tauri::async_runtime::spawn(async move {
-            let _guard = FinishGuard(ah.clone());
-            let binding_id = binding_id.clone(); // Clone for the inner async task
-            debug!(
-                "Starting async transcription task for binding: {}",
-                binding_id
-            );
-
-            let stop_recording_time = Instant::now();
-            if let Some(samples) = rm.stop_recording(&binding_id) {
+        // Stop here rather than in the pipeline so the recorder is free for
+        // the next press while this clip waits its turn
+        let stop_recording_time = Instant::now();
+        let recording = rm.stop_recording(binding_id);
+        // Taken either way, so a failed recording leaves nothing behind
+        let capture_context = app.state::<Arc<ContextManager>>().take(binding_id);
+        let prompt_inputs = take_prompt_inputs(binding_id);
+        match recording {
+            Some(recording) => {
                 debug!(
-                    "Recording stopped and samples retrieved in {:?}, sample count: {}",
+                    "Recording stopped in {:?}, duration: {}ms",
                     stop_recording_time.elapsed(),
-                    samples.len()
+                    recording.duration_ms()
                 );
-
-                let transcription_time = Instant::now();
-                match tm.transcribe(samples) {
-                    Ok(transcription) => {
-                        debug!(
-                            "Transcription completed in {:?}: '{}'",
-                            transcription_time.elapsed(),
-                            transcription
-                        );
-                        if !transcription.is_empty() {
-                            let settings = get_settings(&ah);
-                            let mut final_text = transcription.clone();
-
-                            // First, check if Chinese variant conversion is needed
-                            if let Some(converted_text) =
-                                maybe_convert_chinese_variant(&settings, &transcription).await
-                            {
-                                final_text = converted_text;
-                            }
-
-                            // Then apply LLM post-processing if this is the post-process hotkey
-                            // Uses final_text which may already have Chinese conversion applied
-                            if post_process {
-                                show_processing_overlay(&ah);
-                            }
-                            if let Some(processed_text) = if post_process {
-                                post_process_transcription(&settings, &final_text).await
-                            } else {
-                                None
-                            } {
-                                final_text = processed_text;
-                            }
-
-                            // Paste the final text (either processed or original)
-                            let ah_clone = ah.clone();
-                            let paste_time = Instant::now();
-                            ah.run_on_main_thread(move || {
-                                match utils::paste(final_text, ah_clone.clone()) {
-                                    Ok(()) => debug!(
-                                        "Text pasted successfully in {:?}",
-                                        paste_time.elapsed()
-                                    ),
-                                    Err(e) => error!("Failed to paste transcription: {}", e),
-                                }
-                                // Hide the overlay after transcription is complete
-                                utils::hide_recording_overlay(&ah_clone);
-                                change_tray_icon(&ah_clone, TrayIconState::Idle);
-                            })
-                            .unwrap_or_else(|e| {
-                                error!("Failed to run paste on main thread: {:?}", e);
-                                utils::hide_recording_overlay(&ah);
-                                change_tray_icon(&ah, TrayIconState::Idle);
-                            });
-                        } else {
-                            utils::hide_recording_overlay(&ah);
-                            change_tray_icon(&ah, TrayIconState::Idle);
-                        }
-                    }
-                    Err(err) => {
-                        debug!("Global Shortcut Transcription error: {}", err);
-                        utils::hide_recording_overlay(&ah);
-                        change_tray_icon(&ah, TrayIconState::Idle);
-                    }
-                }
-            } else {
+                app.state::<DictationQueue>().push(Clip {
+                    binding_id: binding_id.to_string(),
+                    recording,
+                    capture_context,
+                    prompt_inputs,
+                    post_process: self.post_process,
+                    append: self.append,
+                });
+            }
+            None => {
                 debug!("No samples retrieved from recording stop");
-                utils::hide_recording_overlay(&ah);
-                change_tray_icon(&ah, TrayIconState::Idle);
+                show_queue_status(app);
             }
-        });
+        }
 
         debug!(
             "TranscribeAction::stop completed in {:?}",
@@ -493,6 +1008,216 @@ impl ShortcutAction for TranscribeAction {
     }
 }
 
+/// Payload of the `transcription-completed` event, sent once a dictation
+/// has been filed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionCompleted {
+    pub binding_id: String,
+    /// The text as delivered, after conversion and post-processing
+    pub text: String,
+    pub raw_text: String,
+    pub note_id: Option<String>,
+    pub created_at: i64, // Unix timestamp in milliseconds
+}
+
+/// Transcribe a recorded clip, file it as a note and paste it. Runs on the
+/// [`DictationQueue`] worker, one clip at a time in recording order.
+pub async fn process_clip(ah: AppHandle, clip: Clip) {
+    let Clip {
+        binding_id,
+        recording,
+        capture_context,
+        prompt_inputs,
+        post_process,
+        append,
+    } = clip;
+    debug!("Processing clip for binding: {}", binding_id);
+
+    let tm = Arc::clone(&ah.state::<Arc<TranscriptionManager>>());
+    let audio_duration_ms = recording.duration_ms();
+    // Transcription consumes the recording, so keep a copy for the history
+    let history_settings = get_settings(&ah);
+    let history_audio = (history_settings.history_enabled && history_settings.history_save_audio)
+        .then(|| recording.audio())
+        .flatten();
+
+    // With immediate unload the previous clip may have unloaded the model;
+    // transcribe waits for this load to finish
+    tm.initiate_model_load();
+
+    let transcription_time = Instant::now();
+    // Long recordings were mostly transcribed while they ran; this waits for
+    // the last pieces and transcribes what came after them
+    let (transcription, segments) = match recording.transcribe(&tm) {
+        Ok(transcript) => (transcript.text, transcript.segments),
+        Err(err) => {
+            debug!("Global Shortcut Transcription error: {}", err);
+            return;
+        }
+    };
+    let transcription_ms = transcription_time.elapsed().as_millis() as u64;
+    debug!(
+        "Transcription completed in {:?}: '{}'",
+        transcription_time.elapsed(),
+        transcription
+    );
+    if transcription.is_empty() {
+        return;
+    }
+
+    let settings = get_settings(&ah);
+    let mut final_text = transcription.clone();
+
+    // First, check if Chinese variant conversion is needed
+    if let Some(converted_text) = maybe_convert_chinese_variant(&settings, &transcription).await {
+        final_text = converted_text;
+    }
+
+    // A spoken "project X" picks the note's project and is not part of the text
+    let mut spoken_project = None;
+    if !append {
+        let mut known = ah
+            .state::<Arc<NoteManager>>()
+            .project_folders()
+            .unwrap_or_default();
+        known.extend(settings.active_project.as_deref().and_then(project_folder));
+        if let Some((project, rest)) = parse_voice_override(&final_text, &known) {
+            debug!("Voice project override: {}", project);
+            spoken_project = Some(project);
+            final_text = rest;
+        }
+    }
+
+    // Then apply LLM post-processing if this is the post-process hotkey
+    // Uses final_text which may already have Chinese conversion applied.
+    // A recording started meanwhile keeps its overlay.
+    if post_process && !ah.state::<Arc<AudioRecordingManager>>().is_recording() {
+        show_processing_overlay(&ah);
+    }
+    let post_process_time = Instant::now();
+    // Typed text is already in the app; a failed or cancelled stream leaves
+    // what was typed rather than pasting the transcription after it
+    let mut typed = None;
+    if post_process {
+        let selected_text = prompt_inputs.selection.and_then(wait_for_selection);
+        let variables = prompt_variables(
+            &ah,
+            &settings,
+            capture_context.as_ref(),
+            prompt_inputs.clipboard,
+            selected_text,
+        );
+        let processed = if settings.post_process_streaming {
+            let type_into_app =
+                settings.post_process_stream_typing && !append && settings.paste_after_capture;
+            let streamed =
+                stream_post_process(&ah, &settings, &final_text, &variables, type_into_app).await;
+            if streamed.typed {
+                typed = Some(streamed.processed.is_some());
+            }
+            streamed.processed
+        } else {
+            post_process_transcription(&settings, &final_text, &variables, |source| {
+                emit_post_process_source(&ah, Some(source))
+            })
+            .await
+        };
+        match processed {
+            Some(processed) => {
+                debug!("Post-processed by {}", processed.source);
+                final_text = processed.text;
+            }
+            None => emit_post_process_source(&ah, None),
+        }
+    }
+    let post_processing_ms = post_process.then(|| post_process_time.elapsed().as_millis() as u64);
+
+    // File the final text as a Markdown note
+    let nm = ah.state::<Arc<NoteManager>>();
+    let note_id = if append {
+        match nm.append_transcription(&final_text, settings.append_target.as_deref()) {
+            Ok(info) => info.map(|info| info.id),
+            Err(e) => {
+                error!("Failed to append transcription: {}", e);
+                None
+            }
+        }
+    } else {
+        match nm.save_transcription(
+            &final_text,
+            capture_context.as_ref(),
+            spoken_project.as_deref(),
+        ) {
+            Ok(info) => Some(info.id),
+            Err(e) => {
+                error!("Failed to save transcription as note: {}", e);
+                None
+            }
+        }
+    };
+
+    ah.state::<DictationQueue>().set_last_text(&final_text);
+    let _ = ah.emit(
+        "transcription-completed",
+        TranscriptionCompleted {
+            binding_id,
+            text: final_text.clone(),
+            raw_text: transcription.clone(),
+            note_id: note_id.clone(),
+            created_at: Utc::now().timestamp_millis(),
+        },
+    );
+
+    if settings.history_enabled {
+        let hm = Arc::clone(&ah.state::<Arc<HistoryManager>>());
+        let entry = NewHistoryEntry {
+            processed_text: (final_text != transcription).then(|| final_text.clone()),
+            raw_text: transcription,
+            model_id: tm.get_current_model(),
+            audio_duration_ms,
+            transcription_ms,
+            post_processing_ms,
+            note_id,
+            segments,
+        };
+        // Writing the WAV must not hold up the paste
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = hm.add_entry(entry, history_audio.as_deref()).await {
+                error!("Failed to add history entry: {}", e);
+            }
+        });
+    }
+
+    if append || !settings.paste_after_capture {
+        return;
+    }
+
+    match typed {
+        Some(true) => {
+            let ah_clone = ah.clone();
+            ah.run_on_main_thread(move || {
+                if let Err(e) = utils::finish_typed_text(final_text, ah_clone) {
+                    error!("Failed to finish typed transcription: {}", e);
+                }
+            })
+            .unwrap_or_else(|e| error!("Failed to run paste on main thread: {:?}", e));
+            return;
+        }
+        Some(false) => return,
+        None => {}
+    }
+
+    // Paste the final text (either processed or original). The main thread
+    // runs these in submission order, so queued clips paste in order too.
+    let ah_clone = ah.clone();
+    let paste_time = Instant::now();
+    ah.run_on_main_thread(move || match utils::paste(final_text, ah_clone) {
+        Ok(()) => debug!("Text pasted successfully in {:?}", paste_time.elapsed()),
+        Err(e) => error!("Failed to paste transcription: {}", e),
+    })
+    .unwrap_or_else(|e| error!("Failed to run paste on main thread: {:?}", e));
+}
+
 // Cancel Action
 struct CancelAction;
 
@@ -506,6 +1231,21 @@ impl ShortcutAction for CancelAction {
     }
 }
 
+// Palette Action
+struct PaletteAction;
+
+impl ShortcutAction for PaletteAction {
+    fn start(&self, app: &AppHandle, binding_id: &str, _shortcut_str: &str) {
+        debug!("Opening command palette from '{}'", binding_id);
+        let _ = app.emit("palette-opened", PaletteEntry::ALL);
+    }
+
+    fn stop(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        debug!("Closing command palette");
+        let _ = app.emit("palette-closed", ());
+    }
+}
+
 // Test Action
 struct TestAction;
 
@@ -536,19 +1276,80 @@ pub static ACTION_MAP: Lazy<HashMap<String, Arc<dyn ShortcutAction>>> = Lazy::ne
         "transcribe".to_string(),
         Arc::new(TranscribeAction {
             post_process: false,
+            append: false,
         }) as Arc<dyn ShortcutAction>,
     );
     map.insert(
         "transcribe_with_post_process".to_string(),
-        Arc::new(TranscribeAction { post_process: true }) as Arc<dyn ShortcutAction>,
+        Arc::new(TranscribeAction {
+            post_process: true,
+            append: false,
+        }) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "append".to_string(),
+        Arc::new(TranscribeAction {
+            post_process: false,
+            append: true,
+        }) as Arc<dyn ShortcutAction>,
     );
     map.insert(
         "cancel".to_string(),
         Arc::new(CancelAction) as Arc<dyn ShortcutAction>,
     );
+    map.insert(
+        "palette".to_string(),
+        Arc::new(PaletteAction) as Arc<dyn ShortcutAction>,
+    );
     map.insert(
         "test".to_string(),
         Arc::new(TestAction) as Arc<dyn ShortcutAction>,
     );
     map
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{get_default_settings, PostProcessFallback};
+
+    #[test]
+    fn post_process_targets_follow_the_fallback_order() {
+        let mut settings = get_default_settings();
+        settings.post_process_selected_prompt_id = Some("default_improve_transcriptions".into());
+        settings.post_process_provider_id = "openai".into();
+        settings
+            .post_process_models
+            .insert("openai".into(), "gpt-test".into());
+        settings
+            .post_process_models
+            .insert("custom".into(), "llama".into());
+        settings.post_process_fallbacks = vec![
+            PostProcessFallback {
+                provider_id: "groq".into(),
+                model: "fast".into(),
+            },
+            PostProcessFallback {
+                provider_id: "missing".into(),
+                model: String::new(),
+            },
+            PostProcessFallback {
+                provider_id: "custom".into(),
+                model: String::new(),
+            },
+        ];
+
+        let targets: Vec<(String, String)> = post_process_targets(&settings)
+            .into_iter()
+            .map(|target| (target.provider.id, target.model))
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                ("openai".to_string(), "gpt-test".to_string()),
+                ("groq".to_string(), "fast".to_string()),
+                ("custom".to_string(), "llama".to_string()),
+            ]
+        );
+    }
+}
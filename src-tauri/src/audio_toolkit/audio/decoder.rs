@@ -0,0 +1,83 @@
+use anyhow::Result;
+use log::debug;
+use rodio::{Decoder, Source};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::audio_toolkit::{audio::FrameResampler, constants};
+
+/// File extensions [`decode_audio_file`] is expected to handle.
+const AUDIO_FILE_EXTENSIONS: &[&str] = &["wav", "flac", "mp3", "ogg", "oga", "m4a"];
+
+pub fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| AUDIO_FILE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Decode an audio file into 16 kHz mono samples, the format the recorder
+/// produces.
+pub fn decode_audio_file<P: AsRef<Path>>(file_path: P) -> Result<Vec<f32>> {
+    let file = File::open(file_path.as_ref())?;
+    let decoder = Decoder::new(BufReader::new(file))
+        .map_err(|e| anyhow::anyhow!("Unsupported audio file: {}", e))?;
+    let channels = decoder.channels() as usize;
+    let sample_rate = decoder.sample_rate() as usize;
+    let interleaved: Vec<f32> = decoder.collect();
+
+    let samples = resample(&downmix(&interleaved, channels), sample_rate);
+    debug!(
+        "Decoded {:?}: {} Hz, {} channels, {:.1}s",
+        file_path.as_ref(),
+        sample_rate,
+        channels,
+        samples.len() as f32 / constants::WHISPER_SAMPLE_RATE as f32
+    );
+    Ok(samples)
+}
+
+/// Average interleaved channels into one.
+fn downmix(interleaved: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return interleaved.to_vec();
+    }
+    interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+fn resample(samples: &[f32], in_hz: usize) -> Vec<f32> {
+    let mut resampler = FrameResampler::new(
+        in_hz,
+        constants::WHISPER_SAMPLE_RATE as usize,
+        Duration::from_millis(30),
+    );
+    let mut out =
+        Vec::with_capacity(samples.len() * constants::WHISPER_SAMPLE_RATE as usize / in_hz);
+    resampler.push(samples, |frame| out.extend_from_slice(frame));
+    resampler.finish(|frame| out.extend_from_slice(frame));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downmix_and_resample() {
+        assert_eq!(downmix(&[1.0, 0.0, 0.5, 0.5], 2), vec![0.5, 0.5]);
+        assert_eq!(downmix(&[0.25, 0.75], 1), vec![0.25, 0.75]);
+
+        // One second at 48 kHz comes out as one second at 16 kHz, give or
+        // take the resampler's delay and the padding of the last chunk
+        let out = resample(&vec![0.1; 48_000], 48_000);
+        let second = constants::WHISPER_SAMPLE_RATE as usize;
+        assert!(out.len().abs_diff(second) < second / 20);
+
+        assert!(is_audio_file(Path::new("/memos/Voice 001.M4A")));
+        assert!(!is_audio_file(Path::new("/memos/notes.txt")));
+    }
+}
@@ -1,12 +1,16 @@
 // Re-export all audio components
+mod decoder;
 mod device;
 mod recorder;
 mod resampler;
+mod segmenter;
 mod utils;
 mod visualizer;
 
+pub use decoder::{decode_audio_file, is_audio_file};
 pub use device::{list_input_devices, list_output_devices, CpalDeviceInfo};
 pub use recorder::AudioRecorder;
 pub use resampler::FrameResampler;
-pub use utils::save_wav_file;
+pub use segmenter::{Segment, Segmenter};
+pub use utils::{read_wav_file, save_wav_file};
 pub use visualizer::AudioVisualiser;
@@ -10,12 +10,14 @@ use cpal::{
 };
 
 use crate::audio_toolkit::{
-    audio::{AudioVisualiser, FrameResampler},
+    audio::{AudioVisualiser, FrameResampler, Segment, Segmenter},
     constants,
     vad::{self, VadFrame},
     VoiceActivityDetector,
 };
 
+type SegmentCallback = Arc<dyn Fn(Segment) + Send + Sync + 'static>;
+
 enum Cmd {
     Start,
     Stop(mpsc::Sender<Vec<f32>>),
@@ -28,6 +30,8 @@ pub struct AudioRecorder {
     worker_handle: Option<std::thread::JoinHandle<()>>,
     vad: Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
     level_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
+    chunk_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
+    segments: Option<(Arc<Segmenter>, SegmentCallback)>,
 }
 
 impl AudioRecorder {
@@ -38,6 +42,8 @@ impl AudioRecorder {
             worker_handle: None,
             vad: None,
             level_cb: None,
+            chunk_cb: None,
+            segments: None,
         })
     }
 
@@ -54,6 +60,27 @@ impl AudioRecorder {
         self
     }
 
+    /// Receive the processed 16 kHz audio as it is recorded, for consumers
+    /// that cannot wait for `stop()`.
+    pub fn with_chunk_callback<F>(mut self, cb: F) -> Self
+    where
+        F: Fn(Vec<f32>) + Send + Sync + 'static,
+    {
+        self.chunk_cb = Some(Arc::new(cb));
+        self
+    }
+
+    /// Split long recordings while they run: each piece `segmenter` cuts off
+    /// goes to `cb` and leaves the buffer, so `stop()` only returns the audio
+    /// after the last cut.
+    pub fn with_segment_callback<F>(mut self, segmenter: Segmenter, cb: F) -> Self
+    where
+        F: Fn(Segment) + Send + Sync + 'static,
+    {
+        self.segments = Some((Arc::new(segmenter), Arc::new(cb)));
+        self
+    }
+
     pub fn open(&mut self, device: Option<Device>) -> Result<(), Box<dyn std::error::Error>> {
         if self.worker_handle.is_some() {
             return Ok(()); // already open
@@ -72,8 +99,10 @@ impl AudioRecorder {
 
         let thread_device = device.clone();
         let vad = self.vad.clone();
-        // Move the optional level callback into the worker thread
+        // Move the optional callbacks into the worker thread
         let level_cb = self.level_cb.clone();
+        let chunk_cb = self.chunk_cb.clone();
+        let segments = self.segments.clone();
 
         let worker = std::thread::spawn(move || {
             let config = AudioRecorder::get_preferred_config(&thread_device)
@@ -117,7 +146,15 @@ impl AudioRecorder {
             stream.play().expect("failed to start stream");
 
             // keep the stream alive while we process samples
-            run_consumer(sample_rate, vad, sample_rx, cmd_rx, level_cb);
+            run_consumer(
+                sample_rate,
+                vad,
+                sample_rx,
+                cmd_rx,
+                level_cb,
+                chunk_cb,
+                segments,
+            );
             // stream is dropped here, after run_consumer returns
         });
 
@@ -245,6 +282,8 @@ fn run_consumer(
     sample_rx: mpsc::Receiver<Vec<f32>>,
     cmd_rx: mpsc::Receiver<Cmd>,
     level_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
+    chunk_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
+    segments: Option<(Arc<Segmenter>, SegmentCallback)>,
 ) {
     let mut frame_resampler = FrameResampler::new(
         in_sample_rate as usize,
@@ -271,19 +310,37 @@ fn run_consumer(
         recording: bool,
         vad: &Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
         out_buf: &mut Vec<f32>,
+        chunk_cb: &Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
+        segments: &Option<(Arc<Segmenter>, SegmentCallback)>,
     ) {
         if !recording {
             return;
         }
 
-        if let Some(vad_arc) = vad {
+        let before = out_buf.len();
+        let speech = if let Some(vad_arc) = vad {
             let mut det = vad_arc.lock().unwrap();
             match det.push_frame(samples).unwrap_or(VadFrame::Speech(samples)) {
-                VadFrame::Speech(buf) => out_buf.extend_from_slice(buf),
-                VadFrame::Noise => {}
+                VadFrame::Speech(buf) => {
+                    out_buf.extend_from_slice(buf);
+                    true
+                }
+                VadFrame::Noise => false,
             }
         } else {
             out_buf.extend_from_slice(samples);
+            true
+        };
+
+        if let Some(cb) = chunk_cb {
+            if out_buf.len() > before {
+                cb(out_buf[before..].to_vec());
+            }
+        }
+        if let Some((segmenter, cb)) = segments {
+            if let Some(segment) = segmenter.push(out_buf, speech) {
+                cb(segment);
+            }
         }
     }
 
@@ -297,7 +354,14 @@ fn run_consumer(
 
         // ---------- existing pipeline ------------------------------------ //
         frame_resampler.push(&raw, &mut |frame: &[f32]| {
-            handle_frame(frame, recording, &vad, &mut processed_samples)
+            handle_frame(
+                frame,
+                recording,
+                &vad,
+                &mut processed_samples,
+                &chunk_cb,
+                &segments,
+            )
         });
 
         // non-blocking check for a command
@@ -317,12 +381,26 @@ fn run_consumer(
                     // Drain any audio chunks that were captured but not yet consumed
                     while let Ok(remaining) = sample_rx.try_recv() {
                         frame_resampler.push(&remaining, &mut |frame: &[f32]| {
-                            handle_frame(frame, true, &vad, &mut processed_samples)
+                            handle_frame(
+                                frame,
+                                true,
+                                &vad,
+                                &mut processed_samples,
+                                &chunk_cb,
+                                &segments,
+                            )
                         });
                     }
 
                     frame_resampler.finish(&mut |frame: &[f32]| {
-                        handle_frame(frame, true, &vad, &mut processed_samples)
+                        handle_frame(
+                            frame,
+                            true,
+                            &vad,
+                            &mut processed_samples,
+                            &chunk_cb,
+                            &segments,
+                        )
                     });
 
                     let _ = reply_tx.send(std::mem::take(&mut processed_samples));
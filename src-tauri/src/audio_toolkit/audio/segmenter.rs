@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use crate::audio_toolkit::constants;
+
+/// A finished piece of a recording that is still going.
+#[derive(Debug)]
+pub struct Segment {
+    pub samples: Vec<f32>,
+    /// How many samples at the end the next piece starts with again, because
+    /// the cut fell in the middle of speech
+    pub overlap: usize,
+}
+
+/// Decides where to split a long recording so the pieces can be transcribed
+/// while it continues.
+///
+/// Cuts go at the first pause after `min` of audio. When nobody pauses, the
+/// buffer is cut at `max` anyway and the next piece repeats the last
+/// `overlap` of it, so words on the cut are heard whole by one of the two
+/// transcriptions.
+pub struct Segmenter {
+    min_samples: usize,
+    max_samples: usize,
+    overlap_samples: usize,
+}
+
+impl Segmenter {
+    pub fn new(min: Duration, max: Duration, overlap: Duration) -> Self {
+        let samples =
+            |d: Duration| (d.as_secs_f64() * constants::WHISPER_SAMPLE_RATE as f64) as usize;
+        let max_samples = samples(max);
+        Self {
+            min_samples: samples(min).min(max_samples),
+            max_samples,
+            overlap_samples: samples(overlap).min(max_samples / 2),
+        }
+    }
+
+    /// Called after each VAD frame has been added to `buf` (or dropped as
+    /// silence). Cuts the finished piece off `buf` when one is due.
+    pub fn push(&self, buf: &mut Vec<f32>, speech: bool) -> Option<Segment> {
+        if !speech && buf.len() >= self.min_samples {
+            return Some(Segment {
+                samples: std::mem::take(buf),
+                overlap: 0,
+            });
+        }
+        if buf.len() >= self.max_samples {
+            let carried = buf[buf.len() - self.overlap_samples..].to_vec();
+            return Some(Segment {
+                samples: std::mem::replace(buf, carried),
+                overlap: self.overlap_samples,
+            });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: usize = constants::WHISPER_SAMPLE_RATE as usize;
+
+    #[test]
+    fn test_cuts_at_pause_or_max_length() {
+        let segmenter = Segmenter::new(
+            Duration::from_secs(2),
+            Duration::from_secs(5),
+            Duration::from_secs(1),
+        );
+
+        // A pause before the minimum length is not a cut
+        let mut buf = vec![0.0; SECOND];
+        assert!(segmenter.push(&mut buf, false).is_none());
+
+        buf.resize(3 * SECOND, 0.0);
+        let segment = segmenter.push(&mut buf, false).unwrap();
+        assert_eq!(segment.samples.len(), 3 * SECOND);
+        assert_eq!(segment.overlap, 0);
+        assert!(buf.is_empty());
+
+        // Continuous speech is cut at the maximum, carrying the overlap over
+        buf.resize(5 * SECOND, 1.0);
+        let segment = segmenter.push(&mut buf, true).unwrap();
+        assert_eq!(segment.overlap, SECOND);
+        assert_eq!(buf.len(), SECOND);
+    }
+}
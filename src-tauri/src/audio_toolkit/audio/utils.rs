@@ -1,5 +1,5 @@
 use anyhow::Result;
-use hound::{WavSpec, WavWriter};
+use hound::{WavReader, WavSpec, WavWriter};
 use log::debug;
 use std::path::Path;
 
@@ -24,3 +24,34 @@ pub async fn save_wav_file<P: AsRef<Path>>(file_path: P, samples: &[f32]) -> Res
     debug!("Saved WAV file: {:?}", file_path.as_ref());
     Ok(())
 }
+
+/// Read a WAV file written by [`save_wav_file`] back into f32 samples.
+/// Only 16 kHz mono files are accepted; anything else needs resampling first.
+pub fn read_wav_file<P: AsRef<Path>>(file_path: P) -> Result<Vec<f32>> {
+    let mut reader = WavReader::open(file_path.as_ref())?;
+    let spec = reader.spec();
+    if spec.channels != 1 || spec.sample_rate != 16000 {
+        return Err(anyhow::anyhow!(
+            "Expected 16 kHz mono audio, got {} Hz with {} channels",
+            spec.sample_rate,
+            spec.channels
+        ));
+    }
+
+    let samples = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<Vec<_>, _>>()?,
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+    };
+    debug!(
+        "Read WAV file: {:?} ({} samples)",
+        file_path.as_ref(),
+        samples.len()
+    );
+    Ok(samples)
+}
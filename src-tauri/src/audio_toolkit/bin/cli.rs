@@ -1,351 +1,366 @@
-use hound::WavWriter;
-use std::io::{self, Write};
+//! `jotline-audio`: microphone and VAD diagnostics. Lists devices, records
+//! and plays back captures, and runs the VAD over recordings or audio files,
+//! optionally dumping its decision for every frame as CSV.
 
-use handy_app_lib::audio_toolkit::{
-    audio::{list_input_devices, CpalDeviceInfo},
-    vad::SmoothedVad,
-    AudioRecorder, SileroVad,
+use clap::{Parser, Subcommand, ValueEnum};
+use hound::WavWriter;
+use rodio::OutputStreamBuilder;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use jotline_lib::audio_toolkit::{
+    audio::{decode_audio_file, list_input_devices, list_output_devices, CpalDeviceInfo},
+    constants::WHISPER_SAMPLE_RATE,
+    vad::{SmoothedVad, VadFrame},
+    AudioRecorder, SileroVad, VoiceActivityDetector,
 };
 
-#[derive(Debug, Clone, PartialEq)]
-enum RecorderMode {
-    AlwaysOn,
-    OnDemand,
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+/// The VAD works on 30 ms frames
+const FRAME_MS: usize = 30;
+const FRAME_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize * FRAME_MS / 1000;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "jotline-audio",
+    about = "Jotline microphone and VAD diagnostics"
+)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
 }
 
-impl std::fmt::Display for RecorderMode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            RecorderMode::AlwaysOn => write!(f, "Always-On"),
-            RecorderMode::OnDemand => write!(f, "On-Demand"),
-        }
-    }
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List input and output devices
+    Devices,
+    /// Record from a microphone until Enter is pressed
+    Record {
+        /// Input device index, as listed by `devices` (default device otherwise)
+        #[arg(long)]
+        device: Option<usize>,
+
+        /// Stop after this many seconds instead of waiting for Enter
+        #[arg(long)]
+        seconds: Option<u64>,
+
+        #[arg(long, short, default_value = "recording.wav")]
+        output: PathBuf,
+
+        #[command(flatten)]
+        vad: VadArgs,
+    },
+    /// Run the VAD over an audio file and report what it keeps
+    Vad {
+        file: PathBuf,
+
+        /// Write the speech the VAD keeps to this WAV file
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        #[command(flatten)]
+        vad: VadArgs,
+    },
+    /// Play an audio file
+    Play {
+        file: PathBuf,
+
+        /// Output device index, as listed by `devices` (default device otherwise)
+        #[arg(long)]
+        device: Option<usize>,
+    },
 }
 
-struct RecorderState {
-    recorder: AudioRecorder,
-    mode: RecorderMode,
-    is_recording: bool,
-    is_open: bool,
-    current_device_index: Option<usize>,
-    recording_index: u32,
+#[derive(clap::Args, Debug)]
+struct VadArgs {
+    /// Which VAD filters the audio
+    #[arg(long, value_enum, default_value_t = VadMode::Smoothed)]
+    vad: VadMode,
+
+    /// Write the decision for every frame to this CSV file
+    #[arg(long)]
+    csv: Option<PathBuf>,
+
+    #[arg(long, default_value = "resources/models/silero_vad_v4.onnx")]
+    vad_model: PathBuf,
+
+    /// Silero speech probability above which a frame counts as speech
+    #[arg(long, default_value_t = 0.3)]
+    threshold: f32,
+
+    /// Frames kept before speech starts
+    #[arg(long, default_value_t = 15)]
+    prefill: usize,
+
+    /// Frames kept after speech stops
+    #[arg(long, default_value_t = 15)]
+    hangover: usize,
+
+    /// Speech frames in a row needed to start speech
+    #[arg(long, default_value_t = 2)]
+    onset: usize,
 }
 
-impl RecorderState {
-    fn new(recorder: AudioRecorder) -> Self {
-        Self {
-            recorder,
-            mode: RecorderMode::AlwaysOn,
-            is_recording: false,
-            is_open: false,
-            current_device_index: None,
-            recording_index: 1,
-        }
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum VadMode {
+    /// Silero wrapped in `SmoothedVad`, as the app records
+    Smoothed,
+    /// Silero's raw decisions
+    Silero,
+    /// Keep everything
+    Off,
+}
 
-    fn switch_mode(&mut self, new_mode: RecorderMode) -> Result<(), Box<dyn std::error::Error>> {
-        if self.mode == new_mode {
-            return Ok(());
-        }
+/// Per-frame speech decisions, filled in by [`Tap`]s as frames go through.
+type DecisionLog = Arc<Mutex<Vec<bool>>>;
 
-        // If we're currently recording, stop first
-        if self.is_recording {
-            println!("Stopping current recording to switch modes...");
-            self.stop_recording()?;
-        }
+/// Passes frames through to a VAD and notes what it decided.
+struct Tap {
+    inner: Box<dyn VoiceActivityDetector>,
+    log: DecisionLog,
+}
 
-        // Close if open and switching to on-demand, or if switching from on-demand to always-on
-        if self.is_open {
-            match (&self.mode, &new_mode) {
-                (RecorderMode::AlwaysOn, RecorderMode::OnDemand) => {
-                    self.recorder.close()?;
-                    self.is_open = false;
-                    println!("Closed recorder for On-Demand mode");
-                }
-                (RecorderMode::OnDemand, RecorderMode::AlwaysOn) => {
-                    // For switching from on-demand to always-on, we need to reopen
-                    // This will be handled when the user starts recording
-                }
-                _ => {}
-            }
-        }
+impl VoiceActivityDetector for Tap {
+    fn push_frame<'a>(&'a mut self, frame: &'a [f32]) -> anyhow::Result<VadFrame<'a>> {
+        let decision = self.inner.push_frame(frame)?;
+        self.log.lock().unwrap().push(decision.is_speech());
+        Ok(decision)
+    }
 
-        self.mode = new_mode;
-        println!("Switched to {} mode", self.mode);
-        Ok(())
+    fn reset(&mut self) {
+        self.inner.reset()
     }
+}
 
-    fn start_recording(
-        &mut self,
-        device_index: Option<usize>,
-        devices: &[CpalDeviceInfo],
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        if self.is_recording {
-            return Err("Already recording! Stop the current recording first.".into());
-        }
+/// The logs of the Silero and, when smoothing, the smoothed decisions.
+struct Trace {
+    silero: DecisionLog,
+    smoothed: Option<DecisionLog>,
+}
 
-        let device = if let Some(idx) = device_index {
-            if idx >= devices.len() {
-                return Err(format!(
-                    "Invalid device index: {}. Available devices: 0-{}",
-                    idx,
-                    devices.len() - 1
-                )
-                .into());
+impl VadArgs {
+    /// The VAD these arguments describe, with the trace of its decisions.
+    fn build(&self) -> CliResult<Option<(Box<dyn VoiceActivityDetector>, Trace)>> {
+        if self.vad == VadMode::Off {
+            if self.csv.is_some() {
+                return Err("--csv needs a VAD; use --vad smoothed or --vad silero".into());
             }
-            Some(devices[idx].device.clone())
-        } else {
-            None
-        };
+            return Ok(None);
+        }
 
-        match self.mode {
-            RecorderMode::AlwaysOn => {
-                // In always-on mode, open once and keep open
-                if !self.is_open || self.current_device_index != device_index {
-                    if self.is_open {
-                        self.recorder.close()?;
-                    }
-                    self.recorder.open(device)?;
-                    self.is_open = true;
-                    self.current_device_index = device_index;
-                    println!("Opened recorder in Always-On mode");
-                }
-                self.recorder.start()?;
-            }
-            RecorderMode::OnDemand => {
-                // In on-demand mode, open for each recording
-                if self.is_open {
-                    self.recorder.close()?;
-                }
-                self.recorder.open(device)?;
-                self.is_open = true;
-                self.current_device_index = device_index;
-                self.recorder.start()?;
-                println!("Opened and started recorder in On-Demand mode");
-            }
+        let silero = SileroVad::new(&self.vad_model, self.threshold).map_err(|e| {
+            format!(
+                "Failed to load the VAD model {}: {} (run from src-tauri or pass --vad-model)",
+                self.vad_model.display(),
+                e
+            )
+        })?;
+        let silero_log = DecisionLog::default();
+        let silero: Box<dyn VoiceActivityDetector> = Box::new(Tap {
+            inner: Box::new(silero),
+            log: Arc::clone(&silero_log),
+        });
+        if self.vad == VadMode::Silero {
+            let trace = Trace {
+                silero: silero_log,
+                smoothed: None,
+            };
+            return Ok(Some((silero, trace)));
         }
 
-        self.is_recording = true;
+        let smoothed_log = DecisionLog::default();
+        let smoothed = SmoothedVad::new(silero, self.prefill, self.hangover, self.onset);
+        let trace = Trace {
+            silero: silero_log,
+            smoothed: Some(Arc::clone(&smoothed_log)),
+        };
+        let vad: Box<dyn VoiceActivityDetector> = Box::new(Tap {
+            inner: Box::new(smoothed),
+            log: smoothed_log,
+        });
+        Ok(Some((vad, trace)))
+    }
+}
+
+impl Trace {
+    /// Print how much of the audio was speech and write the CSV if asked to.
+    fn report(&self, csv: Option<&Path>) -> CliResult<()> {
+        let silero = self.silero.lock().unwrap();
+        let smoothed = self.smoothed.as_ref().map(|log| log.lock().unwrap());
+        let decisions = smoothed.as_deref().unwrap_or(&silero);
+        let speech = decisions.iter().filter(|&&s| s).count();
         println!(
-            "Recording started with device: {}",
-            device_index.map_or("default".to_string(), |i| i.to_string())
+            "{} of {} frames speech ({:.1}s of {:.1}s)",
+            speech,
+            decisions.len(),
+            (speech * FRAME_MS) as f32 / 1000.0,
+            (decisions.len() * FRAME_MS) as f32 / 1000.0
         );
+        if let Some(path) = csv {
+            let mut file = File::create(path)?;
+            write_csv(&mut file, &silero, smoothed.as_deref().map(Vec::as_slice))?;
+            println!("Frame decisions written to {}", path.display());
+        }
         Ok(())
     }
+}
 
-    fn stop_recording(&mut self) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
-        if !self.is_recording {
-            return Err("No recording in progress.".into());
+/// `frame,start_ms,silero[,smoothed]` with 1 for speech and 0 for noise.
+fn write_csv(out: &mut impl Write, silero: &[bool], smoothed: Option<&[bool]>) -> io::Result<()> {
+    match smoothed {
+        Some(_) => writeln!(out, "frame,start_ms,silero,smoothed")?,
+        None => writeln!(out, "frame,start_ms,silero")?,
+    }
+    for (i, &speech) in silero.iter().enumerate() {
+        write!(out, "{},{},{}", i, i * FRAME_MS, speech as u8)?;
+        if let Some(smoothed) = smoothed {
+            let decision = smoothed
+                .get(i)
+                .map_or(String::new(), |&s| (s as u8).to_string());
+            write!(out, ",{}", decision)?;
         }
+        writeln!(out)?;
+    }
+    Ok(())
+}
 
-        let samples = self.recorder.stop()?;
-        self.is_recording = false;
-
-        match self.mode {
-            RecorderMode::AlwaysOn => {
-                // Keep the recorder open for next recording
-                println!("Recording stopped. Recorder remains open for next recording.");
-            }
-            RecorderMode::OnDemand => {
-                // Close the recorder after each recording
-                self.recorder.close()?;
-                self.is_open = false;
-                self.current_device_index = None;
-                println!("Recording stopped and recorder closed.");
-            }
+fn main() -> CliResult<()> {
+    match Args::parse().command {
+        Command::Devices => {
+            println!("Input devices:");
+            print_devices(&list_input_devices()?);
+            println!("Output devices:");
+            print_devices(&list_output_devices()?);
         }
+        Command::Record {
+            device,
+            seconds,
+            output,
+            vad,
+        } => record(device, seconds, &output, &vad)?,
+        Command::Vad { file, output, vad } => analyze(&file, output.as_deref(), &vad)?,
+        Command::Play { file, device } => play(&file, device)?,
+    }
+    Ok(())
+}
 
-        Ok(samples)
+fn record(
+    device: Option<usize>,
+    seconds: Option<u64>,
+    output: &Path,
+    args: &VadArgs,
+) -> CliResult<()> {
+    let device = device
+        .map(|index| pick_device(list_input_devices()?, index))
+        .transpose()?;
+
+    let mut recorder = AudioRecorder::new()?;
+    let mut trace = None;
+    if let Some((vad, vad_trace)) = args.build()? {
+        recorder = recorder.with_vad(vad);
+        trace = Some(vad_trace);
     }
+    recorder.open(device)?;
+    recorder.start()?;
 
-    fn close(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        if self.is_recording {
-            self.stop_recording()?;
+    match seconds {
+        Some(seconds) => {
+            println!("Recording for {}s...", seconds);
+            thread::sleep(Duration::from_secs(seconds));
         }
-        if self.is_open {
-            self.recorder.close()?;
-            self.is_open = false;
+        None => {
+            print!("Recording, press Enter to stop...");
+            io::stdout().flush()?;
+            io::stdin().read_line(&mut String::new())?;
         }
-        Ok(())
     }
-}
-
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Advanced Audio Recorder CLI");
-    println!("=========================");
-    print_help();
-
-    let silero = SileroVad::new("./resources/models/silero_vad_v4.onnx", 0.5)?;
-    let smoothed_vad = SmoothedVad::new(Box::new(silero), 15, 15);
-    let recorder = AudioRecorder::new()?.with_vad(Box::new(smoothed_vad));
-    let mut state = RecorderState::new(recorder);
-
-    let mut devices = list_input_devices()?;
-    print_devices(&devices);
 
-    loop {
-        print!("[{}] > ", state.mode);
-        io::stdout().flush()?;
+    let samples = recorder.stop()?;
+    recorder.close()?;
+    if let Some(trace) = trace {
+        trace.report(args.csv.as_deref())?;
+    }
+    if samples.is_empty() {
+        println!("No audio kept.");
+        return Ok(());
+    }
+    save_audio(&samples, output)?;
+    println!(
+        "Saved {:.1}s to {}",
+        samples.len() as f32 / WHISPER_SAMPLE_RATE as f32,
+        output.display()
+    );
+    Ok(())
+}
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        let parts: Vec<&str> = input.trim().split_whitespace().collect();
+/// Run the VAD over a file the way the recorder would.
+fn analyze(file: &Path, output: Option<&Path>, args: &VadArgs) -> CliResult<()> {
+    let samples = decode_audio_file(file)?;
+    let Some((mut vad, trace)) = args.build()? else {
+        return Err("Nothing to analyze with --vad off".into());
+    };
 
-        if parts.is_empty() {
-            continue;
+    let mut kept = Vec::new();
+    for frame in samples.chunks_exact(FRAME_SAMPLES) {
+        if let VadFrame::Speech(speech) = vad.push_frame(frame)? {
+            kept.extend_from_slice(speech);
         }
+    }
+    trace.report(args.csv.as_deref())?;
 
-        let command = parts[0].to_lowercase();
-
-        match command.as_str() {
-            "start" | "s" => {
-                let device_index = if parts.len() > 1 {
-                    match parts[1].parse::<usize>() {
-                        Ok(idx) => Some(idx),
-                        Err(_) => {
-                            println!("Invalid device index format. Usage: start [device_index]");
-                            continue;
-                        }
-                    }
-                } else {
-                    None
-                };
-
-                match state.start_recording(device_index, &devices) {
-                    Ok(_) => println!("Recording started successfully!"),
-                    Err(e) => println!("Error starting recording: {}", e),
-                }
-            }
-            "stop" => match state.stop_recording() {
-                Ok(samples) => {
-                    if !samples.is_empty() {
-                        let filename = format!("recording_{}.wav", state.recording_index);
-                        match save_audio(&samples, &filename) {
-                            Ok(_) => {
-                                println!("Recording saved as: {}", filename);
-                                state.recording_index += 1;
-                            }
-                            Err(e) => println!("Error saving recording: {}", e),
-                        }
-                    } else {
-                        println!("No audio data captured.");
-                    }
-                }
-                Err(e) => println!("Error stopping recording: {}", e),
-            },
-            "mode" => {
-                if parts.len() > 1 {
-                    let new_mode = match parts[1].to_lowercase().as_str() {
-                        "always" | "alwayson" | "always-on" | "a" => RecorderMode::AlwaysOn,
-                        "demand" | "ondemand" | "on-demand" | "d" => RecorderMode::OnDemand,
-                        _ => {
-                            println!("Invalid mode. Use 'always' or 'demand'");
-                            continue;
-                        }
-                    };
-                    match state.switch_mode(new_mode) {
-                        Ok(_) => {}
-                        Err(e) => println!("Error switching modes: {}", e),
-                    }
-                } else {
-                    println!("Current mode: {}", state.mode);
-                    println!("Usage: mode [always|demand]");
-                }
-            }
-            "devices" | "dev" => {
-                devices = list_input_devices()?;
-                print_devices(&devices);
-            }
-            "status" => {
-                println!("Status:");
-                println!("  Mode: {}", state.mode);
-                println!(
-                    "  Recording: {}",
-                    if state.is_recording { "Yes" } else { "No" }
-                );
-                println!(
-                    "  Recorder Open: {}",
-                    if state.is_open { "Yes" } else { "No" }
-                );
-                println!(
-                    "  Current Device: {}",
-                    state
-                        .current_device_index
-                        .map_or("None".to_string(), |i| i.to_string())
-                );
-                println!("  Next Recording: recording_{}.wav", state.recording_index);
-            }
-            "help" | "h" => {
-                print_help();
-            }
-            "quit" | "exit" | "q" => {
-                println!("Shutting down...");
-                match state.close() {
-                    Ok(_) => {
-                        if state.is_recording {
-                            println!(
-                                "Final recording saved as: recording_{}.wav",
-                                state.recording_index
-                            );
-                        }
-                    }
-                    Err(e) => println!("Error during shutdown: {}", e),
-                }
-                println!("Goodbye!");
-                break;
-            }
-            "" => {
-                // Empty input, continue
-            }
-            _ => {
-                println!(
-                    "Unknown command: '{}'. Type 'help' for available commands.",
-                    command
-                );
-            }
-        }
+    if let Some(output) = output {
+        save_audio(&kept, output)?;
+        println!("Speech written to {}", output.display());
     }
+    Ok(())
+}
 
+fn play(file: &Path, device: Option<usize>) -> CliResult<()> {
+    let stream_builder = match device {
+        Some(index) => {
+            OutputStreamBuilder::from_device(pick_device(list_output_devices()?, index)?)?
+        }
+        None => OutputStreamBuilder::from_default_device()?,
+    };
+    let stream_handle = stream_builder.open_stream()?;
+    let sink = rodio::play(stream_handle.mixer(), BufReader::new(File::open(file)?))?;
+    println!("Playing {}...", file.display());
+    sink.sleep_until_end();
     Ok(())
 }
 
-fn print_help() {
-    println!("Commands:");
-    println!(
-        "  start [device_index] | s [device_index]  - Start recording (optionally with device)"
-    );
-    println!("  stop                                      - Stop recording and save");
-    println!(
-        "  mode [always|demand]                      - Switch recording mode or show current mode"
-    );
-    println!("  devices | dev                             - List available audio devices");
-    println!("  status                                    - Show current recorder status");
-    println!("  help | h                                  - Show this help message");
-    println!("  quit | exit | q                           - Exit the program");
-    println!();
-    println!("Modes:");
-    println!("  Always-On: Keeps recorder open for quick start/stop cycles");
-    println!("  On-Demand: Opens/closes recorder for each recording session");
-    println!();
+fn pick_device(devices: Vec<CpalDeviceInfo>, index: usize) -> CliResult<cpal::Device> {
+    let count = devices.len();
+    let info = devices
+        .into_iter()
+        .nth(index)
+        .ok_or_else(|| format!("Invalid device index: {} ({} devices)", index, count))?;
+    println!("Using {}", info.name);
+    Ok(info.device)
 }
 
 fn print_devices(devices: &[CpalDeviceInfo]) {
-    println!("Available audio devices:");
     for (index, device) in devices.iter().enumerate() {
-        println!("  {}: {}", index, device.name);
+        let default = if device.is_default { " (default)" } else { "" };
+        println!("  {}: {}{}", index, device.name, default);
     }
-    println!();
 }
 
-fn save_audio(samples: &[f32], filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+fn save_audio(samples: &[f32], path: &Path) -> CliResult<()> {
     let spec = hound::WavSpec {
         channels: 1,
-        sample_rate: 16000,
+        sample_rate: WHISPER_SAMPLE_RATE,
         bits_per_sample: 16,
         sample_format: hound::SampleFormat::Int,
     };
 
-    let mut writer = WavWriter::create(filename, spec)?;
+    let mut writer = WavWriter::create(path, spec)?;
 
     for &sample in samples {
         let sample_i16 = (sample * i16::MAX as f32) as i16;
@@ -355,3 +370,39 @@ fn save_audio(samples: &[f32], filename: &str) -> Result<(), Box<dyn std::error:
     writer.finalize()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Calls every frame speech.
+    struct AllSpeech;
+
+    impl VoiceActivityDetector for AllSpeech {
+        fn push_frame<'a>(&'a mut self, frame: &'a [f32]) -> anyhow::Result<VadFrame<'a>> {
+            Ok(VadFrame::Speech(frame))
+        }
+    }
+
+    #[test]
+    fn test_csv_lists_both_decisions_per_frame() {
+        let mut out = Vec::new();
+        write_csv(&mut out, &[false, true, true], Some(&[false, false, true])).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "frame,start_ms,silero,smoothed\n0,0,0,0\n1,30,1,0\n2,60,1,1\n"
+        );
+    }
+
+    #[test]
+    fn test_tap_logs_what_the_vad_decided() {
+        let log = DecisionLog::default();
+        let mut tap = Tap {
+            inner: Box::new(AllSpeech),
+            log: Arc::clone(&log),
+        };
+        let frame = [0.0; FRAME_SAMPLES];
+        assert!(tap.push_frame(&frame).unwrap().is_speech());
+        assert_eq!(*log.lock().unwrap(), vec![true]);
+    }
+}
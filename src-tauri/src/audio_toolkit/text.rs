@@ -283,6 +283,84 @@ pub fn filter_transcription_output(text: &str) -> String {
     filtered.trim().to_string()
 }
 
+/// Longest run of words the start of an overlapping transcript may repeat
+const MAX_OVERLAP_WORDS: usize = 8;
+/// The same for scripts written without spaces, counted in characters
+const MAX_OVERLAP_CHARS: usize = 16;
+
+/// Scripts written without spaces between words
+fn is_unspaced_script(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3000}'..='\u{30FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{FF00}'..='\u{FFEF}'
+    )
+}
+
+fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Joins the transcripts of two consecutive pieces of one recording.
+///
+/// When the pieces overlap in time, `next` tends to begin by repeating the
+/// last words of `prev`; the longest such repetition is dropped. Pieces cut
+/// at a pause don't overlap, so their text is joined as is.
+pub fn stitch_transcripts(prev: &str, next: &str, overlapping: bool) -> String {
+    let prev = prev.trim();
+    let next = next.trim();
+    if prev.is_empty() || next.is_empty() {
+        return format!("{}{}", prev, next);
+    }
+
+    let unspaced = prev.chars().last().is_some_and(is_unspaced_script)
+        && next.chars().next().is_some_and(is_unspaced_script);
+
+    let rest = if !overlapping {
+        next.to_string()
+    } else if unspaced {
+        let prev_chars: Vec<char> = prev.chars().collect();
+        let next_chars: Vec<char> = next.chars().collect();
+        let longest = MAX_OVERLAP_CHARS
+            .min(prev_chars.len())
+            .min(next_chars.len());
+        let repeated = (2..=longest)
+            .rev()
+            .find(|&k| prev_chars[prev_chars.len() - k..] == next_chars[..k])
+            .unwrap_or(0);
+        next_chars[repeated..].iter().collect()
+    } else {
+        let prev_words: Vec<String> = prev.split_whitespace().map(normalize_word).collect();
+        let next_words: Vec<&str> = next.split_whitespace().collect();
+        let longest = MAX_OVERLAP_WORDS
+            .min(prev_words.len())
+            .min(next_words.len());
+        let repeated = (1..=longest)
+            .rev()
+            .find(|&k| {
+                prev_words[prev_words.len() - k..]
+                    .iter()
+                    .zip(&next_words[..k])
+                    .all(|(p, n)| !p.is_empty() && *p == normalize_word(n))
+            })
+            .unwrap_or(0);
+        next_words[repeated..].join(" ")
+    };
+
+    if rest.is_empty() {
+        prev.to_string()
+    } else if unspaced {
+        format!("{}{}", prev, rest)
+    } else {
+        format!("{} {}", prev, rest)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -458,4 +536,22 @@ mod tests {
             result
         );
     }
+
+    #[test]
+    fn test_stitch_transcripts_drops_repeated_overlap() {
+        assert_eq!(
+            stitch_transcripts("we should ship the new", "The new release on Monday.", true),
+            "we should ship the new release on Monday."
+        );
+        // Without overlap a repeated word is meant
+        assert_eq!(
+            stitch_transcripts("I said no.", "No, really.", false),
+            "I said no. No, really."
+        );
+        assert_eq!(
+            stitch_transcripts("我们明天发布新版本", "新版本然后休息", true),
+            "我们明天发布新版本然后休息"
+        );
+        assert_eq!(stitch_transcripts("", "hello", true), "hello");
+    }
 }
@@ -0,0 +1,9 @@
+use clap::Parser;
+use jotline_lib::headless::{self, HeadlessArgs};
+
+fn main() {
+    if let Err(e) = headless::run(HeadlessArgs::parse()) {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
+    }
+}
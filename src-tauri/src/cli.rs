@@ -19,6 +19,10 @@ pub struct CliArgs {
     #[arg(long)]
     pub toggle_post_process: bool,
 
+    /// Toggle transcription appended to the last or configured note (sent to running instance)
+    #[arg(long)]
+    pub toggle_append: bool,
+
     /// Cancel the current operation (sent to running instance)
     #[arg(long)]
     pub cancel: bool,
@@ -1,7 +1,7 @@
 use crate::input::{self, EnigoState};
 #[cfg(target_os = "linux")]
 use crate::settings::TypingTool;
-use crate::settings::{get_settings, AutoSubmitKey, ClipboardHandling, PasteMethod};
+use crate::settings::{get_settings, AppSettings, AutoSubmitKey, ClipboardHandling, PasteMethod};
 use enigo::{Direction, Enigo, Key, Keyboard};
 use log::info;
 use std::process::Command;
@@ -642,22 +642,149 @@ pub fn paste(text: String, app_handle: AppHandle) -> Result<(), String> {
         }
     }
 
-    if should_send_auto_submit(settings.auto_submit, paste_method) {
+    finish_paste(&mut enigo, &text, &settings, &app_handle)
+}
+
+/// Auto-submit and the clipboard copy, once the text is in the app.
+fn finish_paste(
+    enigo: &mut Enigo,
+    text: &str,
+    settings: &AppSettings,
+    app_handle: &AppHandle,
+) -> Result<(), String> {
+    if should_send_auto_submit(settings.auto_submit, settings.paste_method) {
         std::thread::sleep(Duration::from_millis(50));
-        send_return_key(&mut enigo, settings.auto_submit_key)?;
+        send_return_key(enigo, settings.auto_submit_key)?;
     }
 
     // After pasting, optionally copy to clipboard based on settings
     if settings.clipboard_handling == ClipboardHandling::CopyToClipboard {
         let clipboard = app_handle.clipboard();
         clipboard
-            .write_text(&text)
+            .write_text(text)
             .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
     }
 
     Ok(())
 }
 
+/// Types one piece of streamed text at the cursor. Pieces are always typed,
+/// whatever the paste method, since pasting each one would go through the
+/// clipboard dozens of times.
+pub fn type_text(text: &str, app_handle: &AppHandle) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    let typing_tool = get_settings(app_handle).typing_tool;
+    let enigo_state = app_handle
+        .try_state::<EnigoState>()
+        .ok_or("Enigo state not initialized")?;
+    let mut enigo = enigo_state
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to lock Enigo: {}", e))?;
+
+    paste_direct(
+        &mut enigo,
+        text,
+        #[cfg(target_os = "linux")]
+        typing_tool,
+    )
+}
+
+/// Ends text typed piece by piece with [`type_text`] the way [`paste`] ends
+/// a paste: trailing space, auto-submit and clipboard copy.
+pub fn finish_typed_text(text: String, app_handle: AppHandle) -> Result<(), String> {
+    let settings = get_settings(&app_handle);
+    let enigo_state = app_handle
+        .try_state::<EnigoState>()
+        .ok_or("Enigo state not initialized")?;
+    let mut enigo = enigo_state
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to lock Enigo: {}", e))?;
+
+    let text = if settings.append_trailing_space {
+        paste_direct(
+            &mut enigo,
+            " ",
+            #[cfg(target_os = "linux")]
+            settings.typing_tool,
+        )?;
+        format!("{} ", text)
+    } else {
+        text
+    };
+
+    finish_paste(&mut enigo, &text, &settings, &app_handle)
+}
+
+/// The text selected in the focused app, or "" when there is none. On
+/// Linux this is the primary selection. Elsewhere the selection is copied
+/// and the clipboard put back afterwards. Must run on the main thread, like
+/// [`paste`].
+pub fn read_selected_text(app_handle: &AppHandle) -> Result<String, String> {
+    #[cfg(target_os = "linux")]
+    {
+        let _ = app_handle;
+        read_primary_selection()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let enigo_state = app_handle
+            .try_state::<EnigoState>()
+            .ok_or("Enigo state not initialized")?;
+        let mut enigo = enigo_state
+            .0
+            .lock()
+            .map_err(|e| format!("Failed to lock Enigo: {}", e))?;
+
+        let clipboard = app_handle.clipboard();
+        let clipboard_content = clipboard.read_text().unwrap_or_default();
+        let mut copy_selection = || {
+            // Cleared first, so that nothing selected does not read as the
+            // previous clipboard
+            clipboard
+                .write_text("")
+                .map_err(|e| format!("Failed to clear clipboard: {}", e))?;
+            input::send_copy(&mut enigo)?;
+            std::thread::sleep(Duration::from_millis(100));
+            Ok::<_, String>(clipboard.read_text().unwrap_or_default())
+        };
+        // Put back whether or not the copy worked
+        let selected = copy_selection();
+
+        clipboard
+            .write_text(&clipboard_content)
+            .map_err(|e| format!("Failed to restore clipboard: {}", e))?;
+        selected
+    }
+}
+
+/// Reads the primary selection with whichever of wl-paste, xclip and xsel
+/// is installed.
+#[cfg(target_os = "linux")]
+fn read_primary_selection() -> Result<String, String> {
+    let tools: &[(&str, &[&str])] = if is_wayland() {
+        &[("wl-paste", &["--primary", "--no-newline"])]
+    } else {
+        &[
+            ("xclip", &["-o", "-selection", "primary"]),
+            ("xsel", &["-o", "-p"]),
+        ]
+    };
+    for (tool, args) in tools {
+        match Command::new(tool).args(*args).output() {
+            Ok(output) if output.status.success() => {
+                return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+            }
+            // wl-paste fails when the selection is empty
+            Ok(_) => return Ok(String::new()),
+            Err(_) => continue,
+        }
+    }
+    Err("No tool to read the primary selection (wl-paste, xclip or xsel)".into())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,44 @@
+use crate::managers::control::ControlManager;
+use crate::settings::{get_settings, write_settings};
+use serde::Serialize;
+use specta::Type;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ControlSocketStatus {
+    pub running: bool,
+    /// Socket path, or pipe name on Windows, that clients connect to
+    pub endpoint: Option<String>,
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_control_socket_status(
+    control_manager: State<'_, Arc<ControlManager>>,
+) -> Result<ControlSocketStatus, String> {
+    Ok(ControlSocketStatus {
+        running: control_manager.is_running(),
+        endpoint: control_manager.endpoint().ok(),
+    })
+}
+
+/// Turn the JSON-RPC control socket on or off, effective immediately.
+#[tauri::command]
+#[specta::specta]
+pub fn change_control_socket_setting(
+    app: AppHandle,
+    control_manager: State<'_, Arc<ControlManager>>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.control_socket_enabled = enabled;
+    write_settings(&app, settings);
+
+    if enabled {
+        control_manager.start().map_err(|e| e.to_string())
+    } else {
+        control_manager.stop();
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::actions::retranscribe_recording;
+use crate::managers::history::{HistoryEntry, HistoryManager, HistoryRevision};
+use crate::settings::{get_settings, write_settings};
+use crate::transcript::ExportFormat;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_history_entries(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    limit: u32,
+    offset: u32,
+) -> Result<Vec<HistoryEntry>, String> {
+    history_manager
+        .list_entries(limit as usize, offset as usize)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn search_history(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    query: String,
+    limit: u32,
+) -> Result<Vec<HistoryEntry>, String> {
+    history_manager
+        .search_entries(&query, limit as usize)
+        .map_err(|e| e.to_string())
+}
+
+/// Copy an entry to the clipboard: the final text, or the raw model output
+/// when `raw` is set.
+#[tauri::command]
+#[specta::specta]
+pub fn copy_history_entry(
+    app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    id: i64,
+    raw: bool,
+) -> Result<(), String> {
+    let entry = history_manager
+        .get_entry(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("History entry not found: {}", id))?;
+    let text = if raw {
+        &entry.raw_text
+    } else {
+        entry.final_text()
+    };
+    app.clipboard()
+        .write_text(text)
+        .map_err(|e| format!("Failed to write to clipboard: {}", e))
+}
+
+/// Write an entry's timed segments to `path` as subtitles or timestamped
+/// Markdown, adding the format's extension when `path` has none.
+#[tauri::command]
+#[specta::specta]
+pub fn export_history_transcript(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    id: i64,
+    format: ExportFormat,
+    path: String,
+) -> Result<(), String> {
+    let segments = history_manager
+        .get_segments(id)
+        .map_err(|e| e.to_string())?;
+    if segments.is_empty() {
+        return Err(format!("History entry {} has no timestamps", id));
+    }
+    let mut path = std::path::PathBuf::from(path);
+    if path.extension().is_none() {
+        path.set_extension(format.extension());
+    }
+    std::fs::write(&path, format.render(&segments))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_history_entry(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    id: i64,
+) -> Result<(), String> {
+    history_manager.delete_entry(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_history_revisions(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    entry_id: i64,
+) -> Result<Vec<HistoryRevision>, String> {
+    history_manager
+        .list_revisions(entry_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Transcribe the saved recording of a history entry again with another
+/// model, storing the result as a new revision.
+#[tauri::command]
+#[specta::specta]
+pub async fn retranscribe(
+    app: AppHandle,
+    recording_id: i64,
+    model_id: String,
+    post_process: bool,
+) -> Result<HistoryRevision, String> {
+    retranscribe_recording(&app, recording_id, &model_id, post_process).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_history_enabled_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.history_enabled = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_history_save_audio_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.history_save_audio = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_history_limit_setting(
+    app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    limit: u32,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.history_limit = limit;
+    write_settings(&app, settings);
+    history_manager.apply_retention().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_history_retention_days_setting(
+    app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    days: u32,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.history_retention_days = days;
+    write_settings(&app, settings);
+    history_manager.apply_retention().map_err(|e| e.to_string())
+}
@@ -1,6 +1,13 @@
 pub mod audio;
+pub mod control;
+pub mod history;
 pub mod models;
+pub mod notes;
+pub mod palette;
+pub mod projects;
+pub mod search;
 pub mod transcription;
+pub mod watch_folder;
 
 use crate::settings::{get_settings, write_settings, AppSettings, LogLevel};
 use crate::utils::cancel_current_operation;
@@ -9,7 +9,12 @@ use tauri::{AppHandle, State};
 pub async fn get_available_models(
     model_manager: State<'_, Arc<ModelManager>>,
 ) -> Result<Vec<ModelInfo>, String> {
-    Ok(model_manager.get_available_models())
+    // The embedding model is managed from the search settings, not the model picker
+    Ok(model_manager
+        .get_available_models()
+        .into_iter()
+        .filter(|m| m.engine_type.is_transcription())
+        .collect())
 }
 
 #[tauri::command]
@@ -75,6 +80,10 @@ pub async fn set_active_model(
         return Err(format!("Model not downloaded: {}", model_id));
     }
 
+    if !model_info.engine_type.is_transcription() {
+        return Err(format!("Not a transcription model: {}", model_id));
+    }
+
     // Load the model in the transcription manager
     transcription_manager
         .load_model(&model_id)
@@ -119,7 +128,9 @@ pub async fn has_any_models_available(
     model_manager: State<'_, Arc<ModelManager>>,
 ) -> Result<bool, String> {
     let models = model_manager.get_available_models();
-    Ok(models.iter().any(|m| m.is_downloaded))
+    Ok(models
+        .iter()
+        .any(|m| m.is_downloaded && m.engine_type.is_transcription()))
 }
 
 #[tauri::command]
@@ -129,7 +140,9 @@ pub async fn has_any_models_or_downloads(
 ) -> Result<bool, String> {
     let models = model_manager.get_available_models();
     // Return true if any models are downloaded OR if any downloads are in progress
-    Ok(models.iter().any(|m| m.is_downloaded))
+    Ok(models
+        .iter()
+        .any(|m| m.is_downloaded && m.engine_type.is_transcription()))
 }
 
 #[tauri::command]
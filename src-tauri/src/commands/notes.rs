@@ -0,0 +1,122 @@
+use crate::actions::transcribe_audio_file;
+use crate::managers::notes::{validate_append_target, Note, NoteInfo, NoteManager};
+use crate::settings::{get_settings, write_settings};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_notes(
+    note_manager: State<'_, Arc<NoteManager>>,
+) -> Result<Vec<NoteInfo>, String> {
+    note_manager.list_notes().map_err(|e| e.to_string())
+}
+
+/// Transcribe an audio file (WAV, FLAC, MP3, OGG or M4A) into a new note.
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_file(
+    app: AppHandle,
+    path: String,
+    post_process: bool,
+) -> Result<NoteInfo, String> {
+    transcribe_audio_file(&app, PathBuf::from(path), post_process).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_note(
+    note_manager: State<'_, Arc<NoteManager>>,
+    id: String,
+) -> Result<Note, String> {
+    note_manager.get_note(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_note(
+    note_manager: State<'_, Arc<NoteManager>>,
+    id: String,
+    content: String,
+) -> Result<NoteInfo, String> {
+    note_manager
+        .update_note(&id, &content)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_note(
+    note_manager: State<'_, Arc<NoteManager>>,
+    id: String,
+) -> Result<(), String> {
+    note_manager.delete_note(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_notes_dir_path(note_manager: State<'_, Arc<NoteManager>>) -> Result<String, String> {
+    note_manager
+        .notes_dir()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .map_err(|e| format!("Failed to get notes directory: {}", e))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_notes_directory_setting(app: AppHandle, path: Option<String>) -> Result<(), String> {
+    if let Some(dir) = path.as_deref().filter(|p| !p.trim().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create notes directory: {}", e))?;
+    }
+
+    let mut settings = get_settings(&app);
+    settings.notes_directory = path;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_paste_after_capture_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.paste_after_capture = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_capture_context_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.capture_context = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// Set the Markdown file the append shortcut adds to, or `None` to append to
+/// the most recently modified note. Relative paths are inside the notes
+/// directory.
+#[tauri::command]
+#[specta::specta]
+pub fn change_append_target_setting(app: AppHandle, path: Option<String>) -> Result<(), String> {
+    let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if let Some(path) = path.as_deref() {
+        validate_append_target(path).map_err(|e| e.to_string())?;
+    }
+
+    let mut settings = get_settings(&app);
+    settings.append_target = path;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_auto_tag_notes_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.auto_tag_notes = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use crate::palette::{self, PaletteEntry};
+use crate::settings::{get_settings, write_settings};
+use crate::TranscriptionCoordinator;
+use tauri::{AppHandle, State};
+
+/// Entries of the command palette, in display order.
+#[tauri::command]
+#[specta::specta]
+pub fn get_palette_entries() -> Vec<PaletteEntry> {
+    PaletteEntry::ALL.to_vec()
+}
+
+/// Run the entry picked in the palette and close it. Voice and Append start
+/// recording; the next press of the hotkey stops it.
+#[tauri::command]
+#[specta::specta]
+pub fn run_palette_entry(
+    app: AppHandle,
+    coordinator: State<'_, TranscriptionCoordinator>,
+    entry: PaletteEntry,
+) -> Result<(), String> {
+    coordinator.run_palette_entry(entry);
+    palette::run_entry(&app, entry).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn close_palette(coordinator: State<'_, TranscriptionCoordinator>) {
+    coordinator.close_palette();
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_command_palette_enabled_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.command_palette_enabled = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_tap_threshold_ms_setting(app: AppHandle, threshold_ms: u32) -> Result<(), String> {
+    if !(50..=2000).contains(&threshold_ms) {
+        return Err(format!(
+            "Tap threshold must be between 50 and 2000 ms, got {}",
+            threshold_ms
+        ));
+    }
+    let mut settings = get_settings(&app);
+    settings.tap_threshold_ms = threshold_ms;
+    write_settings(&app, settings);
+    Ok(())
+}
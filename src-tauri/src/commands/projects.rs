@@ -0,0 +1,41 @@
+use crate::managers::notes::{NoteInfo, NoteManager, ProjectInfo};
+use crate::settings::{get_settings, write_settings};
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_projects(
+    note_manager: State<'_, Arc<NoteManager>>,
+) -> Result<Vec<ProjectInfo>, String> {
+    note_manager.list_projects().map_err(|e| e.to_string())
+}
+
+/// Move a note to another project. `project: None` moves it out of any project.
+#[tauri::command]
+#[specta::specta]
+pub async fn move_note(
+    note_manager: State<'_, Arc<NoteManager>>,
+    id: String,
+    project: Option<String>,
+) -> Result<NoteInfo, String> {
+    note_manager
+        .move_note(&id, project.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// File new notes under `project` regardless of the detected workspace.
+/// Pass `None` to go back to automatic detection.
+#[tauri::command]
+#[specta::specta]
+pub fn change_active_project_setting(
+    app: AppHandle,
+    project: Option<String>,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.active_project = project
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty());
+    write_settings(&app, settings);
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use crate::managers::search::{NoteSearchResult, SearchManager, SearchStatus};
+use crate::managers::text_search::{TextSearchManager, TextSearchResult};
+use crate::settings;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+/// Find the `k` notes closest in meaning to `query`. Runs fully offline
+/// against the local embedding model.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_notes(
+    search_manager: State<'_, Arc<SearchManager>>,
+    query: String,
+    k: u32,
+) -> Result<Vec<NoteSearchResult>, String> {
+    search_manager
+        .search(&query, k as usize)
+        .map_err(|e| e.to_string())
+}
+
+/// Find notes and history entries containing the words of `query`, the last
+/// one as a prefix, with a highlighted snippet around the first match.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_text(
+    text_search_manager: State<'_, Arc<TextSearchManager>>,
+    query: String,
+    limit: u32,
+) -> Result<Vec<TextSearchResult>, String> {
+    text_search_manager
+        .search(&query, limit as usize)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_search_status(search_manager: State<'_, Arc<SearchManager>>) -> SearchStatus {
+    search_manager.status()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_auto_link_notes_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.auto_link_notes = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_auto_link_threshold_setting(app: AppHandle, threshold: f64) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(format!(
+            "Auto-link threshold must be between 0 and 1, got {}",
+            threshold
+        ));
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.auto_link_threshold = threshold;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_auto_link_max_links_setting(app: AppHandle, max_links: u32) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.auto_link_max_links = max_links;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
@@ -18,6 +18,30 @@ pub fn set_model_unload_timeout(app: AppHandle, timeout: ModelUnloadTimeout) {
     write_settings(&app, settings);
 }
 
+#[tauri::command]
+#[specta::specta]
+pub fn change_dictation_queue_depth_setting(app: AppHandle, depth: u32) -> Result<(), String> {
+    if !(1..=20).contains(&depth) {
+        return Err(format!(
+            "Dictation queue depth must be between 1 and 20, got {}",
+            depth
+        ));
+    }
+    let mut settings = get_settings(&app);
+    settings.dictation_queue_depth = depth;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_live_transcription_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.live_transcription = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn get_model_load_status(
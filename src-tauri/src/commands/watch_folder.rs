@@ -0,0 +1,56 @@
+use crate::managers::watch_folder::{WatchFolderManager, WatchFolderStatus};
+use crate::settings::{get_settings, write_settings};
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_watch_folder_status(
+    watch_folder_manager: State<'_, Arc<WatchFolderManager>>,
+) -> Result<WatchFolderStatus, String> {
+    Ok(watch_folder_manager.status())
+}
+
+/// Set the folder whose audio files are transcribed into notes, or `None` to
+/// stop watching.
+#[tauri::command]
+#[specta::specta]
+pub fn change_watch_folder_setting(app: AppHandle, path: Option<String>) -> Result<(), String> {
+    let path = path.filter(|p| !p.trim().is_empty());
+    if let Some(path) = path.as_deref().map(std::path::Path::new) {
+        if !path.is_absolute() {
+            return Err(format!("Watch folder must be an absolute path: {:?}", path));
+        }
+        if !path.is_dir() {
+            return Err(format!("Watch folder is not a directory: {:?}", path));
+        }
+    }
+
+    let mut settings = get_settings(&app);
+    settings.watch_folder = path;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_watch_folder_post_process_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.watch_folder_post_process = enabled;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// Transcribe a file that failed again now instead of waiting for its next
+/// retry.
+#[tauri::command]
+#[specta::specta]
+pub fn retry_watch_folder_file(
+    watch_folder_manager: State<'_, Arc<WatchFolderManager>>,
+    path: String,
+) -> Result<(), String> {
+    watch_folder_manager.retry(&path).map_err(|e| e.to_string())
+}
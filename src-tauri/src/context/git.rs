@@ -0,0 +1,94 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Walk up from `start` until a directory containing `.git` is found.
+/// `.git` may be a directory or, for worktrees and submodules, a file.
+pub fn find_git_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Read the checked-out branch from `.git/HEAD` without shelling out to git.
+/// Detached heads are reported as the abbreviated commit hash.
+pub fn read_git_branch(repo_root: &Path) -> Option<String> {
+    let git_dir = resolve_git_dir(repo_root)?;
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+
+    if let Some(reference) = head.strip_prefix("ref:") {
+        let reference = reference.trim();
+        return Some(
+            reference
+                .strip_prefix("refs/heads/")
+                .unwrap_or(reference)
+                .to_string(),
+        );
+    }
+
+    if head.len() >= 7 && head.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Some(head[..7].to_string());
+    }
+
+    None
+}
+
+/// Resolve the actual git directory, following `gitdir:` pointer files.
+fn resolve_git_dir(repo_root: &Path) -> Option<PathBuf> {
+    let dot_git = repo_root.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+
+    let pointer = fs::read_to_string(&dot_git).ok()?;
+    let target = pointer.trim().strip_prefix("gitdir:")?.trim();
+    let target = Path::new(target);
+    Some(if target.is_absolute() {
+        target.to_path_buf()
+    } else {
+        repo_root.join(target)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_branch_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path();
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(repo.join("a/b")).unwrap();
+
+        fs::write(repo.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        assert_eq!(find_git_root(&repo.join("a/b")).as_deref(), Some(repo));
+        assert_eq!(read_git_branch(repo).as_deref(), Some("main"));
+
+        fs::write(
+            repo.join(".git/HEAD"),
+            "3f786850e387550fdab836ed7e6dc881de23001b\n",
+        )
+        .unwrap();
+        assert_eq!(read_git_branch(repo).as_deref(), Some("3f78685"));
+    }
+
+    #[test]
+    fn test_worktree_pointer_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let real_git_dir = temp_dir.path().join("main/.git/worktrees/wt");
+        let worktree = temp_dir.path().join("wt");
+        fs::create_dir_all(&real_git_dir).unwrap();
+        fs::create_dir_all(&worktree).unwrap();
+        fs::write(real_git_dir.join("HEAD"), "ref: refs/heads/wip\n").unwrap();
+        fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", real_git_dir.display()),
+        )
+        .unwrap();
+
+        assert_eq!(read_git_branch(&worktree).as_deref(), Some("wip"));
+    }
+}
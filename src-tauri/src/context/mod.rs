@@ -0,0 +1,372 @@
+//! Context capture for dictated notes.
+//!
+//! When a recording starts, every registered [`ContextProvider`] is asked to
+//! contribute key/value pairs describing what the user was doing (focused
+//! window, working directory, Git branch, ...). The collected
+//! [`CaptureContext`] is held until the transcription finishes and is then
+//! written to the note as YAML frontmatter.
+
+mod git;
+mod project;
+mod window;
+
+pub use git::{find_git_root, read_git_branch};
+pub use project::detect_project;
+pub use window::{SystemWindowInfo, WindowInfo, WindowInfoSource};
+
+use anyhow::Result;
+use chrono::Local;
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+use crate::notes::Frontmatter;
+
+/// Everything the providers learned about the moment a capture started.
+#[derive(Debug, Clone, Default)]
+pub struct CaptureContext {
+    entries: Vec<(String, String)>,
+    /// Focused window at capture time, shared between providers
+    pub window: Option<WindowInfo>,
+    /// Working directory resolved by [`WorkingDirectoryProvider`]
+    pub working_dir: Option<PathBuf>,
+}
+
+impl CaptureContext {
+    /// Insert or replace a value, keeping insertion order for stable frontmatter.
+    pub fn set(&mut self, key: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self.entries.iter_mut().find(|(k, _)| k == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.entries.push((key.to_string(), value)),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn entries(&self) -> &[(String, String)] {
+        &self.entries
+    }
+
+    pub fn to_frontmatter(&self) -> Frontmatter {
+        let mut frontmatter = Frontmatter::new();
+        for (key, value) in &self.entries {
+            frontmatter.set_text(key, value.clone());
+        }
+        frontmatter
+    }
+}
+
+/// A source of capture context. Providers run in registration order and may
+/// read what earlier providers stored in the [`CaptureContext`].
+pub trait ContextProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn collect(&self, context: &mut CaptureContext) -> Result<()>;
+}
+
+/// Records when the capture started.
+pub struct TimestampProvider;
+
+impl ContextProvider for TimestampProvider {
+    fn name(&self) -> &'static str {
+        "timestamp"
+    }
+
+    fn collect(&self, context: &mut CaptureContext) -> Result<()> {
+        context.set(
+            "captured_at",
+            Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        );
+        Ok(())
+    }
+}
+
+/// Records the title and application of the focused window.
+pub struct FocusedWindowProvider {
+    source: Arc<dyn WindowInfoSource>,
+}
+
+impl FocusedWindowProvider {
+    pub fn new(source: Arc<dyn WindowInfoSource>) -> Self {
+        Self { source }
+    }
+}
+
+impl ContextProvider for FocusedWindowProvider {
+    fn name(&self) -> &'static str {
+        "focused_window"
+    }
+
+    fn collect(&self, context: &mut CaptureContext) -> Result<()> {
+        let Some(window) = self.source.focused_window() else {
+            return Ok(());
+        };
+        if !window.app_name.is_empty() {
+            context.set("app_name", window.app_name.clone());
+        }
+        if !window.title.is_empty() {
+            context.set("window_title", window.title.clone());
+        }
+        context.window = Some(window);
+        Ok(())
+    }
+}
+
+/// Records the working directory of the focused application along with its
+/// Git repository and branch. Must run after [`FocusedWindowProvider`] to
+/// see the focused process; without one nothing is recorded.
+pub struct WorkingDirectoryProvider {
+    process_cwd: Box<dyn Fn(u32) -> Option<PathBuf> + Send + Sync>,
+}
+
+impl WorkingDirectoryProvider {
+    pub fn new() -> Self {
+        Self::with_resolver(window::process_cwd)
+    }
+
+    /// Resolve working directories with `process_cwd` instead of asking the
+    /// OS about the process
+    pub fn with_resolver(
+        process_cwd: impl Fn(u32) -> Option<PathBuf> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            process_cwd: Box::new(process_cwd),
+        }
+    }
+}
+
+impl Default for WorkingDirectoryProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContextProvider for WorkingDirectoryProvider {
+    fn name(&self) -> &'static str {
+        "working_directory"
+    }
+
+    fn collect(&self, context: &mut CaptureContext) -> Result<()> {
+        let Some(dir) = context
+            .window
+            .as_ref()
+            .and_then(|w| w.pid)
+            .and_then(|pid| (self.process_cwd)(pid))
+            // GUI apps usually report `/`, which says nothing about the user's work
+            .filter(|dir| dir.parent().is_some())
+        else {
+            return Ok(());
+        };
+
+        context.set("cwd", dir.to_string_lossy().to_string());
+        if let Some(root) = find_git_root(&dir) {
+            if let Some(branch) = read_git_branch(&root) {
+                context.set("git_branch", branch);
+            }
+            context.set("git_root", root.to_string_lossy().to_string());
+        }
+        context.working_dir = Some(dir);
+        Ok(())
+    }
+}
+
+/// Assigns the capture to a project based on the working directory. Must run
+/// after [`WorkingDirectoryProvider`].
+pub struct ProjectProvider;
+
+impl ContextProvider for ProjectProvider {
+    fn name(&self) -> &'static str {
+        "project"
+    }
+
+    fn collect(&self, context: &mut CaptureContext) -> Result<()> {
+        if let Some(project) = context.working_dir.as_deref().and_then(detect_project) {
+            context.set("project", project);
+        }
+        Ok(())
+    }
+}
+
+/// Runs the context providers at the start of a recording and keeps the
+/// result until the matching stop. Providers may spawn processes, so they
+/// run on a thread of their own rather than delaying the recording.
+pub struct ContextManager {
+    providers: Arc<Vec<Box<dyn ContextProvider>>>,
+    pending: Mutex<HashMap<String, JoinHandle<CaptureContext>>>,
+}
+
+impl ContextManager {
+    pub fn new() -> Self {
+        let window_source: Arc<dyn WindowInfoSource> = Arc::new(SystemWindowInfo);
+        Self::with_providers(vec![
+            Box::new(TimestampProvider),
+            Box::new(FocusedWindowProvider::new(window_source)),
+            Box::new(WorkingDirectoryProvider::new()),
+            Box::new(ProjectProvider),
+        ])
+    }
+
+    pub fn with_providers(providers: Vec<Box<dyn ContextProvider>>) -> Self {
+        Self {
+            providers: Arc::new(providers),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run every provider. A failing provider is logged and skipped.
+    pub fn collect(&self) -> CaptureContext {
+        collect_with(&self.providers)
+    }
+
+    /// Start collecting context for a recording started by `binding_id`.
+    pub fn capture(&self, binding_id: &str) {
+        let providers = Arc::clone(&self.providers);
+        let handle = thread::spawn(move || collect_with(&providers));
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(binding_id.to_string(), handle);
+    }
+
+    /// Take the context captured for `binding_id`, if any, waiting for the
+    /// providers to finish.
+    pub fn take(&self, binding_id: &str) -> Option<CaptureContext> {
+        let handle = self.pending.lock().unwrap().remove(binding_id)?;
+        match handle.join() {
+            Ok(context) => Some(context),
+            Err(_) => {
+                warn!("Context capture for '{}' panicked", binding_id);
+                None
+            }
+        }
+    }
+}
+
+fn collect_with(providers: &[Box<dyn ContextProvider>]) -> CaptureContext {
+    let start = Instant::now();
+    let mut context = CaptureContext::default();
+    for provider in providers {
+        if let Err(e) = provider.collect(&mut context) {
+            warn!("Context provider '{}' failed: {}", provider.name(), e);
+        }
+    }
+    debug!(
+        "Collected {} context entries in {:?}",
+        context.entries().len(),
+        start.elapsed()
+    );
+    context
+}
+
+impl Default for ContextManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    struct FakeWindowSource(Option<WindowInfo>);
+
+    impl WindowInfoSource for FakeWindowSource {
+        fn focused_window(&self) -> Option<WindowInfo> {
+            self.0.clone()
+        }
+    }
+
+    fn fake_window(title: &str) -> Arc<dyn WindowInfoSource> {
+        Arc::new(FakeWindowSource(Some(WindowInfo {
+            title: title.to_string(),
+            app_name: "code".to_string(),
+            pid: Some(42),
+        })))
+    }
+
+    #[test]
+    fn test_collects_window_and_git_context() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("jotline");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(repo.join("src")).unwrap();
+        fs::write(repo.join(".git/HEAD"), "ref: refs/heads/feature/notes\n").unwrap();
+
+        let manager = ContextManager::with_providers(vec![
+            Box::new(TimestampProvider),
+            Box::new(FocusedWindowProvider::new(fake_window("lib.rs - jotline"))),
+            Box::new(WorkingDirectoryProvider::with_resolver({
+                let src = repo.join("src");
+                move |pid| (pid == 42).then(|| src.clone())
+            })),
+            Box::new(ProjectProvider),
+        ]);
+        let context = manager.collect();
+
+        assert!(context.get("captured_at").is_some());
+        assert_eq!(context.get("window_title"), Some("lib.rs - jotline"));
+        assert_eq!(context.get("app_name"), Some("code"));
+        assert_eq!(context.get("git_branch"), Some("feature/notes"));
+        assert_eq!(context.get("project"), Some("jotline"));
+        assert_eq!(
+            context.get("git_root"),
+            Some(repo.to_string_lossy().as_ref())
+        );
+
+        let keys: Vec<_> = context.entries().iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "captured_at",
+                "app_name",
+                "window_title",
+                "cwd",
+                "git_branch",
+                "git_root",
+                "project"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_window_is_not_an_error() {
+        let manager = ContextManager::with_providers(vec![Box::new(FocusedWindowProvider::new(
+            Arc::new(FakeWindowSource(None)),
+        ))]);
+        let context = manager.collect();
+        assert!(context.entries().is_empty());
+        assert!(context.window.is_none());
+    }
+
+    #[test]
+    fn test_no_working_directory_without_a_focused_process() {
+        let manager = ContextManager::with_providers(vec![
+            Box::new(FocusedWindowProvider::new(Arc::new(FakeWindowSource(None)))),
+            Box::new(WorkingDirectoryProvider::with_resolver(|_| {
+                Some(PathBuf::from("/home/user"))
+            })),
+        ]);
+        let context = manager.collect();
+        assert!(context.get("cwd").is_none());
+        assert!(context.working_dir.is_none());
+    }
+
+    #[test]
+    fn test_capture_and_take_by_binding() {
+        let manager = ContextManager::with_providers(vec![Box::new(TimestampProvider)]);
+        manager.capture("transcribe");
+        assert!(manager.take("transcribe_with_post_process").is_none());
+        assert!(manager.take("transcribe").is_some());
+        assert!(manager.take("transcribe").is_none());
+    }
+}
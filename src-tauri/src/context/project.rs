@@ -0,0 +1,69 @@
+use std::fs;
+use std::path::Path;
+
+/// Marker file that pins a directory to a project. An empty marker uses the
+/// directory name; otherwise the first non-comment line is the project name.
+pub const PROJECT_MARKER: &str = ".jotline";
+
+/// Find the project that `start` belongs to. The closest `.jotline` marker or
+/// Git root wins, so a marker inside a monorepo overrides the repository name.
+pub fn detect_project(start: &Path) -> Option<String> {
+    for dir in start.ancestors() {
+        let marker = dir.join(PROJECT_MARKER);
+        if marker.is_file() {
+            let named = fs::read_to_string(&marker).ok().and_then(|content| {
+                content
+                    .lines()
+                    .map(str::trim)
+                    .find(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string)
+            });
+            return named.or_else(|| dir_name(dir));
+        }
+        if dir.join(".git").exists() {
+            return dir_name(dir);
+        }
+    }
+    None
+}
+
+fn dir_name(dir: &Path) -> Option<String> {
+    dir.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_marker_and_git_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("monorepo");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(repo.join("apps/web/src")).unwrap();
+        fs::create_dir_all(repo.join("apps/api")).unwrap();
+        fs::create_dir_all(repo.join("docs")).unwrap();
+        fs::write(
+            repo.join("apps/web/.jotline"),
+            "# project name\nWeb Client\n",
+        )
+        .unwrap();
+        fs::write(repo.join("apps/api/.jotline"), "").unwrap();
+
+        assert_eq!(
+            detect_project(&repo.join("apps/web/src")).as_deref(),
+            Some("Web Client")
+        );
+        assert_eq!(
+            detect_project(&repo.join("apps/api")).as_deref(),
+            Some("api")
+        );
+        assert_eq!(
+            detect_project(&repo.join("docs")).as_deref(),
+            Some("monorepo")
+        );
+        assert_eq!(detect_project(temp_dir.path()), None);
+    }
+}
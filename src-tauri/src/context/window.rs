@@ -0,0 +1,174 @@
+use std::path::PathBuf;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::process::Command;
+
+/// The window that had focus when a capture started.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowInfo {
+    pub title: String,
+    pub app_name: String,
+    pub pid: Option<u32>,
+}
+
+/// Abstraction over the platform's "which window is focused" query so the
+/// providers can be tested without a display server.
+pub trait WindowInfoSource: Send + Sync {
+    fn focused_window(&self) -> Option<WindowInfo>;
+}
+
+/// Queries the focused window using the native mechanism of each platform.
+pub struct SystemWindowInfo;
+
+impl WindowInfoSource for SystemWindowInfo {
+    fn focused_window(&self) -> Option<WindowInfo> {
+        focused_window()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// X11 via xdotool. Wayland compositors do not expose the focused window to
+/// regular clients, so this returns `None` there unless XWayland has focus.
+#[cfg(target_os = "linux")]
+fn focused_window() -> Option<WindowInfo> {
+    let title = command_output("xdotool", &["getactivewindow", "getwindowname"])?;
+    let pid = command_output("xdotool", &["getactivewindow", "getwindowpid"])
+        .and_then(|pid| pid.parse::<u32>().ok());
+    let app_name = pid
+        .and_then(|pid| std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok())
+        .map(|name| name.trim().to_string())
+        .unwrap_or_default();
+
+    Some(WindowInfo {
+        title,
+        app_name,
+        pid,
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn focused_window() -> Option<WindowInfo> {
+    // Window titles require the accessibility permission, which Jotline
+    // already needs for pasting
+    let script = r#"tell application "System Events"
+    set frontApp to first application process whose frontmost is true
+    set appName to name of frontApp
+    set appPid to unix id of frontApp
+    set windowTitle to ""
+    try
+        set windowTitle to name of front window of frontApp
+    end try
+    return appName & linefeed & appPid & linefeed & windowTitle
+end tell"#;
+    let output = command_output("osascript", &["-e", script])?;
+    let mut lines = output.lines();
+    let app_name = lines.next().unwrap_or_default().to_string();
+    let pid = lines.next().and_then(|pid| pid.trim().parse::<u32>().ok());
+    let title = lines.next().unwrap_or_default().to_string();
+
+    Some(WindowInfo {
+        title,
+        app_name,
+        pid,
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn focused_window() -> Option<WindowInfo> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId,
+    };
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return None;
+        }
+
+        let mut title_buf = [0u16; 512];
+        let len = GetWindowTextW(hwnd, &mut title_buf);
+        let title = String::from_utf16_lossy(&title_buf[..len.max(0) as usize]);
+
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+
+        let mut app_name = String::new();
+        if pid != 0 {
+            if let Ok(process) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+                let mut path_buf = [0u16; 1024];
+                let mut size = path_buf.len() as u32;
+                if QueryFullProcessImageNameW(
+                    process,
+                    PROCESS_NAME_WIN32,
+                    PWSTR(path_buf.as_mut_ptr()),
+                    &mut size,
+                )
+                .is_ok()
+                {
+                    let path = String::from_utf16_lossy(&path_buf[..size as usize]);
+                    app_name = std::path::Path::new(&path)
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                }
+                let _ = CloseHandle(process);
+            }
+        }
+
+        Some(WindowInfo {
+            title,
+            app_name,
+            pid: (pid != 0).then_some(pid),
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn focused_window() -> Option<WindowInfo> {
+    None
+}
+
+/// Working directory of a process. For terminals the interesting directory
+/// is that of the shell running inside, so the newest child is preferred.
+#[cfg(target_os = "linux")]
+pub(super) fn process_cwd(pid: u32) -> Option<PathBuf> {
+    let children =
+        std::fs::read_to_string(format!("/proc/{0}/task/{0}/children", pid)).unwrap_or_default();
+    let target = children
+        .split_whitespace()
+        .filter_map(|child| child.parse::<u32>().ok())
+        .next_back()
+        .unwrap_or(pid);
+
+    std::fs::read_link(format!("/proc/{}/cwd", target))
+        .or_else(|_| std::fs::read_link(format!("/proc/{}/cwd", pid)))
+        .ok()
+}
+
+#[cfg(target_os = "macos")]
+pub(super) fn process_cwd(pid: u32) -> Option<PathBuf> {
+    let output = command_output("lsof", &["-a", "-p", &pid.to_string(), "-d", "cwd", "-Fn"])?;
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix('n'))
+        .map(PathBuf::from)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(super) fn process_cwd(_pid: u32) -> Option<PathBuf> {
+    None
+}
@@ -0,0 +1,110 @@
+//! Finished recordings waiting to be transcribed.
+//!
+//! Recording is released as soon as a clip is stopped, so the next thought
+//! can be dictated while earlier clips are still being transcribed or
+//! post-processed. Clips are handled one at a time by a single worker, which
+//! keeps pastes and saved notes in the order the clips were recorded.
+
+use crate::actions::{process_clip, PromptInputs};
+use crate::context::CaptureContext;
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::segments::Recording;
+use crate::tray::{change_tray_icon, TrayIconState};
+use crate::utils::{hide_recording_overlay, show_transcribing_overlay};
+use log::{debug, error};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tauri::{AppHandle, Manager};
+
+/// A stopped recording and what to do with its text.
+pub struct Clip {
+    pub binding_id: String,
+    pub recording: Recording,
+    pub capture_context: Option<CaptureContext>,
+    /// The clipboard and selected text, when the prompt uses them
+    pub prompt_inputs: PromptInputs,
+    pub post_process: bool,
+    pub append: bool,
+}
+
+pub struct DictationQueue {
+    jobs: Sender<Clip>,
+    /// Clips queued or being processed
+    pending: Arc<AtomicUsize>,
+    /// Text of the last clip delivered, for `${previous_transcription}`
+    last_text: Mutex<Option<String>>,
+}
+
+impl DictationQueue {
+    pub fn new(app: AppHandle) -> Self {
+        let (jobs, receiver) = mpsc::channel::<Clip>();
+        let pending = Arc::new(AtomicUsize::new(0));
+
+        let worker_pending = Arc::clone(&pending);
+        thread::spawn(move || {
+            while let Ok(clip) = receiver.recv() {
+                let binding_id = clip.binding_id.clone();
+                // A separate task, so a panic in the pipeline loses one clip
+                // rather than the worker
+                let task = tauri::async_runtime::spawn(process_clip(app.clone(), clip));
+                if let Err(e) = tauri::async_runtime::block_on(task) {
+                    error!("Dictation pipeline for '{}' failed: {}", binding_id, e);
+                }
+                worker_pending.fetch_sub(1, Ordering::SeqCst);
+                show_queue_status(&app);
+            }
+            debug!("Dictation queue exited");
+        });
+
+        Self {
+            jobs,
+            pending,
+            last_text: Mutex::new(None),
+        }
+    }
+
+    pub fn push(&self, clip: Clip) {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        if self.jobs.send(clip).is_err() {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            error!("Dictation queue closed; dropping clip");
+        }
+    }
+
+    /// Number of clips queued or being processed.
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    /// The text of the last clip delivered since the app started.
+    pub fn last_text(&self) -> Option<String> {
+        self.last_text.lock().unwrap().clone()
+    }
+
+    pub fn set_last_text(&self, text: &str) {
+        *self.last_text.lock().unwrap() = Some(text.to_string());
+    }
+}
+
+/// Point the overlay and tray at whatever is going on now: a recording keeps
+/// its own overlay, queued clips show as transcribing, otherwise hide.
+pub fn show_queue_status(app: &AppHandle) {
+    let recording = app
+        .try_state::<Arc<AudioRecordingManager>>()
+        .is_some_and(|rm| rm.is_recording());
+    if recording {
+        return;
+    }
+    if app
+        .try_state::<DictationQueue>()
+        .is_some_and(|queue| queue.pending() > 0)
+    {
+        change_tray_icon(app, TrayIconState::Transcribing);
+        show_transcribing_overlay(app);
+    } else {
+        change_tray_icon(app, TrayIconState::Idle);
+        hide_recording_overlay(app);
+    }
+}
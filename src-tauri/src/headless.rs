@@ -0,0 +1,183 @@
+//! `jotline-cli`: transcription and model management without the desktop app,
+//! for scripts, CI and SSH sessions. It works on the app's own settings and
+//! models directory through a [`HeadlessHost`].
+
+use crate::audio_toolkit::audio::{decode_audio_file, is_audio_file};
+use crate::audio_toolkit::VoiceActivityDetector;
+use crate::host::{HeadlessHost, Host};
+use crate::managers::audio::{create_segmenter, create_vad, VAD_MODEL_RESOURCE};
+use crate::managers::model::{DownloadProgress, ModelManager};
+use crate::managers::segments::transcribe_audio;
+use crate::managers::transcription::TranscriptionManager;
+use crate::transcript::{ExportFormat, Transcript};
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "jotline-cli",
+    about = "Transcribe audio files and manage Jotline's models without the app"
+)]
+pub struct HeadlessArgs {
+    /// Use this data directory instead of the desktop app's
+    #[arg(long, global = true)]
+    pub data_dir: Option<PathBuf>,
+
+    /// Look for bundled resources (the VAD model) here first
+    #[arg(long, global = true)]
+    pub resource_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: HeadlessCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HeadlessCommand {
+    /// Transcribe an audio file (WAV, FLAC, MP3 or OGG) to stdout
+    Transcribe {
+        file: PathBuf,
+
+        /// Model to use instead of the one selected in the app
+        #[arg(long)]
+        model: Option<String>,
+
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Keep silence instead of filtering the audio with the VAD
+        #[arg(long)]
+        no_vad: bool,
+    },
+    /// List the available models and which are downloaded
+    Models,
+    /// Download a model
+    Download { model: String },
+    /// Delete a downloaded model
+    Delete { model: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Srt,
+    Vtt,
+    Markdown,
+}
+
+pub fn run(args: HeadlessArgs) -> Result<()> {
+    let mut host = HeadlessHost::new(args.data_dir)?.with_event_handler(report_progress);
+    if let Some(dir) = args.resource_dir {
+        host = host.with_resource_dir(dir);
+    }
+    let host: Arc<dyn Host> = Arc::new(host);
+    let model_manager = Arc::new(ModelManager::new(Arc::clone(&host))?);
+
+    match args.command {
+        HeadlessCommand::Transcribe {
+            file,
+            model,
+            format,
+            no_vad,
+        } => {
+            let transcript = transcribe(host, model_manager, &file, model, no_vad)?;
+            print!("{}", render(&transcript, format)?);
+        }
+        HeadlessCommand::Models => {
+            let selected = host.settings().selected_model;
+            let mut models = model_manager.get_available_models();
+            models.sort_by(|a, b| a.id.cmp(&b.id));
+            for model in models {
+                let status = match (model.is_downloaded, model.id == selected) {
+                    (true, true) => "selected",
+                    (true, false) => "downloaded",
+                    (false, _) => "",
+                };
+                println!(
+                    "{:<32} {:>6} MB  {:<10}  {}",
+                    model.id, model.size_mb, status, model.name
+                );
+            }
+        }
+        HeadlessCommand::Download { model } => {
+            tauri::async_runtime::block_on(model_manager.download_model(&model))?;
+            eprintln!();
+        }
+        HeadlessCommand::Delete { model } => model_manager.delete_model(&model)?,
+    }
+    Ok(())
+}
+
+fn transcribe(
+    host: Arc<dyn Host>,
+    model_manager: Arc<ModelManager>,
+    file: &Path,
+    model: Option<String>,
+    no_vad: bool,
+) -> Result<Transcript> {
+    if !is_audio_file(file) {
+        bail!("Not a supported audio file: {}", file.display());
+    }
+    let model_id = model.unwrap_or_else(|| host.settings().selected_model);
+    if model_id.is_empty() {
+        bail!("No model selected; pass --model or download one first");
+    }
+
+    let mut vad = if no_vad {
+        None
+    } else {
+        let vad_path = host
+            .resource_path(VAD_MODEL_RESOURCE)
+            .context("VAD model not found; pass --resource-dir or --no-vad")?;
+        Some(create_vad(&vad_path)?)
+    };
+
+    let tm = TranscriptionManager::new(host, model_manager)?;
+    tm.load_model(&model_id)?;
+    let samples =
+        decode_audio_file(file).with_context(|| format!("Failed to decode {}", file.display()))?;
+    let vad = vad
+        .as_mut()
+        .map(|vad| vad as &mut dyn VoiceActivityDetector);
+    transcribe_audio(&tm, &samples, vad, &create_segmenter())
+}
+
+fn render(transcript: &Transcript, format: OutputFormat) -> Result<String> {
+    let export = |format: ExportFormat| {
+        if transcript.segments.is_empty() {
+            bail!("The model did not report timestamps; use --format text or json");
+        }
+        Ok(format.render(&transcript.segments))
+    };
+    match format {
+        OutputFormat::Text => Ok(format!("{}\n", transcript.text)),
+        OutputFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(transcript)?)),
+        OutputFormat::Srt => export(ExportFormat::Srt),
+        OutputFormat::Vtt => export(ExportFormat::WebVtt),
+        OutputFormat::Markdown => export(ExportFormat::Markdown),
+    }
+}
+
+/// Show download progress on stderr, keeping stdout for the output.
+fn report_progress(event: &str, payload: &serde_json::Value) {
+    match event {
+        "model-download-progress" => {
+            if let Ok(progress) = serde_json::from_value::<DownloadProgress>(payload.clone()) {
+                eprint!(
+                    "\rDownloading {}: {:.0}% ({} / {} MB)",
+                    progress.model_id,
+                    progress.percentage,
+                    progress.downloaded / 1_000_000,
+                    progress.total / 1_000_000
+                );
+                let _ = std::io::stderr().flush();
+            }
+        }
+        "model-extraction-started" => eprint!("\nExtracting..."),
+        "model-extraction-failed" => eprintln!("\nExtraction failed: {}", payload),
+        _ => {}
+    }
+}
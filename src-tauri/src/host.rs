@@ -0,0 +1,223 @@
+//! What the model and transcription managers need from the app around them:
+//! settings, the data directory, bundled resources and a way to report
+//! progress.
+//!
+//! In the desktop app the Tauri [`AppHandle`] provides all of that.
+//! [`HeadlessHost`] stands in for it in `jotline-cli`, reading the same
+//! `settings_store.json` and models directory without starting a window.
+
+use crate::settings::{
+    get_default_settings, get_settings, write_settings, AppSettings, SETTINGS_STORE_PATH,
+};
+use anyhow::Result;
+use log::warn;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Must match `identifier` in `tauri.conf.json`, which names the data directory
+const APP_IDENTIFIER: &str = "com.jotline.app";
+
+pub trait Host: Send + Sync {
+    fn app_data_dir(&self) -> Result<PathBuf>;
+    /// A file shipped in the app's resources, e.g.
+    /// `resources/models/silero_vad_v4.onnx`, if it can be found.
+    fn resource_path(&self, path: &str) -> Option<PathBuf>;
+    fn settings(&self) -> AppSettings;
+    fn write_settings(&self, settings: AppSettings);
+    fn emit_value(&self, event: &str, payload: Value);
+}
+
+impl dyn Host {
+    pub fn emit<S: Serialize>(&self, event: &str, payload: S) {
+        match serde_json::to_value(payload) {
+            Ok(payload) => self.emit_value(event, payload),
+            Err(e) => warn!("Failed to serialize '{}' event: {}", event, e),
+        }
+    }
+}
+
+impl Host for AppHandle {
+    fn app_data_dir(&self) -> Result<PathBuf> {
+        self.path()
+            .app_data_dir()
+            .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))
+    }
+
+    fn resource_path(&self, path: &str) -> Option<PathBuf> {
+        self.path()
+            .resolve(path, tauri::path::BaseDirectory::Resource)
+            .ok()
+            .filter(|p| p.exists())
+    }
+
+    fn settings(&self) -> AppSettings {
+        get_settings(self)
+    }
+
+    fn write_settings(&self, settings: AppSettings) {
+        write_settings(self, settings)
+    }
+
+    fn emit_value(&self, event: &str, payload: Value) {
+        let _ = self.emit(event, payload);
+    }
+}
+
+type EventHandler = Box<dyn Fn(&str, &Value) + Send + Sync>;
+
+/// A [`Host`] for running without Tauri, on the desktop app's data.
+pub struct HeadlessHost {
+    data_dir: PathBuf,
+    resource_dirs: Vec<PathBuf>,
+    on_event: Option<EventHandler>,
+}
+
+impl HeadlessHost {
+    /// Use `data_dir`, or the desktop app's data directory when `None`.
+    pub fn new(data_dir: Option<PathBuf>) -> Result<Self> {
+        let data_dir = match data_dir {
+            Some(dir) => dir,
+            None => default_app_data_dir()?,
+        };
+        Ok(Self {
+            data_dir,
+            resource_dirs: default_resource_dirs(),
+            on_event: None,
+        })
+    }
+
+    /// Look for bundled resources in `dir` before the usual install locations.
+    pub fn with_resource_dir(mut self, dir: PathBuf) -> Self {
+        self.resource_dirs.insert(0, dir);
+        self
+    }
+
+    /// Receive the events the desktop app would send to its window.
+    pub fn with_event_handler(
+        mut self,
+        handler: impl Fn(&str, &Value) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_event = Some(Box::new(handler));
+        self
+    }
+
+    fn store_path(&self) -> PathBuf {
+        self.data_dir.join(SETTINGS_STORE_PATH)
+    }
+
+    /// The whole store file; the settings live under its `settings` key.
+    fn read_store(&self) -> serde_json::Map<String, Value> {
+        fs::read_to_string(self.store_path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+}
+
+impl Host for HeadlessHost {
+    fn app_data_dir(&self) -> Result<PathBuf> {
+        Ok(self.data_dir.clone())
+    }
+
+    fn resource_path(&self, path: &str) -> Option<PathBuf> {
+        self.resource_dirs
+            .iter()
+            .map(|dir| dir.join(path))
+            .find(|p| p.exists())
+    }
+
+    fn settings(&self) -> AppSettings {
+        self.read_store()
+            .remove("settings")
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_else(get_default_settings)
+    }
+
+    fn write_settings(&self, settings: AppSettings) {
+        let mut store = self.read_store();
+        store.insert(
+            "settings".to_string(),
+            serde_json::to_value(&settings).unwrap(),
+        );
+        let written = fs::create_dir_all(&self.data_dir).and_then(|()| {
+            fs::write(
+                self.store_path(),
+                serde_json::to_string_pretty(&store).unwrap(),
+            )
+        });
+        if let Err(e) = written {
+            warn!("Failed to write settings to {:?}: {}", self.store_path(), e);
+        }
+    }
+
+    fn emit_value(&self, event: &str, payload: Value) {
+        if let Some(handler) = &self.on_event {
+            handler(event, &payload);
+        }
+    }
+}
+
+/// Where Tauri puts the app's data on this platform.
+fn default_app_data_dir() -> Result<PathBuf> {
+    let env_dir = |var: &str| std::env::var_os(var).map(PathBuf::from);
+    #[cfg(target_os = "windows")]
+    let base = env_dir("APPDATA");
+    #[cfg(target_os = "macos")]
+    let base = env_dir("HOME").map(|home| home.join("Library/Application Support"));
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = env_dir("XDG_DATA_HOME")
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env_dir("HOME").map(|home| home.join(".local/share")));
+
+    base.map(|dir| dir.join(APP_IDENTIFIER))
+        .ok_or_else(|| anyhow::anyhow!("Could not find the app data directory"))
+}
+
+/// Where the installed app keeps its resources, relative to the binary: next
+/// to it on Windows and in AppImages, `../Resources` in a macOS bundle and
+/// `../lib/Jotline` in Linux packages. Development builds use the sources.
+fn default_resource_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(exe_dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(PathBuf::from))
+    {
+        dirs.push(exe_dir.join("../Resources"));
+        dirs.push(exe_dir.join("../lib/Jotline"));
+        dirs.push(exe_dir);
+    }
+    if cfg!(debug_assertions) {
+        dirs.push(PathBuf::from(env!("CARGO_MANIFEST_DIR")));
+    }
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headless_settings_round_trip_and_keep_other_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(SETTINGS_STORE_PATH),
+            r#"{"onboarding": true}"#,
+        )
+        .unwrap();
+        let host = HeadlessHost::new(Some(dir.path().to_path_buf())).unwrap();
+
+        let mut settings = host.settings();
+        assert_eq!(settings.selected_model, "");
+        settings.selected_model = "parakeet-tdt-0.6b-v3".to_string();
+        host.write_settings(settings);
+
+        assert_eq!(host.settings().selected_model, "parakeet-tdt-0.6b-v3");
+        assert_eq!(
+            host.read_store().get("onboarding"),
+            Some(&Value::Bool(true))
+        );
+    }
+}
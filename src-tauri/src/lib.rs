@@ -6,14 +6,24 @@ pub mod audio_toolkit;
 pub mod cli;
 mod clipboard;
 mod commands;
+mod context;
+mod dictation_queue;
+pub mod headless;
 mod helpers;
+pub mod host;
 mod input;
 mod llm_client;
 mod managers;
+mod notes;
 mod overlay;
+mod palette;
+mod prompt_template;
+mod search;
+mod secrets;
 mod settings;
 mod shortcut;
 mod signal_handle;
+mod transcript;
 mod transcription_coordinator;
 mod tray;
 mod tray_i18n;
@@ -23,10 +33,19 @@ pub use cli::CliArgs;
 use specta_typescript::{BigIntExportBehavior, Typescript};
 use tauri_specta::{collect_commands, Builder};
 
+use context::ContextManager;
+use dictation_queue::DictationQueue;
 use env_filter::Builder as EnvFilterBuilder;
+use host::Host;
 use managers::audio::AudioRecordingManager;
+use managers::control::ControlManager;
+use managers::history::HistoryManager;
 use managers::model::ModelManager;
+use managers::notes::NoteManager;
+use managers::search::SearchManager;
+use managers::text_search::TextSearchManager;
 use managers::transcription::TranscriptionManager;
+use managers::watch_folder::WatchFolderManager;
 #[cfg(unix)]
 use signal_hook::consts::{SIGUSR1, SIGUSR2};
 #[cfg(unix)]
@@ -113,16 +132,42 @@ fn initialize_core_logic(app_handle: &AppHandle) {
     let recording_manager = Arc::new(
         AudioRecordingManager::new(app_handle).expect("Failed to initialize recording manager"),
     );
+    let host: Arc<dyn Host> = Arc::new(app_handle.clone());
     let model_manager =
-        Arc::new(ModelManager::new(app_handle).expect("Failed to initialize model manager"));
+        Arc::new(ModelManager::new(host.clone()).expect("Failed to initialize model manager"));
     let transcription_manager = Arc::new(
-        TranscriptionManager::new(app_handle, model_manager.clone())
+        TranscriptionManager::new(host, model_manager.clone())
             .expect("Failed to initialize transcription manager"),
     );
+    let note_manager =
+        Arc::new(NoteManager::new(app_handle).expect("Failed to initialize note manager"));
+    let search_manager = Arc::new(
+        SearchManager::new(app_handle, model_manager.clone(), note_manager.clone())
+            .expect("Failed to initialize search manager"),
+    );
+    let context_manager = Arc::new(ContextManager::new());
+    let history_manager =
+        Arc::new(HistoryManager::new(app_handle).expect("Failed to initialize history manager"));
+    let text_search_manager = Arc::new(
+        TextSearchManager::new(app_handle, note_manager.clone(), history_manager.clone())
+            .expect("Failed to initialize text search manager"),
+    );
+    let watch_folder_manager = Arc::new(
+        WatchFolderManager::new(app_handle).expect("Failed to initialize watch folder manager"),
+    );
+    let control_manager =
+        Arc::new(ControlManager::new(app_handle).expect("Failed to initialize control manager"));
     // Add managers to Tauri's managed state
     app_handle.manage(recording_manager.clone());
     app_handle.manage(model_manager.clone());
     app_handle.manage(transcription_manager.clone());
+    app_handle.manage(note_manager.clone());
+    app_handle.manage(search_manager.clone());
+    app_handle.manage(context_manager.clone());
+    app_handle.manage(history_manager.clone());
+    app_handle.manage(text_search_manager.clone());
+    app_handle.manage(watch_folder_manager.clone());
+    app_handle.manage(control_manager.clone());
 
     // Note: Shortcuts are NOT initialized here.
     // The frontend is responsible for calling the `initialize_shortcuts` command
@@ -226,6 +271,7 @@ fn initialize_core_logic(app_handle: &AppHandle) {
     }
 
     // Create the recording overlay window (hidden by default)
+    app_handle.manage(utils::OverlayState::default());
     utils::create_recording_overlay(app_handle);
 }
 
@@ -269,10 +315,15 @@ pub fn run(cli_args: CliArgs) {
         shortcut::change_auto_submit_setting,
         shortcut::change_auto_submit_key_setting,
         shortcut::change_post_process_enabled_setting,
+        shortcut::change_post_process_streaming_setting,
+        shortcut::change_post_process_stream_typing_setting,
         shortcut::change_experimental_enabled_setting,
         shortcut::change_post_process_base_url_setting,
         shortcut::change_post_process_api_key_setting,
+        shortcut::unlock_secret_store,
         shortcut::change_post_process_model_setting,
+        shortcut::change_post_process_request_setting,
+        shortcut::set_post_process_fallbacks,
         shortcut::set_post_process_provider,
         shortcut::fetch_post_process_models,
         shortcut::add_post_process_prompt,
@@ -314,6 +365,48 @@ pub fn run(cli_args: CliArgs) {
         commands::models::is_model_loading,
         commands::models::has_any_models_available,
         commands::models::has_any_models_or_downloads,
+        commands::projects::list_projects,
+        commands::projects::move_note,
+        commands::projects::change_active_project_setting,
+        commands::search::search_notes,
+        commands::search::search_text,
+        commands::search::get_search_status,
+        commands::search::change_auto_link_notes_setting,
+        commands::search::change_auto_link_threshold_setting,
+        commands::search::change_auto_link_max_links_setting,
+        commands::history::get_history_entries,
+        commands::history::search_history,
+        commands::history::copy_history_entry,
+        commands::history::export_history_transcript,
+        commands::history::delete_history_entry,
+        commands::history::get_history_revisions,
+        commands::history::retranscribe,
+        commands::history::change_history_enabled_setting,
+        commands::history::change_history_save_audio_setting,
+        commands::history::change_history_limit_setting,
+        commands::history::change_history_retention_days_setting,
+        commands::watch_folder::get_watch_folder_status,
+        commands::watch_folder::change_watch_folder_setting,
+        commands::watch_folder::change_watch_folder_post_process_setting,
+        commands::watch_folder::retry_watch_folder_file,
+        commands::control::get_control_socket_status,
+        commands::control::change_control_socket_setting,
+        commands::palette::get_palette_entries,
+        commands::palette::run_palette_entry,
+        commands::palette::close_palette,
+        commands::palette::change_command_palette_enabled_setting,
+        commands::palette::change_tap_threshold_ms_setting,
+        commands::notes::list_notes,
+        commands::notes::get_note,
+        commands::notes::transcribe_file,
+        commands::notes::update_note,
+        commands::notes::delete_note,
+        commands::notes::get_notes_dir_path,
+        commands::notes::change_notes_directory_setting,
+        commands::notes::change_paste_after_capture_setting,
+        commands::notes::change_capture_context_setting,
+        commands::notes::change_auto_tag_notes_setting,
+        commands::notes::change_append_target_setting,
         commands::audio::update_microphone_mode,
         commands::audio::get_microphone_mode,
         commands::audio::get_available_microphones,
@@ -328,6 +421,8 @@ pub fn run(cli_args: CliArgs) {
         commands::audio::get_clamshell_microphone,
         commands::audio::is_recording,
         commands::transcription::set_model_unload_timeout,
+        commands::transcription::change_dictation_queue_depth_setting,
+        commands::transcription::change_live_transcription_setting,
         commands::transcription::get_model_load_status,
         commands::transcription::unload_model_manually,
         helpers::clamshell::is_laptop,
@@ -377,6 +472,8 @@ pub fn run(cli_args: CliArgs) {
                 signal_handle::send_transcription_input(app, "transcribe", "CLI");
             } else if args.iter().any(|a| a == "--toggle-post-process") {
                 signal_handle::send_transcription_input(app, "transcribe_with_post_process", "CLI");
+            } else if args.iter().any(|a| a == "--toggle-append") {
+                signal_handle::send_transcription_input(app, "append", "CLI");
             } else if args.iter().any(|a| a == "--cancel") {
                 crate::utils::cancel_current_operation(app);
             } else {
@@ -410,6 +507,7 @@ pub fn run(cli_args: CliArgs) {
             // Store the file log level in the atomic for the filter to use
             FILE_LOG_LEVEL.store(file_log_level.to_level_filter() as u8, Ordering::Relaxed);
             let app_handle = app.handle().clone();
+            app.manage(DictationQueue::new(app_handle.clone()));
             app.manage(TranscriptionCoordinator::new(app_handle.clone()));
 
             initialize_core_logic(&app_handle);
@@ -452,6 +550,9 @@ pub fn run(cli_args: CliArgs) {
                     }
                 }
             }
+            tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                actions::transcribe_dropped_files(window.app_handle(), paths.clone());
+            }
             tauri::WindowEvent::ThemeChanged(theme) => {
                 log::info!("Theme changed to: {:?}", theme);
                 // Update tray icon to match new theme, maintaining idle state
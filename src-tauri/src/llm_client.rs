@@ -1,8 +1,15 @@
-use crate::settings::PostProcessProvider;
-use log::debug;
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, REFERER, USER_AGENT};
+use crate::settings::{PostProcessProvider, ANTHROPIC_PROVIDER_ID};
+use futures_util::StreamExt;
+use log::{debug, warn};
+use reqwest::header::{
+    HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, REFERER, RETRY_AFTER, USER_AGENT,
+};
+use reqwest::{RequestBuilder, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
 
 #[derive(Debug, Serialize)]
 struct ChatMessage {
@@ -30,6 +37,8 @@ struct ChatCompletionRequest {
     messages: Vec<ChatMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     response_format: Option<ResponseFormat>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,6 +56,94 @@ struct ChatMessageResponse {
     content: Option<String>,
 }
 
+/// Upper bound on the reply; the Messages API requires one
+const ANTHROPIC_MAX_TOKENS: u32 = 4096;
+/// Tool the model is made to call when a JSON schema is given, since the
+/// Messages API has no `response_format`
+const ANTHROPIC_OUTPUT_TOOL: &str = "transcription_output";
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text { text: String },
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicMessage {
+    role: String,
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicTool {
+    name: String,
+    description: String,
+    input_schema: Value,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ToolChoice {
+    Tool { name: String },
+}
+
+#[derive(Debug, Serialize)]
+struct MessagesRequest {
+    model: String,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    messages: Vec<AnthropicMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<AnthropicTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<ToolChoice>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagesResponse {
+    content: Vec<ResponseBlock>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ResponseBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        name: String,
+        input: Value,
+    },
+    /// Thinking and any block types added later
+    #[serde(other)]
+    Other,
+}
+
+impl MessagesResponse {
+    /// The reply as the chat completions path returns it: the tool input as
+    /// a JSON string for structured output, otherwise the text.
+    fn into_content(self) -> Option<String> {
+        let mut text = String::new();
+        for block in self.content {
+            match block {
+                ResponseBlock::ToolUse { name, input } if name == ANTHROPIC_OUTPUT_TOOL => {
+                    return Some(input.to_string());
+                }
+                ResponseBlock::Text { text: part } => text.push_str(&part),
+                _ => {}
+            }
+        }
+        (!text.is_empty()).then_some(text)
+    }
+}
+
+fn is_anthropic(provider: &PostProcessProvider) -> bool {
+    provider.id == ANTHROPIC_PROVIDER_ID
+}
+
 /// Build headers for API requests based on provider type
 fn build_headers(provider: &PostProcessProvider, api_key: &str) -> Result<HeaderMap, String> {
     let mut headers = HeaderMap::new();
@@ -65,7 +162,7 @@ fn build_headers(provider: &PostProcessProvider, api_key: &str) -> Result<Header
 
     // Provider-specific auth headers
     if !api_key.is_empty() {
-        if provider.id == "anthropic" {
+        if is_anthropic(provider) {
             headers.insert(
                 "x-api-key",
                 HeaderValue::from_str(api_key)
@@ -89,10 +186,82 @@ fn create_client(provider: &PostProcessProvider, api_key: &str) -> Result<reqwes
     let headers = build_headers(provider, api_key)?;
     reqwest::Client::builder()
         .default_headers(headers)
+        .connect_timeout(provider_timeout(provider))
         .build()
         .map_err(|e| format!("Failed to build HTTP client: {}", e))
 }
 
+/// How long a request to `provider` may take, or a stream may go quiet
+fn provider_timeout(provider: &PostProcessProvider) -> Duration {
+    Duration::from_secs(provider.timeout_secs.max(1) as u64)
+}
+
+/// Backoff before the first retry, doubled for each one after
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// Longest wait between retries, even if `Retry-After` asks for more
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Rate limits and server errors are worth another try
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// How long to wait before retry number `attempt` (from 0), preferring the
+/// server's `Retry-After` seconds when it sent them.
+fn retry_delay(attempt: u32, retry_after: Option<&HeaderValue>) -> Duration {
+    retry_after
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_else(|| RETRY_BASE_DELAY * 2u32.saturating_pow(attempt))
+        .min(MAX_RETRY_DELAY)
+}
+
+/// Send `request`, retrying up to the provider's `max_retries` times with
+/// backoff when it times out, cannot connect, or is answered with 429 or a
+/// 5xx. The last response is returned whatever its status.
+async fn send_with_retries(
+    provider: &PostProcessProvider,
+    request: RequestBuilder,
+) -> Result<Response, String> {
+    let timeout = provider_timeout(provider);
+    let mut attempt = 0;
+    loop {
+        let attempt_request = request
+            .try_clone()
+            .ok_or("Request body cannot be retried")?;
+        let (error, delay) = match tokio::time::timeout(timeout, attempt_request.send()).await {
+            Ok(Ok(response)) if is_retryable(response.status()) => {
+                let delay = retry_delay(attempt, response.headers().get(RETRY_AFTER));
+                if attempt >= provider.max_retries {
+                    return Ok(response);
+                }
+                (format!("status {}", response.status()), delay)
+            }
+            Ok(Ok(response)) => return Ok(response),
+            Ok(Err(e)) if e.is_timeout() || e.is_connect() => {
+                if attempt >= provider.max_retries {
+                    return Err(format!("HTTP request failed: {}", e));
+                }
+                (e.to_string(), retry_delay(attempt, None))
+            }
+            Ok(Err(e)) => return Err(format!("HTTP request failed: {}", e)),
+            Err(_) => {
+                if attempt >= provider.max_retries {
+                    return Err(format!("Request timed out after {}s", timeout.as_secs()));
+                }
+                ("timed out".to_string(), retry_delay(attempt, None))
+            }
+        };
+        attempt += 1;
+        warn!(
+            "Request to provider '{}' failed ({}); retry {} of {} in {:?}",
+            provider.id, error, attempt, provider.max_retries, delay
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
 /// Send a chat completion request to an OpenAI-compatible API
 /// Returns Ok(Some(content)) on success, Ok(None) if response has no content,
 /// or Err on actual errors (HTTP, parsing, etc.)
@@ -116,6 +285,18 @@ pub async fn send_chat_completion_with_schema(
     system_prompt: Option<String>,
     json_schema: Option<Value>,
 ) -> Result<Option<String>, String> {
+    if is_anthropic(provider) {
+        return send_messages(
+            provider,
+            api_key,
+            model,
+            user_content,
+            system_prompt,
+            json_schema,
+        )
+        .await;
+    }
+
     let base_url = provider.base_url.trim_end_matches('/');
     let url = format!("{}/chat/completions", base_url);
 
@@ -154,14 +335,14 @@ pub async fn send_chat_completion_with_schema(
         model: model.to_string(),
         messages,
         response_format,
+        stream: false,
     };
 
-    let response = client
+    let request = client
         .post(&url)
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("HTTP request failed: {}", e))?;
+        .timeout(provider_timeout(provider))
+        .json(&request_body);
+    let response = send_with_retries(provider, request).await?;
 
     let status = response.status();
     if !status.is_success() {
@@ -186,12 +367,288 @@ pub async fn send_chat_completion_with_schema(
         .and_then(|choice| choice.message.content.clone()))
 }
 
-/// Fetch available models from an OpenAI-compatible API
+fn build_messages_request(
+    model: &str,
+    user_content: String,
+    system_prompt: Option<String>,
+    json_schema: Option<Value>,
+) -> MessagesRequest {
+    let (tools, tool_choice) = match json_schema {
+        Some(schema) => (
+            vec![AnthropicTool {
+                name: ANTHROPIC_OUTPUT_TOOL.to_string(),
+                description: "Return the processed transcription".to_string(),
+                input_schema: schema,
+            }],
+            Some(ToolChoice::Tool {
+                name: ANTHROPIC_OUTPUT_TOOL.to_string(),
+            }),
+        ),
+        None => (Vec::new(), None),
+    };
+
+    MessagesRequest {
+        model: model.to_string(),
+        max_tokens: ANTHROPIC_MAX_TOKENS,
+        system: system_prompt.filter(|system| !system.is_empty()),
+        messages: vec![AnthropicMessage {
+            role: "user".to_string(),
+            content: vec![ContentBlock::Text { text: user_content }],
+        }],
+        tools,
+        tool_choice,
+        stream: false,
+    }
+}
+
+/// Send a request to the Anthropic Messages API. With a schema the model
+/// must answer by calling a tool whose input follows it, and that input is
+/// returned as a JSON string.
+async fn send_messages(
+    provider: &PostProcessProvider,
+    api_key: String,
+    model: &str,
+    user_content: String,
+    system_prompt: Option<String>,
+    json_schema: Option<Value>,
+) -> Result<Option<String>, String> {
+    let base_url = provider.base_url.trim_end_matches('/');
+    let url = format!("{}/messages", base_url);
+
+    debug!("Sending messages request to: {}", url);
+
+    let client = create_client(provider, &api_key)?;
+    let request_body = build_messages_request(model, user_content, system_prompt, json_schema);
+
+    let request = client
+        .post(&url)
+        .timeout(provider_timeout(provider))
+        .json(&request_body);
+    let response = send_with_retries(provider, request).await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Failed to read error response".to_string());
+        return Err(format!(
+            "API request failed with status {}: {}",
+            status, error_text
+        ));
+    }
+
+    let message: MessagesResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse API response: {}", e))?;
+
+    Ok(message.into_content())
+}
+
+/// Aborts a streaming request from elsewhere, such as the cancel shortcut.
+/// Clones share the same flag.
+#[derive(Clone)]
+pub struct CancelToken(Arc<watch::Sender<bool>>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self(Arc::new(watch::Sender::new(false)))
+    }
+
+    pub fn cancel(&self) {
+        self.0.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Resolves once [`cancel`](Self::cancel) has been called, even if that
+    /// was before this was awaited.
+    async fn cancelled(&self) {
+        let mut rx = self.0.subscribe();
+        let _ = rx.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Splits a server-sent event stream into the `data` of each event. Bytes
+/// arrive in arbitrary chunks, so partial lines are kept until the rest
+/// comes in.
+#[derive(Default)]
+struct SseDecoder {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(self.data.join("\n"));
+                    self.data.clear();
+                }
+            } else if let Some(data) = line.strip_prefix("data:") {
+                self.data
+                    .push(data.strip_prefix(' ').unwrap_or(data).to_string());
+            }
+            // `event:`, `id:` and comments are not needed: the payloads say
+            // what they are
+        }
+        events
+    }
+}
+
+/// What one streamed event contributes to the reply
+#[derive(Debug, PartialEq)]
+enum StreamEvent {
+    Text(String),
+    Done,
+    Ignored,
+}
+
+/// Read one event's `data`, in the chat completions or the Messages API
+/// streaming format.
+fn parse_stream_event(anthropic: bool, data: &str) -> Result<StreamEvent, String> {
+    if !anthropic && data == "[DONE]" {
+        return Ok(StreamEvent::Done);
+    }
+    let event: Value =
+        serde_json::from_str(data).map_err(|e| format!("Failed to parse stream event: {}", e))?;
+    if let Some(error) = event.get("error").filter(|error| !error.is_null()) {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+        return Err(format!("Stream failed: {}", message));
+    }
+
+    let text = if anthropic {
+        match event.get("type").and_then(Value::as_str) {
+            Some("message_stop") => return Ok(StreamEvent::Done),
+            Some("content_block_delta") => event
+                .pointer("/delta/text")
+                .filter(|_| event.pointer("/delta/type") == Some(&Value::from("text_delta"))),
+            _ => None,
+        }
+    } else {
+        event.pointer("/choices/0/delta/content")
+    };
+    Ok(match text.and_then(Value::as_str) {
+        Some(text) if !text.is_empty() => StreamEvent::Text(text.to_string()),
+        _ => StreamEvent::Ignored,
+    })
+}
+
+/// Send a plain prompt with the reply streamed back, calling `on_delta`
+/// with each piece of text as it arrives. Returns the whole reply like
+/// [`send_chat_completion`]. Cancelling `cancel` drops the connection and
+/// returns an error.
+pub async fn stream_chat_completion(
+    provider: &PostProcessProvider,
+    api_key: String,
+    model: &str,
+    prompt: String,
+    cancel: &CancelToken,
+    mut on_delta: impl FnMut(&str),
+) -> Result<Option<String>, String> {
+    let base_url = provider.base_url.trim_end_matches('/');
+    let anthropic = is_anthropic(provider);
+    let client = create_client(provider, &api_key)?;
+
+    let request = if anthropic {
+        let url = format!("{}/messages", base_url);
+        debug!("Streaming messages request to: {}", url);
+        let mut body = build_messages_request(model, prompt, None, None);
+        body.stream = true;
+        client.post(url).json(&body)
+    } else {
+        let url = format!("{}/chat/completions", base_url);
+        debug!("Streaming chat completion request to: {}", url);
+        client.post(url).json(&ChatCompletionRequest {
+            model: model.to_string(),
+            messages: vec![ChatMessage {
+                role: "user".to_string(),
+                content: prompt,
+            }],
+            response_format: None,
+            stream: true,
+        })
+    };
+
+    let response = tokio::select! {
+        response = send_with_retries(provider, request) => response?,
+        _ = cancel.cancelled() => return Err("Request cancelled".to_string()),
+    };
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Failed to read error response".to_string());
+        return Err(format!(
+            "API request failed with status {}: {}",
+            status, error_text
+        ));
+    }
+
+    // A total timeout would cut long replies short, so the limit is on how
+    // long the stream may go quiet
+    let timeout = provider_timeout(provider);
+    let mut body = response.bytes_stream();
+    let mut decoder = SseDecoder::default();
+    let mut content = String::new();
+    'stream: loop {
+        let chunk = tokio::select! {
+            chunk = tokio::time::timeout(timeout, body.next()) => chunk.map_err(|_| {
+                format!("Stream stalled for {}s", timeout.as_secs())
+            })?,
+            _ = cancel.cancelled() => return Err("Request cancelled".to_string()),
+        };
+        let Some(chunk) = chunk else {
+            break;
+        };
+        let chunk = chunk.map_err(|e| format!("Failed to read response stream: {}", e))?;
+        for data in decoder.push(&chunk) {
+            match parse_stream_event(anthropic, &data)? {
+                StreamEvent::Text(text) => {
+                    on_delta(&text);
+                    content.push_str(&text);
+                }
+                StreamEvent::Done => break 'stream,
+                StreamEvent::Ignored => {}
+            }
+        }
+    }
+
+    Ok((!content.is_empty()).then_some(content))
+}
+
+/// Fetch available models from an OpenAI-compatible API, with the same
+/// timeout and retries as chat requests.
 /// Returns a list of model IDs
 pub async fn fetch_models(
     provider: &PostProcessProvider,
     api_key: String,
 ) -> Result<Vec<String>, String> {
+    if is_anthropic(provider) {
+        return fetch_anthropic_models(provider, api_key).await;
+    }
+
     let base_url = provider.base_url.trim_end_matches('/');
     let url = format!("{}/models", base_url);
 
@@ -199,9 +656,8 @@ pub async fn fetch_models(
 
     let client = create_client(provider, &api_key)?;
 
-    let response = client
-        .get(&url)
-        .send()
+    let request = client.get(&url).timeout(provider_timeout(provider));
+    let response = send_with_retries(provider, request)
         .await
         .map_err(|e| format!("Failed to fetch models: {}", e))?;
 
@@ -245,3 +701,217 @@ pub async fn fetch_models(
 
     Ok(models)
 }
+
+#[derive(Debug, Deserialize)]
+struct AnthropicModelPage {
+    data: Vec<AnthropicModel>,
+    #[serde(default)]
+    has_more: bool,
+    last_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicModel {
+    id: String,
+}
+
+/// Fetch model IDs from Anthropic's `/models`, which is paginated.
+async fn fetch_anthropic_models(
+    provider: &PostProcessProvider,
+    api_key: String,
+) -> Result<Vec<String>, String> {
+    let base_url = provider.base_url.trim_end_matches('/');
+    let url = format!("{}/models", base_url);
+
+    debug!("Fetching models from: {}", url);
+
+    let client = create_client(provider, &api_key)?;
+
+    let mut models = Vec::new();
+    let mut after_id: Option<String> = None;
+    loop {
+        let mut request = client
+            .get(&url)
+            .timeout(provider_timeout(provider))
+            .query(&[("limit", "1000")]);
+        if let Some(after_id) = &after_id {
+            request = request.query(&[("after_id", after_id)]);
+        }
+        let response = send_with_retries(provider, request)
+            .await
+            .map_err(|e| format!("Failed to fetch models: {}", e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(format!(
+                "Model list request failed ({}): {}",
+                status, error_text
+            ));
+        }
+
+        let page: AnthropicModelPage = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        models.extend(page.data.into_iter().map(|model| model.id));
+
+        match page.last_id {
+            Some(last_id) if page.has_more => after_id = Some(last_id),
+            _ => break,
+        }
+    }
+
+    Ok(models)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_messages_request_has_a_system_string_and_a_forced_tool() {
+        let schema = json!({ "type": "object" });
+        let request = build_messages_request(
+            "claude-haiku",
+            "hello".to_string(),
+            Some("Fix punctuation".to_string()),
+            Some(schema.clone()),
+        );
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "model": "claude-haiku",
+                "max_tokens": ANTHROPIC_MAX_TOKENS,
+                "system": "Fix punctuation",
+                "messages": [
+                    { "role": "user", "content": [{ "type": "text", "text": "hello" }] }
+                ],
+                "tools": [{
+                    "name": ANTHROPIC_OUTPUT_TOOL,
+                    "description": "Return the processed transcription",
+                    "input_schema": schema,
+                }],
+                "tool_choice": { "type": "tool", "name": ANTHROPIC_OUTPUT_TOOL },
+            })
+        );
+
+        let plain = build_messages_request("claude-haiku", "hello".to_string(), None, None);
+        let plain = serde_json::to_value(&plain).unwrap();
+        assert!(plain.get("system").is_none());
+        assert!(plain.get("tools").is_none());
+    }
+
+    #[test]
+    fn test_retries_rate_limits_and_server_errors_only() {
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable(StatusCode::UNAUTHORIZED));
+        assert!(!is_retryable(StatusCode::BAD_REQUEST));
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_and_honours_retry_after() {
+        assert_eq!(retry_delay(0, None), Duration::from_millis(500));
+        assert_eq!(retry_delay(2, None), Duration::from_secs(2));
+        assert_eq!(retry_delay(10, None), MAX_RETRY_DELAY);
+        assert_eq!(
+            retry_delay(0, Some(&HeaderValue::from_static("3"))),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            retry_delay(0, Some(&HeaderValue::from_static("3600"))),
+            MAX_RETRY_DELAY
+        );
+        // HTTP dates are not worth parsing; fall back to the backoff
+        assert_eq!(
+            retry_delay(
+                1,
+                Some(&HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"))
+            ),
+            Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn test_sse_decoder_joins_events_split_across_chunks() {
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.push(b"event: ping\ndata: {\"a\"").is_empty());
+        assert_eq!(
+            decoder.push(b":1}\r\n\r\ndata: [DONE]\n"),
+            vec!["{\"a\":1}"]
+        );
+        assert_eq!(decoder.push(b"\n"), vec!["[DONE]"]);
+    }
+
+    #[test]
+    fn test_parse_stream_event_reads_both_formats() {
+        let openai = r#"{"choices":[{"index":0,"delta":{"content":"Hel"}}]}"#;
+        assert_eq!(
+            parse_stream_event(false, openai).unwrap(),
+            StreamEvent::Text("Hel".to_string())
+        );
+        let role_only = r#"{"choices":[{"index":0,"delta":{"role":"assistant"}}]}"#;
+        assert_eq!(
+            parse_stream_event(false, role_only).unwrap(),
+            StreamEvent::Ignored
+        );
+        assert_eq!(
+            parse_stream_event(false, "[DONE]").unwrap(),
+            StreamEvent::Done
+        );
+
+        let anthropic =
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"lo"}}"#;
+        assert_eq!(
+            parse_stream_event(true, anthropic).unwrap(),
+            StreamEvent::Text("lo".to_string())
+        );
+        assert_eq!(
+            parse_stream_event(true, r#"{"type":"message_stop"}"#).unwrap(),
+            StreamEvent::Done
+        );
+        let overloaded =
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        assert_eq!(
+            parse_stream_event(true, overloaded).unwrap_err(),
+            "Stream failed: Overloaded"
+        );
+    }
+
+    #[test]
+    fn test_messages_response_prefers_tool_input_over_text() {
+        let response: MessagesResponse = serde_json::from_value(json!({
+            "content": [
+                { "type": "thinking", "thinking": "..." },
+                { "type": "text", "text": "Here you go" },
+                {
+                    "type": "tool_use",
+                    "id": "toolu_1",
+                    "name": ANTHROPIC_OUTPUT_TOOL,
+                    "input": { "transcription": "Hello." }
+                }
+            ],
+            "stop_reason": "tool_use"
+        }))
+        .unwrap();
+        let content = response.into_content().unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&content).unwrap(),
+            json!({ "transcription": "Hello." })
+        );
+
+        let response: MessagesResponse = serde_json::from_value(json!({
+            "content": [
+                { "type": "text", "text": "Hello, " },
+                { "type": "text", "text": "world." }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(response.into_content().as_deref(), Some("Hello, world."));
+    }
+}
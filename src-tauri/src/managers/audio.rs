@@ -1,10 +1,15 @@
-use crate::audio_toolkit::{list_input_devices, vad::SmoothedVad, AudioRecorder, SileroVad};
+use crate::audio_toolkit::{
+    audio::Segmenter, list_input_devices, vad::SmoothedVad, AudioRecorder, SileroVad,
+};
 use crate::helpers::clamshell;
+use crate::managers::partial_transcript::PartialTranscriber;
+use crate::managers::segments::{Recording, SegmentTranscriber};
 use crate::settings::{get_settings, AppSettings};
 use crate::utils;
 use log::{debug, error, info};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tauri::Manager;
 
 fn set_mute(mute: bool) {
@@ -114,16 +119,45 @@ pub enum MicrophoneMode {
 
 /* ──────────────────────────────────────────────────────────────── */
 
+/// Long recordings are cut at the first pause after this much audio…
+const SEGMENT_MIN: Duration = Duration::from_secs(15);
+/// …or here regardless, repeating `SEGMENT_OVERLAP` in the next piece
+const SEGMENT_MAX: Duration = Duration::from_secs(30);
+const SEGMENT_OVERLAP: Duration = Duration::from_secs(1);
+
+/// Where long recordings and audio files are cut for transcription.
+pub fn create_segmenter() -> Segmenter {
+    Segmenter::new(SEGMENT_MIN, SEGMENT_MAX, SEGMENT_OVERLAP)
+}
+
+/// The Silero VAD model, relative to the app's resources.
+pub const VAD_MODEL_RESOURCE: &str = "resources/models/silero_vad_v4.onnx";
+
+pub fn vad_model_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, anyhow::Error> {
+    app_handle
+        .path()
+        .resolve(VAD_MODEL_RESOURCE, tauri::path::BaseDirectory::Resource)
+        .map_err(|e| anyhow::anyhow!("Failed to resolve VAD path: {}", e))
+}
+
+/// The voice activity detector recordings are filtered with.
+pub fn create_vad(vad_path: &Path) -> Result<SmoothedVad, anyhow::Error> {
+    let silero = SileroVad::new(vad_path, 0.3)
+        .map_err(|e| anyhow::anyhow!("Failed to create SileroVad: {}", e))?;
+    Ok(SmoothedVad::new(Box::new(silero), 15, 15, 2))
+}
+
 fn create_audio_recorder(
-    vad_path: &str,
+    vad_path: &Path,
     app_handle: &tauri::AppHandle,
+    partials: Arc<PartialTranscriber>,
+    segments: Arc<SegmentTranscriber>,
 ) -> Result<AudioRecorder, anyhow::Error> {
-    let silero = SileroVad::new(vad_path, 0.3)
-        .map_err(|e| anyhow::anyhow!("Failed to create SileroVad: {}", e))?;
-    let smoothed_vad = SmoothedVad::new(Box::new(silero), 15, 15, 2);
+    let smoothed_vad = create_vad(vad_path)?;
 
     // Recorder with VAD plus a spectrum-level callback that forwards updates to
-    // the frontend.
+    // the frontend, a chunk callback feeding the live preview and a segment
+    // callback transcribing long recordings piece by piece.
     let recorder = AudioRecorder::new()
         .map_err(|e| anyhow::anyhow!("Failed to create AudioRecorder: {}", e))?
         .with_vad(Box::new(smoothed_vad))
@@ -132,7 +166,9 @@ fn create_audio_recorder(
             move |levels| {
                 utils::emit_levels(&app_handle, &levels);
             }
-        });
+        })
+        .with_chunk_callback(move |chunk| partials.push(chunk))
+        .with_segment_callback(create_segmenter(), move |segment| segments.push(segment));
 
     Ok(recorder)
 }
@@ -149,6 +185,8 @@ pub struct AudioRecordingManager {
     is_open: Arc<Mutex<bool>>,
     is_recording: Arc<Mutex<bool>>,
     did_mute: Arc<Mutex<bool>>,
+    partials: Arc<PartialTranscriber>,
+    segments: Arc<SegmentTranscriber>,
 }
 
 impl AudioRecordingManager {
@@ -171,6 +209,8 @@ impl AudioRecordingManager {
             is_open: Arc::new(Mutex::new(false)),
             is_recording: Arc::new(Mutex::new(false)),
             did_mute: Arc::new(Mutex::new(false)),
+            partials: Arc::new(PartialTranscriber::new(app)),
+            segments: Arc::new(SegmentTranscriber::new(app)),
         };
 
         // Always-on?  Open immediately.
@@ -247,20 +287,15 @@ impl AudioRecordingManager {
         let mut did_mute_guard = self.did_mute.lock().unwrap();
         *did_mute_guard = false;
 
-        let vad_path = self
-            .app_handle
-            .path()
-            .resolve(
-                "resources/models/silero_vad_v4.onnx",
-                tauri::path::BaseDirectory::Resource,
-            )
-            .map_err(|e| anyhow::anyhow!("Failed to resolve VAD path: {}", e))?;
+        let vad_path = vad_model_path(&self.app_handle)?;
         let mut recorder_opt = self.recorder.lock().unwrap();
 
         if recorder_opt.is_none() {
             *recorder_opt = Some(create_audio_recorder(
-                vad_path.to_str().unwrap(),
+                &vad_path,
                 &self.app_handle,
+                Arc::clone(&self.partials),
+                Arc::clone(&self.segments),
             )?);
         }
 
@@ -346,6 +381,8 @@ impl AudioRecordingManager {
 
             if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
                 if rec.start().is_ok() {
+                    self.partials.begin();
+                    self.segments.begin();
                     *self.is_recording.lock().unwrap() = true;
                     *state = RecordingState::Recording {
                         binding_id: binding_id.to_string(),
@@ -370,7 +407,10 @@ impl AudioRecordingManager {
         Ok(())
     }
 
-    pub fn stop_recording(&self, binding_id: &str) -> Option<Vec<f32>> {
+    /// Stop recording for `binding_id`. Pieces of a long recording already
+    /// went to the segment transcriber; the rest of the audio comes with them
+    /// in the returned [`Recording`].
+    pub fn stop_recording(&self, binding_id: &str) -> Option<Recording> {
         let mut state = self.state.lock().unwrap();
 
         match *state {
@@ -379,6 +419,7 @@ impl AudioRecordingManager {
             } if active == binding_id => {
                 *state = RecordingState::Idle;
                 drop(state);
+                self.partials.end();
 
                 let samples = if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
                     match rec.stop() {
@@ -403,13 +444,14 @@ impl AudioRecordingManager {
                 // Pad if very short
                 let s_len = samples.len();
                 // debug!("Got {} samples", s_len);
-                if s_len < WHISPER_SAMPLE_RATE && s_len > 0 {
+                let samples = if s_len < WHISPER_SAMPLE_RATE && s_len > 0 {
                     let mut padded = samples;
                     padded.resize(WHISPER_SAMPLE_RATE * 5 / 4, 0.0);
-                    Some(padded)
+                    padded
                 } else {
-                    Some(samples)
-                }
+                    samples
+                };
+                Some(self.segments.end(samples))
             }
             _ => None,
         }
@@ -428,6 +470,8 @@ impl AudioRecordingManager {
         if let RecordingState::Recording { .. } = *state {
             *state = RecordingState::Idle;
             drop(state);
+            self.partials.end();
+            self.segments.cancel();
 
             if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
                 let _ = rec.stop(); // Discard the result
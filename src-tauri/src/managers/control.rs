@@ -0,0 +1,507 @@
+use crate::actions::TranscriptionCompleted;
+use crate::commands::models::set_active_model;
+use crate::dictation_queue::DictationQueue;
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::TranscriptionManager;
+use crate::settings::get_settings;
+use crate::transcription_coordinator::{
+    is_transcribe_binding, CoordinatorState, TranscriptionCoordinator,
+};
+use crate::utils::cancel_current_operation;
+use anyhow::Result;
+use log::{debug, info, warn};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, EventId, Listener, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::task::JoinSet;
+
+/// Directory in the app data directory holding the socket, private to the user
+#[cfg(unix)]
+const SOCKET_DIR: &str = "control";
+#[cfg(unix)]
+const SOCKET_FILE: &str = "control.sock";
+#[cfg(windows)]
+const PIPE_NAME: &str = r"\\.\pipe\jotline-control";
+
+/// Events sent to clients that subscribe without naming any
+const DEFAULT_EVENTS: &[&str] = &[
+    "transcription-completed",
+    "partial-transcript",
+    "model-state-changed",
+    "note-saved",
+    "history-updated",
+];
+
+// JSON-RPC 2.0 error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The request was understood but the app could not carry it out
+const APP_ERROR: i64 = -32000;
+
+/// Lets other local tools (editors, window-manager scripts) drive the app
+/// over JSON-RPC 2.0 while the `control_socket_enabled` setting is on.
+///
+/// Clients connect to a Unix socket in the app data directory's `control`
+/// folder, or the `\\.\pipe\jotline-control` named pipe on Windows, and
+/// exchange one JSON message per line. After `subscribe`, app events
+/// arrive as `event` notifications on the same connection.
+pub struct ControlManager {
+    app_handle: AppHandle,
+    last_transcription: Arc<Mutex<Option<TranscriptionCompleted>>>,
+    server: Mutex<Option<JoinHandle<()>>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ControlStatus {
+    #[serde(flatten)]
+    state: CoordinatorState,
+    /// Clips waiting to be transcribed or being processed
+    queued: usize,
+    selected_model: String,
+    loaded_model: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    /// Absent for notifications, which get no response
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn to_response(&self, id: Value) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": self.code, "message": self.message },
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct StartParams {
+    binding_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SetModelParams {
+    model_id: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SubscribeParams {
+    events: Vec<String>,
+}
+
+impl ControlManager {
+    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+        let manager = Self {
+            app_handle: app_handle.clone(),
+            last_transcription: Arc::new(Mutex::new(None)),
+            server: Mutex::new(None),
+        };
+
+        let last_transcription = Arc::clone(&manager.last_transcription);
+        app_handle.listen("transcription-completed", move |event| {
+            if let Ok(completed) = serde_json::from_str(event.payload()) {
+                *last_transcription.lock().unwrap() = Some(completed);
+            }
+        });
+
+        if get_settings(app_handle).control_socket_enabled {
+            manager.start()?;
+        }
+        Ok(manager)
+    }
+
+    /// Where clients connect: the socket path.
+    #[cfg(unix)]
+    pub fn endpoint(&self) -> Result<String> {
+        let app_data_dir = self
+            .app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?;
+        Ok(app_data_dir
+            .join(SOCKET_DIR)
+            .join(SOCKET_FILE)
+            .to_string_lossy()
+            .into_owned())
+    }
+
+    /// Where clients connect: the pipe name.
+    #[cfg(windows)]
+    pub fn endpoint(&self) -> Result<String> {
+        Ok(PIPE_NAME.to_string())
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.server.lock().unwrap().is_some()
+    }
+
+    /// Start accepting connections, if not already.
+    pub fn start(&self) -> Result<()> {
+        let mut server = self.server.lock().unwrap();
+        if server.is_some() {
+            return Ok(());
+        }
+        let endpoint = self.endpoint()?;
+        let app = self.app_handle.clone();
+        *server = Some(tauri::async_runtime::spawn(async move {
+            if let Err(e) = listen(app, &endpoint).await {
+                warn!("Control socket {} stopped: {}", endpoint, e);
+            }
+        }));
+        Ok(())
+    }
+
+    /// Stop accepting connections and close the open ones.
+    pub fn stop(&self) {
+        let Some(server) = self.server.lock().unwrap().take() else {
+            return;
+        };
+        server.abort();
+        #[cfg(unix)]
+        {
+            if let Ok(endpoint) = self.endpoint() {
+                let _ = std::fs::remove_file(endpoint);
+            }
+        }
+        debug!("Control socket stopped");
+    }
+
+    fn last_transcription(&self) -> Option<TranscriptionCompleted> {
+        self.last_transcription.lock().unwrap().clone()
+    }
+}
+
+impl Drop for ControlManager {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(unix)]
+async fn listen(app: AppHandle, endpoint: &str) -> Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    // The socket is created with the umask's permissions, so bind it in a
+    // directory only the user can enter rather than narrowing it afterwards
+    let dir = std::path::Path::new(endpoint)
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Socket path has no parent: {}", endpoint))?;
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)?;
+    std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+
+    // A socket left behind by a crash would make the bind fail
+    let _ = std::fs::remove_file(endpoint);
+    let listener = tokio::net::UnixListener::bind(endpoint)?;
+    std::fs::set_permissions(endpoint, std::fs::Permissions::from_mode(0o600))?;
+    info!("Control socket listening on {}", endpoint);
+
+    // Dropped with the server task, which closes every connection
+    let mut connections = JoinSet::new();
+    loop {
+        let (stream, _) = listener.accept().await?;
+        while connections.try_join_next().is_some() {}
+        connections.spawn(serve_connection(app.clone(), stream));
+    }
+}
+
+#[cfg(windows)]
+async fn listen(app: AppHandle, endpoint: &str) -> Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(endpoint)?;
+    info!("Control pipe listening on {}", endpoint);
+
+    // Dropped with the server task, which closes every connection
+    let mut connections = JoinSet::new();
+    loop {
+        server.connect().await?;
+        // Each client gets its own pipe instance; create the next one before
+        // handing this one over so no client finds the pipe missing
+        let client = std::mem::replace(&mut server, ServerOptions::new().create(endpoint)?);
+        while connections.try_join_next().is_some() {}
+        connections.spawn(serve_connection(app.clone(), client));
+    }
+}
+
+/// Unlistens a connection's event subscriptions when it goes away.
+struct Subscriptions {
+    app: AppHandle,
+    ids: Vec<EventId>,
+}
+
+impl Drop for Subscriptions {
+    fn drop(&mut self) {
+        for id in self.ids.drain(..) {
+            self.app.unlisten(id);
+        }
+    }
+}
+
+async fn serve_connection<S>(app: AppHandle, stream: S)
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    let (events_tx, mut events) = mpsc::unbounded_channel::<Value>();
+    let mut subscriptions = Subscriptions {
+        app: app.clone(),
+        ids: Vec::new(),
+    };
+
+    loop {
+        let message = tokio::select! {
+            line = lines.next_line() => match line {
+                Ok(Some(line)) if line.trim().is_empty() => continue,
+                Ok(Some(line)) => {
+                    match handle_line(&app, &line, &events_tx, &mut subscriptions).await {
+                        Some(response) => response,
+                        None => continue,
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    debug!("Control connection read failed: {}", e);
+                    break;
+                }
+            },
+            Some(event) = events.recv() => event,
+        };
+        let mut line = message.to_string();
+        line.push('\n');
+        if writer.write_all(line.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+    debug!("Control connection closed");
+}
+
+/// Parse a line as a JSON-RPC request, or the error response to send back.
+fn parse_request(line: &str) -> Result<Request, Value> {
+    let value: Value = serde_json::from_str(line)
+        .map_err(|e| RpcError::new(PARSE_ERROR, e.to_string()).to_response(Value::Null))?;
+    let id = value.get("id").cloned().unwrap_or(Value::Null);
+    let request: Request = serde_json::from_value(value)
+        .map_err(|e| RpcError::new(INVALID_REQUEST, e.to_string()).to_response(id.clone()))?;
+    if request.jsonrpc != "2.0" {
+        return Err(RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\"").to_response(id));
+    }
+    Ok(request)
+}
+
+/// Answer one request; `None` for notifications.
+async fn handle_line(
+    app: &AppHandle,
+    line: &str,
+    events_tx: &UnboundedSender<Value>,
+    subscriptions: &mut Subscriptions,
+) -> Option<Value> {
+    let request = match parse_request(line) {
+        Ok(request) => request,
+        Err(response) => return Some(response),
+    };
+    debug!("Control request: {}", request.method);
+    let result = call(
+        app,
+        &request.method,
+        request.params,
+        events_tx,
+        subscriptions,
+    )
+    .await;
+    let id = request.id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => e.to_response(id),
+    })
+}
+
+async fn call(
+    app: &AppHandle,
+    method: &str,
+    params: Value,
+    events_tx: &UnboundedSender<Value>,
+    subscriptions: &mut Subscriptions,
+) -> Result<Value, RpcError> {
+    match method {
+        "get_state" => status(app),
+        "start_recording" => {
+            let params: StartParams = parse_params(params)?;
+            let binding_id = params.binding_id.as_deref().unwrap_or("transcribe");
+            if !is_transcribe_binding(binding_id) {
+                return Err(RpcError::new(
+                    INVALID_PARAMS,
+                    format!("Not a transcribe binding: {}", binding_id),
+                ));
+            }
+            coordinator(app)?.start_recording(binding_id);
+            status(app)
+        }
+        "stop_recording" => {
+            coordinator(app)?.stop_recording();
+            status(app)
+        }
+        "cancel" => {
+            cancel_current_operation(app);
+            Ok(Value::Null)
+        }
+        "get_last_transcription" => {
+            let last = app.state::<Arc<ControlManager>>().last_transcription();
+            Ok(json!(last))
+        }
+        "list_models" => {
+            let mut models: Vec<_> = app
+                .state::<Arc<ModelManager>>()
+                .get_available_models()
+                .into_iter()
+                .filter(|model| model.engine_type.is_transcription())
+                .collect();
+            models.sort_by(|a, b| a.id.cmp(&b.id));
+            Ok(json!(models))
+        }
+        "set_model" => {
+            let params: SetModelParams = parse_params(params)?;
+            set_active_model(
+                app.clone(),
+                app.state::<Arc<ModelManager>>(),
+                app.state::<Arc<TranscriptionManager>>(),
+                params.model_id,
+            )
+            .await
+            .map_err(|e| RpcError::new(APP_ERROR, e))?;
+            status(app)
+        }
+        "subscribe" => {
+            let params: SubscribeParams = parse_params(params)?;
+            let events = if params.events.is_empty() {
+                DEFAULT_EVENTS.iter().map(|e| e.to_string()).collect()
+            } else {
+                params.events
+            };
+            for event in &events {
+                let tx = events_tx.clone();
+                let name = event.clone();
+                let id = app.listen(event.clone(), move |e| {
+                    let payload = serde_json::from_str(e.payload()).unwrap_or(Value::Null);
+                    let _ = tx.send(json!({
+                        "jsonrpc": "2.0",
+                        "method": "event",
+                        "params": { "event": name, "payload": payload },
+                    }));
+                });
+                subscriptions.ids.push(id);
+            }
+            Ok(json!({ "subscribed": events }))
+        }
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method: {}", method),
+        )),
+    }
+}
+
+/// Missing params count as an empty object.
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn coordinator(app: &AppHandle) -> Result<tauri::State<'_, TranscriptionCoordinator>, RpcError> {
+    app.try_state::<TranscriptionCoordinator>()
+        .ok_or_else(|| RpcError::new(APP_ERROR, "Transcription coordinator not initialized"))
+}
+
+fn status(app: &AppHandle) -> Result<Value, RpcError> {
+    let state = coordinator(app)?
+        .state()
+        .ok_or_else(|| RpcError::new(APP_ERROR, "Transcription coordinator not responding"))?;
+    let status = ControlStatus {
+        state,
+        queued: app
+            .try_state::<DictationQueue>()
+            .map_or(0, |queue| queue.pending()),
+        selected_model: get_settings(app).selected_model,
+        loaded_model: app.state::<Arc<TranscriptionManager>>().get_current_model(),
+    };
+    Ok(json!(status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request_reports_errors_with_the_request_id() {
+        let parse_error = parse_request("{not json").unwrap_err();
+        assert_eq!(parse_error["error"]["code"], PARSE_ERROR);
+        assert_eq!(parse_error["id"], Value::Null);
+
+        let no_method = parse_request(r#"{"jsonrpc": "2.0", "id": 7}"#).unwrap_err();
+        assert_eq!(no_method["error"]["code"], INVALID_REQUEST);
+        assert_eq!(no_method["id"], 7);
+
+        let old_version =
+            parse_request(r#"{"jsonrpc": "1.0", "id": "a", "method": "get_state"}"#).unwrap_err();
+        assert_eq!(old_version["error"]["code"], INVALID_REQUEST);
+        assert_eq!(old_version["id"], "a");
+    }
+
+    #[test]
+    fn test_parse_request_tells_notifications_apart() {
+        let request = parse_request(r#"{"jsonrpc": "2.0", "method": "cancel"}"#).unwrap();
+        assert_eq!(request.method, "cancel");
+        assert!(request.id.is_none());
+        assert!(request.params.is_null());
+
+        let request =
+            parse_request(r#"{"jsonrpc": "2.0", "id": 1, "method": "set_model", "params": {"model_id": "small"}}"#)
+                .unwrap();
+        assert_eq!(request.id, Some(json!(1)));
+        let params: SetModelParams = serde_json::from_value(request.params).unwrap();
+        assert_eq!(params.model_id, "small");
+    }
+
+    #[test]
+    fn test_missing_params_use_defaults() {
+        let params: SubscribeParams = parse_params(Value::Null).unwrap();
+        assert!(params.events.is_empty());
+        let err = parse_params::<StartParams>(json!({ "binding_id": 3 })).unwrap_err();
+        assert_eq!(err.code, INVALID_PARAMS);
+        let err = parse_params::<SetModelParams>(Value::Null).unwrap_err();
+        assert_eq!(err.code, INVALID_PARAMS);
+    }
+}
@@ -0,0 +1,729 @@
+use crate::audio_toolkit::{read_wav_file, save_wav_file};
+use crate::settings::get_settings;
+use crate::transcript::TranscriptSegment;
+use anyhow::Result;
+use chrono::Utc;
+use log::{debug, info, warn};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Schema migrations, applied in order. `PRAGMA user_version` records how
+/// many have run, so append new steps and never edit old ones.
+const MIGRATIONS: &[&str] = &[
+    r#"
+    CREATE TABLE history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        created_at INTEGER NOT NULL,
+        raw_text TEXT NOT NULL,
+        processed_text TEXT,
+        model_id TEXT,
+        audio_duration_ms INTEGER NOT NULL,
+        transcription_ms INTEGER NOT NULL,
+        post_processing_ms INTEGER,
+        audio_file TEXT,
+        note_id TEXT
+    );
+    CREATE INDEX history_created_at ON history (created_at);
+"#,
+    r#"
+    CREATE TABLE revisions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        entry_id INTEGER NOT NULL REFERENCES history (id) ON DELETE CASCADE,
+        created_at INTEGER NOT NULL,
+        model_id TEXT NOT NULL,
+        raw_text TEXT NOT NULL,
+        processed_text TEXT,
+        transcription_ms INTEGER NOT NULL,
+        post_processing_ms INTEGER
+    );
+    CREATE INDEX revisions_entry_id ON revisions (entry_id);
+"#,
+    // Segment timestamps as JSON, for engines that report them
+    "ALTER TABLE history ADD COLUMN segments TEXT;",
+];
+
+const ENTRY_COLUMNS: &str = "id, created_at, raw_text, processed_text, model_id, \
+     audio_duration_ms, transcription_ms, post_processing_ms, audio_file, note_id, \
+     (SELECT COUNT(*) FROM revisions WHERE revisions.entry_id = history.id), \
+     segments IS NOT NULL";
+
+const REVISION_COLUMNS: &str = "id, entry_id, created_at, model_id, raw_text, processed_text, \
+     transcription_ms, post_processing_ms";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub created_at: i64, // Unix timestamp in milliseconds
+    /// Text exactly as the model produced it
+    pub raw_text: String,
+    /// Final text after conversion and post-processing, if it differs
+    pub processed_text: Option<String>,
+    pub model_id: Option<String>,
+    pub audio_duration_ms: u64,
+    pub transcription_ms: u64,
+    pub post_processing_ms: Option<u64>,
+    /// Absolute path of the saved recording, if audio was kept
+    pub audio_path: Option<String>,
+    pub note_id: Option<String>,
+    /// Number of re-transcriptions stored for this entry
+    pub revision_count: u32,
+    /// Whether segment timestamps were stored, so the entry can be exported
+    /// as subtitles
+    pub has_timestamps: bool,
+}
+
+impl HistoryEntry {
+    /// The text that was delivered to the user.
+    pub fn final_text(&self) -> &str {
+        self.processed_text.as_deref().unwrap_or(&self.raw_text)
+    }
+}
+
+/// A finished dictation, as reported by the transcription pipeline.
+#[derive(Debug, Clone, Default)]
+pub struct NewHistoryEntry {
+    pub raw_text: String,
+    pub processed_text: Option<String>,
+    pub model_id: Option<String>,
+    pub audio_duration_ms: u64,
+    pub transcription_ms: u64,
+    pub post_processing_ms: Option<u64>,
+    pub note_id: Option<String>,
+    /// Timed segments of `raw_text`; empty when the engine has none
+    pub segments: Vec<TranscriptSegment>,
+}
+
+/// A re-transcription of a stored recording with another model.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct HistoryRevision {
+    pub id: i64,
+    pub entry_id: i64,
+    pub created_at: i64, // Unix timestamp in milliseconds
+    pub model_id: String,
+    pub raw_text: String,
+    pub processed_text: Option<String>,
+    pub transcription_ms: u64,
+    pub post_processing_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NewHistoryRevision {
+    pub model_id: String,
+    pub raw_text: String,
+    pub processed_text: Option<String>,
+    pub transcription_ms: u64,
+    pub post_processing_ms: Option<u64>,
+}
+
+/// How far a reader of the history has got: the newest entry and revision it
+/// has seen. Ids only grow, so anything above them is new.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryCursor {
+    entry_id: i64,
+    revision_id: i64,
+}
+
+/// Keeps every dictation in a SQLite database in the app data directory,
+/// optionally with its audio as a WAV file in `recordings/`.
+pub struct HistoryManager {
+    app_handle: AppHandle,
+    conn: Mutex<Connection>,
+    recordings_dir: PathBuf,
+}
+
+impl HistoryManager {
+    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+        let app_data_dir = app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?;
+        let recordings_dir = app_data_dir.join("recordings");
+        fs::create_dir_all(&recordings_dir)?;
+
+        let db_path = app_data_dir.join("history.db");
+        let conn = Connection::open(&db_path)?;
+        migrate(&conn)?;
+        debug!("History database: {:?}", db_path);
+
+        let manager = Self {
+            app_handle: app_handle.clone(),
+            conn: Mutex::new(conn),
+            recordings_dir,
+        };
+
+        // Settings may have been tightened while the app was closed
+        if let Err(e) = manager.apply_retention() {
+            warn!("Failed to apply history retention: {}", e);
+        }
+
+        Ok(manager)
+    }
+
+    /// Record a dictation. `samples` (16 kHz mono) are written to a WAV file
+    /// when saving audio is enabled.
+    pub async fn add_entry(
+        &self,
+        entry: NewHistoryEntry,
+        samples: Option<&[f32]>,
+    ) -> Result<HistoryEntry> {
+        let settings = get_settings(&self.app_handle);
+        let created_at = Utc::now().timestamp_millis();
+
+        let id = {
+            let conn = self.conn.lock().unwrap();
+            insert_entry(&conn, created_at, &entry, None)?
+        };
+        debug!("Added history entry {}", id);
+
+        let samples = samples.filter(|s| settings.history_save_audio && !s.is_empty());
+        if let Some(samples) = samples {
+            // Ids are never reused, so neither are file names
+            let file_name = format!("jotline-{}.wav", id);
+            match save_wav_file(self.recordings_dir.join(&file_name), samples).await {
+                Ok(()) => {
+                    let updated = {
+                        let conn = self.conn.lock().unwrap();
+                        conn.execute(
+                            "UPDATE history SET audio_file = ?1 WHERE id = ?2",
+                            params![file_name, id],
+                        )?
+                    };
+                    // Deleted while the recording was being written
+                    if updated == 0 {
+                        self.remove_recordings(std::iter::once(&file_name));
+                    }
+                }
+                Err(e) => warn!("Failed to save recording for history: {}", e),
+            }
+        }
+
+        if let Err(e) = self.apply_retention() {
+            warn!("Failed to apply history retention: {}", e);
+        }
+        let _ = self.app_handle.emit("history-updated", ());
+
+        self.get_entry(id)?
+            .ok_or_else(|| anyhow::anyhow!("History entry {} was pruned on insert", id))
+    }
+
+    /// Entries newest first.
+    pub fn list_entries(&self, limit: usize, offset: usize) -> Result<Vec<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        query_entries(
+            &conn,
+            &self.recordings_dir,
+            &format!(
+                "SELECT {} FROM history ORDER BY created_at DESC, id DESC LIMIT ?1 OFFSET ?2",
+                ENTRY_COLUMNS
+            ),
+            params![limit as i64, offset as i64],
+        )
+    }
+
+    /// Entries whose text, or the text of one of their revisions, contains
+    /// `query`, newest first.
+    pub fn search_entries(&self, query: &str, limit: usize) -> Result<Vec<HistoryEntry>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return self.list_entries(limit, 0);
+        }
+        let pattern = format!("%{}%", escape_like(query));
+        let conn = self.conn.lock().unwrap();
+        query_entries(
+            &conn,
+            &self.recordings_dir,
+            &format!(
+                "SELECT {} FROM history \
+                 WHERE raw_text LIKE ?1 ESCAPE '\\' OR processed_text LIKE ?1 ESCAPE '\\' \
+                 OR id IN (SELECT entry_id FROM revisions \
+                     WHERE raw_text LIKE ?1 ESCAPE '\\' OR processed_text LIKE ?1 ESCAPE '\\') \
+                 ORDER BY created_at DESC, id DESC LIMIT ?2",
+                ENTRY_COLUMNS
+            ),
+            params![pattern, limit as i64],
+        )
+    }
+
+    pub fn get_entry(&self, id: i64) -> Result<Option<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let entry = conn
+            .query_row(
+                &format!("SELECT {} FROM history WHERE id = ?1", ENTRY_COLUMNS),
+                params![id],
+                |row| entry_from_row(row, &self.recordings_dir),
+            )
+            .optional()?;
+        Ok(entry)
+    }
+
+    /// The timed segments of an entry; empty when none were stored.
+    pub fn get_segments(&self, id: i64) -> Result<Vec<TranscriptSegment>> {
+        let conn = self.conn.lock().unwrap();
+        load_segments(&conn, id)
+    }
+
+    /// The saved samples of an entry, as 16 kHz mono.
+    pub fn load_recording(&self, id: i64) -> Result<Vec<f32>> {
+        let entry = self
+            .get_entry(id)?
+            .ok_or_else(|| anyhow::anyhow!("History entry not found: {}", id))?;
+        let path = entry
+            .audio_path
+            .ok_or_else(|| anyhow::anyhow!("No recording was saved for history entry {}", id))?;
+        read_wav_file(&path)
+    }
+
+    pub fn add_revision(
+        &self,
+        entry_id: i64,
+        revision: NewHistoryRevision,
+    ) -> Result<HistoryRevision> {
+        let revision = {
+            let conn = self.conn.lock().unwrap();
+            let id = insert_revision(&conn, entry_id, Utc::now().timestamp_millis(), &revision)?;
+            conn.query_row(
+                &format!("SELECT {} FROM revisions WHERE id = ?1", REVISION_COLUMNS),
+                params![id],
+                revision_from_row,
+            )?
+        };
+        info!(
+            "Added revision {} of history entry {} with {}",
+            revision.id, entry_id, revision.model_id
+        );
+        let _ = self.app_handle.emit("history-updated", ());
+        Ok(revision)
+    }
+
+    /// Ids of the entries added or re-transcribed since `cursor`, and the
+    /// cursor to pass next time.
+    pub fn changed_since(&self, cursor: HistoryCursor) -> Result<(Vec<i64>, HistoryCursor)> {
+        let conn = self.conn.lock().unwrap();
+        changed_since(&conn, cursor)
+    }
+
+    pub fn count_entries(&self) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    pub fn entry_ids(&self) -> Result<Vec<i64>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id FROM history")?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<i64>>>()?;
+        Ok(ids)
+    }
+
+    /// Revisions of an entry, oldest first.
+    pub fn list_revisions(&self, entry_id: i64) -> Result<Vec<HistoryRevision>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM revisions WHERE entry_id = ?1 ORDER BY created_at, id",
+            REVISION_COLUMNS
+        ))?;
+        let revisions = stmt
+            .query_map(params![entry_id], revision_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(revisions)
+    }
+
+    pub fn delete_entry(&self, id: i64) -> Result<()> {
+        let audio_file = {
+            let conn = self.conn.lock().unwrap();
+            let audio_file: Option<Option<String>> = conn
+                .query_row(
+                    "SELECT audio_file FROM history WHERE id = ?1",
+                    params![id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let Some(audio_file) = audio_file else {
+                return Err(anyhow::anyhow!("History entry not found: {}", id));
+            };
+            conn.execute("DELETE FROM history WHERE id = ?1", params![id])?;
+            audio_file
+        };
+
+        self.remove_recordings(audio_file.iter());
+        info!("Deleted history entry {}", id);
+        let _ = self.app_handle.emit("history-updated", ());
+        Ok(())
+    }
+
+    /// Drop entries beyond the configured count and age limits, along with
+    /// their recordings.
+    pub fn apply_retention(&self) -> Result<()> {
+        let settings = get_settings(&self.app_handle);
+        let cutoff = (settings.history_retention_days > 0).then(|| {
+            Utc::now().timestamp_millis() - settings.history_retention_days as i64 * 86_400_000
+        });
+
+        let pruned = {
+            let conn = self.conn.lock().unwrap();
+            prune(&conn, settings.history_limit as usize, cutoff)?
+        };
+        if !pruned.is_empty() {
+            debug!("Pruned {} recordings from history", pruned.len());
+            self.remove_recordings(pruned.iter());
+        }
+        Ok(())
+    }
+
+    fn remove_recordings<'a>(&self, files: impl Iterator<Item = &'a String>) {
+        for file in files {
+            let path = self.recordings_dir.join(file);
+            if let Err(e) = fs::remove_file(&path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("Failed to remove recording {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+}
+
+fn migrate(conn: &Connection) -> Result<()> {
+    // Off by default and set per connection; revisions rely on the cascade
+    conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        conn.execute_batch(&format!(
+            "BEGIN; {} PRAGMA user_version = {}; COMMIT;",
+            migration,
+            i + 1
+        ))?;
+        debug!("Applied history migration {}", i + 1);
+    }
+    Ok(())
+}
+
+fn insert_entry(
+    conn: &Connection,
+    created_at: i64,
+    entry: &NewHistoryEntry,
+    audio_file: Option<&str>,
+) -> Result<i64> {
+    let segments_json = if entry.segments.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&entry.segments)?)
+    };
+    conn.execute(
+        "INSERT INTO history (created_at, raw_text, processed_text, model_id, \
+         audio_duration_ms, transcription_ms, post_processing_ms, audio_file, note_id, \
+         segments) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            created_at,
+            entry.raw_text,
+            entry.processed_text,
+            entry.model_id,
+            entry.audio_duration_ms as i64,
+            entry.transcription_ms as i64,
+            entry.post_processing_ms.map(|ms| ms as i64),
+            audio_file,
+            entry.note_id,
+            segments_json,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn load_segments(conn: &Connection, id: i64) -> Result<Vec<TranscriptSegment>> {
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT segments FROM history WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| anyhow::anyhow!("History entry not found: {}", id))?;
+    match json {
+        Some(json) => Ok(serde_json::from_str(&json)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn insert_revision(
+    conn: &Connection,
+    entry_id: i64,
+    created_at: i64,
+    revision: &NewHistoryRevision,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO revisions (entry_id, created_at, model_id, raw_text, processed_text, \
+         transcription_ms, post_processing_ms) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            entry_id,
+            created_at,
+            revision.model_id,
+            revision.raw_text,
+            revision.processed_text,
+            revision.transcription_ms as i64,
+            revision.post_processing_ms.map(|ms| ms as i64),
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn changed_since(conn: &Connection, cursor: HistoryCursor) -> Result<(Vec<i64>, HistoryCursor)> {
+    // The newest entry may have been deleted, so never step back
+    let next = HistoryCursor {
+        entry_id: conn
+            .query_row("SELECT COALESCE(MAX(id), 0) FROM history", [], |row| {
+                row.get::<_, i64>(0)
+            })?
+            .max(cursor.entry_id),
+        revision_id: conn
+            .query_row("SELECT COALESCE(MAX(id), 0) FROM revisions", [], |row| {
+                row.get::<_, i64>(0)
+            })?
+            .max(cursor.revision_id),
+    };
+    let mut stmt = conn.prepare(
+        "SELECT id FROM history WHERE id > ?1 \
+         UNION SELECT entry_id FROM revisions WHERE id > ?2 ORDER BY 1",
+    )?;
+    let ids = stmt
+        .query_map(params![cursor.entry_id, cursor.revision_id], |row| {
+            row.get(0)
+        })?
+        .collect::<rusqlite::Result<Vec<i64>>>()?;
+    Ok((ids, next))
+}
+
+fn query_entries(
+    conn: &Connection,
+    recordings_dir: &Path,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> Result<Vec<HistoryEntry>> {
+    let mut stmt = conn.prepare(sql)?;
+    let entries = stmt
+        .query_map(params, |row| entry_from_row(row, recordings_dir))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(entries)
+}
+
+fn entry_from_row(row: &Row, recordings_dir: &Path) -> rusqlite::Result<HistoryEntry> {
+    let audio_file: Option<String> = row.get(8)?;
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        created_at: row.get(1)?,
+        raw_text: row.get(2)?,
+        processed_text: row.get(3)?,
+        model_id: row.get(4)?,
+        audio_duration_ms: row.get::<_, i64>(5)? as u64,
+        transcription_ms: row.get::<_, i64>(6)? as u64,
+        post_processing_ms: row.get::<_, Option<i64>>(7)?.map(|ms| ms as u64),
+        audio_path: audio_file.map(|f| recordings_dir.join(f).to_string_lossy().to_string()),
+        note_id: row.get(9)?,
+        revision_count: row.get(10)?,
+        has_timestamps: row.get(11)?,
+    })
+}
+
+fn revision_from_row(row: &Row) -> rusqlite::Result<HistoryRevision> {
+    Ok(HistoryRevision {
+        id: row.get(0)?,
+        entry_id: row.get(1)?,
+        created_at: row.get(2)?,
+        model_id: row.get(3)?,
+        raw_text: row.get(4)?,
+        processed_text: row.get(5)?,
+        transcription_ms: row.get::<_, i64>(6)? as u64,
+        post_processing_ms: row.get::<_, Option<i64>>(7)?.map(|ms| ms as u64),
+    })
+}
+
+/// Delete entries beyond the newest `limit` (0 keeps all) and those created
+/// before `cutoff`. Returns the recordings that belonged to them.
+fn prune(conn: &Connection, limit: usize, cutoff: Option<i64>) -> Result<Vec<String>> {
+    let condition = "(?1 > 0 AND id NOT IN \
+         (SELECT id FROM history ORDER BY created_at DESC, id DESC LIMIT ?1)) \
+         OR created_at < ?2";
+    let params = params![limit as i64, cutoff.unwrap_or(i64::MIN)];
+
+    let audio_files = {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT audio_file FROM history WHERE audio_file IS NOT NULL AND ({})",
+            condition
+        ))?;
+        let files = stmt
+            .query_map(params, |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        files
+    };
+    conn.execute(&format!("DELETE FROM history WHERE {}", condition), params)?;
+    Ok(audio_files)
+}
+
+/// Escape `%`, `_` and the escape character itself for a LIKE pattern.
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+        // Running again is a no-op
+        migrate(&conn).unwrap();
+        conn
+    }
+
+    fn add(conn: &Connection, created_at: i64, text: &str, audio: Option<&str>) -> i64 {
+        let entry = NewHistoryEntry {
+            raw_text: text.to_string(),
+            transcription_ms: 120,
+            audio_duration_ms: 2_000,
+            ..Default::default()
+        };
+        insert_entry(conn, created_at, &entry, audio).unwrap()
+    }
+
+    #[test]
+    fn test_insert_and_search() {
+        let conn = db();
+        let dir = Path::new("/recordings");
+        add(&conn, 1, "Call the dentist", Some("a.wav"));
+        add(&conn, 2, "Buy 100% rye bread", None);
+
+        let sql = format!(
+            "SELECT {} FROM history WHERE raw_text LIKE ?1 ESCAPE '\\' ORDER BY created_at DESC",
+            ENTRY_COLUMNS
+        );
+        let hits = query_entries(&conn, dir, &sql, params!["%DENTIST%"]).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].final_text(), "Call the dentist");
+        assert_eq!(hits[0].audio_path.as_deref(), Some("/recordings/a.wav"));
+        assert_eq!(hits[0].transcription_ms, 120);
+
+        let pattern = format!("%{}%", escape_like("100%"));
+        assert_eq!(
+            query_entries(&conn, dir, &sql, params![pattern])
+                .unwrap()
+                .len(),
+            1
+        );
+        let pattern = format!("%{}%", escape_like("0_"));
+        assert!(query_entries(&conn, dir, &sql, params![pattern])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_prune_by_count_and_age() {
+        let conn = db();
+        add(&conn, 10, "one", Some("1.wav"));
+        add(&conn, 20, "two", Some("2.wav"));
+        add(&conn, 30, "three", None);
+        add(&conn, 40, "four", Some("4.wav"));
+
+        assert!(prune(&conn, 0, None).unwrap().is_empty());
+        assert_eq!(prune(&conn, 3, None).unwrap(), vec!["1.wav"]);
+        assert_eq!(prune(&conn, 0, Some(35)).unwrap(), vec!["2.wav"]);
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_revisions_cascade_with_entry() {
+        let conn = db();
+        let id = add(&conn, 10, "call the dentist", Some("1.wav"));
+        let revision = NewHistoryRevision {
+            model_id: "parakeet-tdt-0.6b-v3".to_string(),
+            raw_text: "Call the dentist.".to_string(),
+            transcription_ms: 80,
+            ..Default::default()
+        };
+        insert_revision(&conn, id, 20, &revision).unwrap();
+
+        let sql = format!("SELECT {} FROM history", ENTRY_COLUMNS);
+        let entries = query_entries(&conn, Path::new("/r"), &sql, []).unwrap();
+        assert_eq!(entries[0].revision_count, 1);
+
+        let stored = conn
+            .query_row(
+                &format!("SELECT {} FROM revisions", REVISION_COLUMNS),
+                [],
+                revision_from_row,
+            )
+            .unwrap();
+        assert_eq!(stored.entry_id, id);
+        assert_eq!(stored.raw_text, "Call the dentist.");
+        assert_eq!(stored.post_processing_ms, None);
+
+        prune(&conn, 0, Some(15)).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM revisions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_changed_since() {
+        let conn = db();
+        let first = add(&conn, 10, "one", None);
+        let (ids, cursor) = changed_since(&conn, HistoryCursor::default()).unwrap();
+        assert_eq!(ids, vec![first]);
+        assert!(changed_since(&conn, cursor).unwrap().0.is_empty());
+
+        let second = add(&conn, 20, "two", None);
+        insert_revision(&conn, first, 30, &NewHistoryRevision::default()).unwrap();
+        let (ids, cursor) = changed_since(&conn, cursor).unwrap();
+        assert_eq!(ids, vec![first, second]);
+
+        // Deleting the newest entry does not make its id new again
+        conn.execute("DELETE FROM history WHERE id = ?1", params![second])
+            .unwrap();
+        let (ids, next) = changed_since(&conn, cursor).unwrap();
+        assert!(ids.is_empty());
+        assert_eq!(next, cursor);
+    }
+
+    #[test]
+    fn test_segments_round_trip() {
+        let conn = db();
+        let plain = add(&conn, 10, "no timestamps", None);
+        let segments = vec![TranscriptSegment {
+            start_ms: 0,
+            end_ms: 1_200,
+            text: "Hello there.".to_string(),
+        }];
+        let entry = NewHistoryEntry {
+            raw_text: "Hello there.".to_string(),
+            segments: segments.clone(),
+            ..Default::default()
+        };
+        let timed = insert_entry(&conn, 20, &entry, None).unwrap();
+
+        let sql = format!("SELECT {} FROM history ORDER BY id", ENTRY_COLUMNS);
+        let entries = query_entries(&conn, Path::new("/r"), &sql, []).unwrap();
+        assert!(!entries[0].has_timestamps);
+        assert!(entries[1].has_timestamps);
+        assert!(load_segments(&conn, plain).unwrap().is_empty());
+        assert_eq!(load_segments(&conn, timed).unwrap(), segments);
+        assert!(load_segments(&conn, 99).is_err());
+    }
+}
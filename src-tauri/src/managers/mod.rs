@@ -1,7 +1,15 @@
 pub mod audio;
+pub mod control;
+pub mod history;
 pub mod model;
+pub mod notes;
+pub mod partial_transcript;
+pub mod search;
+pub mod segments;
+pub mod text_search;
 #[cfg(feature = "transcription")]
 pub mod transcription;
 #[cfg(not(feature = "transcription"))]
 #[path = "transcription_mock.rs"]
 pub mod transcription;
+pub mod watch_folder;
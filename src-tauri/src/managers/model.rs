@@ -1,4 +1,4 @@
-use crate::settings::{get_settings, write_settings};
+use crate::host::Host;
 use anyhow::Result;
 use flate2::read::GzDecoder;
 use futures_util::StreamExt;
@@ -14,7 +14,6 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tar::Archive;
-use tauri::{AppHandle, Emitter, Manager};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub enum EngineType {
@@ -23,8 +22,26 @@ pub enum EngineType {
     Moonshine,
     MoonshineStreaming,
     SenseVoice,
+    /// Sentence encoder used for semantic search, not for transcription
+    Embedding,
 }
 
+impl EngineType {
+    pub fn is_transcription(&self) -> bool {
+        !matches!(self, EngineType::Embedding)
+    }
+}
+
+/// Model used to embed notes for semantic search
+pub const EMBEDDING_MODEL_ID: &str = "all-minilm-l6-v2";
+
+/// Files fetched individually for the embedding model, as
+/// (path relative to the model URL, filename inside the model directory)
+const EMBEDDING_MODEL_FILES: &[(&str, &str)] = &[
+    ("onnx/model.onnx", crate::search::MODEL_FILE),
+    ("vocab.txt", crate::search::VOCAB_FILE),
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct ModelInfo {
     pub id: String,
@@ -55,7 +72,7 @@ pub struct DownloadProgress {
 }
 
 pub struct ModelManager {
-    app_handle: AppHandle,
+    host: Arc<dyn Host>,
     models_dir: PathBuf,
     available_models: Mutex<HashMap<String, ModelInfo>>,
     cancel_flags: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
@@ -63,13 +80,9 @@ pub struct ModelManager {
 }
 
 impl ModelManager {
-    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+    pub fn new(host: Arc<dyn Host>) -> Result<Self> {
         // Create models directory in app data
-        let models_dir = app_handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?
-            .join("models");
+        let models_dir = host.app_data_dir()?.join("models");
 
         if !models_dir.exists() {
             fs::create_dir_all(&models_dir)?;
@@ -397,13 +410,41 @@ impl ModelManager {
             },
         );
 
+        // Sentence encoder for semantic search. Published as separate files
+        // rather than an archive, see EMBEDDING_MODEL_FILES.
+        available_models.insert(
+            EMBEDDING_MODEL_ID.to_string(),
+            ModelInfo {
+                id: EMBEDDING_MODEL_ID.to_string(),
+                name: "MiniLM L6".to_string(),
+                description: "Small sentence encoder for searching notes by meaning.".to_string(),
+                filename: "all-MiniLM-L6-v2".to_string(), // Directory name
+                url: Some(
+                    "https://huggingface.co/sentence-transformers/all-MiniLM-L6-v2/resolve/main"
+                        .to_string(),
+                ),
+                size_mb: 91,
+                is_downloaded: false,
+                is_downloading: false,
+                partial_size: 0,
+                is_directory: true,
+                engine_type: EngineType::Embedding,
+                accuracy_score: 0.0,
+                speed_score: 0.0,
+                supports_translation: false,
+                is_recommended: false,
+                supported_languages: vec!["en".to_string()],
+                is_custom: false,
+            },
+        );
+
         // Auto-discover custom Whisper models (.bin files) in the models directory
         if let Err(e) = Self::discover_custom_whisper_models(&models_dir, &mut available_models) {
             warn!("Failed to discover custom models: {}", e);
         }
 
         let manager = Self {
-            app_handle: app_handle.clone(),
+            host,
             models_dir,
             available_models: Mutex::new(available_models),
             cancel_flags: Arc::new(Mutex::new(HashMap::new())),
@@ -437,12 +478,11 @@ impl ModelManager {
         let bundled_models = ["ggml-small.bin"]; // Add other bundled models here if any
 
         for filename in &bundled_models {
-            let bundled_path = self.app_handle.path().resolve(
-                format!("resources/models/{}", filename),
-                tauri::path::BaseDirectory::Resource,
-            );
+            let bundled_path = self
+                .host
+                .resource_path(&format!("resources/models/{}", filename));
 
-            if let Ok(bundled_path) = bundled_path {
+            if let Some(bundled_path) = bundled_path {
                 if bundled_path.exists() {
                     let user_path = self.models_dir.join(filename);
 
@@ -485,8 +525,18 @@ impl ModelManager {
                 model.is_downloaded = model_path.exists() && model_path.is_dir();
                 model.is_downloading = false;
 
-                // Get partial file size if it exists (for the .tar.gz being downloaded)
-                if partial_path.exists() {
+                // Get partial file size if it exists (for the .tar.gz being downloaded,
+                // or the directory of files for multi-file models)
+                if partial_path.is_dir() {
+                    model.partial_size = fs::read_dir(&partial_path)
+                        .map(|entries| {
+                            entries
+                                .filter_map(|e| e.ok()?.metadata().ok())
+                                .map(|m| m.len())
+                                .sum()
+                        })
+                        .unwrap_or(0);
+                } else if partial_path.exists() {
                     model.partial_size = partial_path.metadata().map(|m| m.len()).unwrap_or(0);
                 } else {
                     model.partial_size = 0;
@@ -512,7 +562,7 @@ impl ModelManager {
     }
 
     fn auto_select_model_if_needed(&self) -> Result<()> {
-        let mut settings = get_settings(&self.app_handle);
+        let mut settings = self.host.settings();
 
         // Clear stale selection: selected model is set but doesn't exist
         // in available_models (e.g. deleted custom model file)
@@ -527,7 +577,7 @@ impl ModelManager {
                     settings.selected_model
                 );
                 settings.selected_model = String::new();
-                write_settings(&self.app_handle, settings.clone());
+                self.host.write_settings(settings.clone());
             }
         }
 
@@ -535,7 +585,10 @@ impl ModelManager {
         if settings.selected_model.is_empty() {
             // Find the first available (downloaded) model
             let models = self.available_models.lock().unwrap();
-            if let Some(available_model) = models.values().find(|model| model.is_downloaded) {
+            if let Some(available_model) = models
+                .values()
+                .find(|model| model.is_downloaded && model.engine_type.is_transcription())
+            {
                 info!(
                     "Auto-selecting model: {} ({})",
                     available_model.id, available_model.name
@@ -544,7 +597,7 @@ impl ModelManager {
                 // Update settings with the selected model
                 let mut updated_settings = settings;
                 updated_settings.selected_model = available_model.id.clone();
-                write_settings(&self.app_handle, updated_settings);
+                self.host.write_settings(updated_settings);
 
                 info!("Successfully auto-selected model: {}", available_model.id);
             }
@@ -683,7 +736,15 @@ impl ModelManager {
 
         let url = model_info
             .url
+            .clone()
             .ok_or_else(|| anyhow::anyhow!("No download URL for model"))?;
+
+        if matches!(model_info.engine_type, EngineType::Embedding) {
+            return self
+                .download_model_files(&model_info, &url, EMBEDDING_MODEL_FILES)
+                .await;
+        }
+
         let model_path = self.models_dir.join(&model_info.filename);
         let partial_path = self
             .models_dir
@@ -800,9 +861,7 @@ impl ModelManager {
                 0.0
             },
         };
-        let _ = self
-            .app_handle
-            .emit("model-download-progress", &initial_progress);
+        self.host.emit("model-download-progress", &initial_progress);
 
         // Throttle progress events to max 10/sec (100ms intervals)
         let mut last_emit = Instant::now();
@@ -861,7 +920,7 @@ impl ModelManager {
                     total: total_size,
                     percentage,
                 };
-                let _ = self.app_handle.emit("model-download-progress", &progress);
+                self.host.emit("model-download-progress", &progress);
                 last_emit = Instant::now();
             }
         }
@@ -877,9 +936,7 @@ impl ModelManager {
                 100.0
             },
         };
-        let _ = self
-            .app_handle
-            .emit("model-download-progress", &final_progress);
+        self.host.emit("model-download-progress", &final_progress);
 
         file.flush()?;
         drop(file); // Ensure file is closed before moving
@@ -913,7 +970,7 @@ impl ModelManager {
             }
 
             // Emit extraction started event
-            let _ = self.app_handle.emit("model-extraction-started", model_id);
+            self.host.emit("model-extraction-started", model_id);
             info!("Extracting archive for directory-based model: {}", model_id);
 
             // Use a temporary extraction directory to ensure atomic operations
@@ -945,7 +1002,7 @@ impl ModelManager {
                     let mut extracting = self.extracting_models.lock().unwrap();
                     extracting.remove(model_id);
                 }
-                let _ = self.app_handle.emit(
+                self.host.emit(
                     "model-extraction-failed",
                     &serde_json::json!({
                         "model_id": model_id,
@@ -985,7 +1042,7 @@ impl ModelManager {
                 extracting.remove(model_id);
             }
             // Emit extraction completed event
-            let _ = self.app_handle.emit("model-extraction-completed", model_id);
+            self.host.emit("model-extraction-completed", model_id);
 
             // Remove the downloaded tar.gz file
             let _ = fs::remove_file(&partial_path);
@@ -1011,7 +1068,7 @@ impl ModelManager {
         }
 
         // Emit completion event
-        let _ = self.app_handle.emit("model-download-complete", model_id);
+        self.host.emit("model-download-complete", model_id);
 
         info!(
             "Successfully downloaded model {} to {:?}",
@@ -1021,6 +1078,147 @@ impl ModelManager {
         Ok(())
     }
 
+    /// Download a directory-based model published as individual files under
+    /// `base_url`. Files are collected in `<filename>.partial/` and the
+    /// directory is renamed into place once all of them are complete; files
+    /// finished by an earlier, interrupted attempt are not fetched again.
+    async fn download_model_files(
+        &self,
+        model_info: &ModelInfo,
+        base_url: &str,
+        files: &[(&str, &str)],
+    ) -> Result<()> {
+        let model_id = model_info.id.as_str();
+        let model_path = self.models_dir.join(&model_info.filename);
+        let partial_dir = self
+            .models_dir
+            .join(format!("{}.partial", &model_info.filename));
+
+        if model_path.is_dir() {
+            let _ = fs::remove_dir_all(&partial_dir);
+            self.update_download_status()?;
+            return Ok(());
+        }
+        fs::create_dir_all(&partial_dir)?;
+
+        let set_downloading = |downloading: bool| {
+            let mut models = self.available_models.lock().unwrap();
+            if let Some(model) = models.get_mut(model_id) {
+                model.is_downloading = downloading;
+            }
+        };
+        set_downloading(true);
+
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        {
+            let mut flags = self.cancel_flags.lock().unwrap();
+            flags.insert(model_id.to_string(), cancel_flag.clone());
+        }
+        let remove_cancel_flag = || {
+            let mut flags = self.cancel_flags.lock().unwrap();
+            flags.remove(model_id);
+        };
+
+        // Progress is reported against the advertised model size since the
+        // file sizes are only known once each request starts
+        let total = model_info.size_mb * 1024 * 1024;
+        let mut downloaded: u64 = 0;
+        let mut last_emit = Instant::now();
+        let throttle_duration = Duration::from_millis(100);
+        let client = reqwest::Client::new();
+
+        for (remote_path, filename) in files {
+            let target = partial_dir.join(filename);
+            if target.exists() {
+                downloaded += target.metadata().map(|m| m.len()).unwrap_or(0);
+                continue;
+            }
+
+            let url = format!("{}/{}", base_url.trim_end_matches('/'), remote_path);
+            info!("Downloading {} for model {}", url, model_id);
+            let response = match client.get(&url).send().await {
+                Ok(response) if response.status().is_success() => response,
+                Ok(response) => {
+                    set_downloading(false);
+                    remove_cancel_flag();
+                    return Err(anyhow::anyhow!(
+                        "Failed to download {}: HTTP {}",
+                        remote_path,
+                        response.status()
+                    ));
+                }
+                Err(e) => {
+                    set_downloading(false);
+                    remove_cancel_flag();
+                    return Err(e.into());
+                }
+            };
+
+            let file_part = partial_dir.join(format!("{}.part", filename));
+            let mut file = File::create(&file_part)?;
+            let mut stream = response.bytes_stream();
+            while let Some(chunk) = stream.next().await {
+                if cancel_flag.load(Ordering::Relaxed) {
+                    drop(file);
+                    let _ = fs::remove_file(&file_part);
+                    info!("Download cancelled for: {}", model_id);
+                    set_downloading(false);
+                    remove_cancel_flag();
+                    // Completed files are kept for the next attempt
+                    return Ok(());
+                }
+
+                let chunk = chunk.inspect_err(|_| set_downloading(false))?;
+                file.write_all(&chunk)?;
+                downloaded += chunk.len() as u64;
+
+                if last_emit.elapsed() >= throttle_duration {
+                    let progress = DownloadProgress {
+                        model_id: model_id.to_string(),
+                        downloaded,
+                        total: total.max(downloaded),
+                        percentage: (downloaded as f64 / total.max(downloaded) as f64) * 100.0,
+                    };
+                    self.host.emit("model-download-progress", &progress);
+                    last_emit = Instant::now();
+                }
+            }
+            file.flush()?;
+            drop(file);
+            fs::rename(&file_part, &target)?;
+        }
+
+        self.host.emit(
+            "model-download-progress",
+            &DownloadProgress {
+                model_id: model_id.to_string(),
+                downloaded,
+                total: downloaded,
+                percentage: 100.0,
+            },
+        );
+
+        fs::rename(&partial_dir, &model_path)?;
+
+        {
+            let mut models = self.available_models.lock().unwrap();
+            if let Some(model) = models.get_mut(model_id) {
+                model.is_downloading = false;
+                model.is_downloaded = true;
+                model.partial_size = 0;
+            }
+        }
+        remove_cancel_flag();
+
+        self.host.emit("model-download-complete", model_id);
+        info!(
+            "Successfully downloaded model {} to {:?}",
+            model_id, model_path
+        );
+
+        Ok(())
+    }
+
     pub fn delete_model(&self, model_id: &str) -> Result<()> {
         debug!("ModelManager: delete_model called for: {}", model_id);
 
@@ -1061,8 +1259,13 @@ impl ModelManager {
             }
         }
 
-        // Delete partial file if it exists (same for both types)
-        if partial_path.exists() {
+        // Delete partial file if it exists (same for both types). Multi-file
+        // models download into a partial directory instead.
+        if partial_path.is_dir() {
+            info!("Deleting partial directory at: {:?}", partial_path);
+            fs::remove_dir_all(&partial_path)?;
+            deleted_something = true;
+        } else if partial_path.exists() {
             info!("Deleting partial file at: {:?}", partial_path);
             fs::remove_file(&partial_path)?;
             info!("Partial file deleted successfully");
@@ -1086,12 +1289,11 @@ impl ModelManager {
         }
 
         // Emit event to notify UI
-        let _ = self.app_handle.emit("model-deleted", model_id);
+        self.host.emit("model-deleted", model_id);
 
         Ok(())
     }
 
-    #[cfg(feature = "transcription")]
     pub fn get_model_path(&self, model_id: &str) -> Result<PathBuf> {
         let model_info = self
             .get_model_info(model_id)
@@ -1163,7 +1365,7 @@ impl ModelManager {
         self.update_download_status()?;
 
         // Emit cancellation event so all UI components can clear their state
-        let _ = self.app_handle.emit("model-download-cancelled", model_id);
+        self.host.emit("model-download-cancelled", model_id);
 
         info!("Download cancellation initiated for: {}", model_id);
         Ok(())
@@ -0,0 +1,775 @@
+use crate::context::CaptureContext;
+use crate::host::Host;
+use crate::notes::{
+    extract_keywords, keyword_tag, project_folder, set_frequency_dir, split_related,
+    with_frontmatter, with_related, Frontmatter,
+};
+use crate::settings::get_settings;
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Maximum number of characters taken from the transcription for the filename slug
+const MAX_SLUG_LEN: usize = 48;
+
+/// Maximum number of characters used for a note title
+const MAX_TITLE_LEN: usize = 80;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct NoteInfo {
+    /// Path of the note relative to the notes directory, always using `/` separators
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    pub created_at: i64,  // Unix timestamp in milliseconds
+    pub modified_at: i64, // Unix timestamp in milliseconds
+    pub size_bytes: u64,
+    pub project: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ProjectInfo {
+    pub name: String,
+    /// Folder inside the notes directory holding this project's notes
+    pub folder: String,
+    pub note_count: usize,
+    pub last_modified_at: i64, // Unix timestamp in milliseconds, 0 if empty
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct NoteMovedEvent<'a> {
+    from_id: &'a str,
+    note: &'a NoteInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct Note {
+    pub info: NoteInfo,
+    pub content: String,
+}
+
+/// Owns the on-disk notes directory. Every finished transcription is written
+/// here as a plain Markdown file.
+pub struct NoteManager {
+    app_handle: AppHandle,
+}
+
+impl NoteManager {
+    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+        let manager = Self {
+            app_handle: app_handle.clone(),
+        };
+
+        let notes_dir = manager.notes_dir()?;
+        if !notes_dir.exists() {
+            fs::create_dir_all(&notes_dir)?;
+        }
+        debug!("Notes directory: {:?}", notes_dir);
+
+        match app_handle.resource_path("resources/wordfreq") {
+            Some(dir) => set_frequency_dir(dir),
+            None => warn!("Word frequency tables not found; tags are ranked by length"),
+        }
+
+        Ok(manager)
+    }
+
+    /// Resolve the notes directory from settings, falling back to `<app data>/notes`.
+    /// Resolved on every call so that changing the setting takes effect immediately.
+    pub fn notes_dir(&self) -> Result<PathBuf> {
+        let settings = get_settings(&self.app_handle);
+        match settings
+            .notes_directory
+            .as_deref()
+            .map(str::trim)
+            .filter(|dir| !dir.is_empty())
+        {
+            Some(dir) => Ok(PathBuf::from(dir)),
+            None => Ok(self
+                .app_handle
+                .path()
+                .app_data_dir()
+                .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?
+                .join("notes")),
+        }
+    }
+
+    /// Save a transcription as a new note. When `context` is given it is
+    /// written as YAML frontmatter ahead of the text.
+    ///
+    /// The project is taken from `project` (a spoken override), then the
+    /// `active_project` setting, then the captured context. Notes with a
+    /// project are filed into that project's folder. Keywords from the text
+    /// are added as `tags` when auto-tagging is on.
+    pub fn save_transcription(
+        &self,
+        text: &str,
+        context: Option<&CaptureContext>,
+        project: Option<&str>,
+    ) -> Result<NoteInfo> {
+        let notes_dir = self.notes_dir()?;
+        let settings = get_settings(&self.app_handle);
+        let project = project
+            .map(str::to_string)
+            .or(settings.active_project)
+            .or_else(|| context.and_then(|c| c.get("project")).map(str::to_string))
+            .filter(|p| !p.trim().is_empty());
+
+        let mut frontmatter = context
+            .map(CaptureContext::to_frontmatter)
+            .unwrap_or_default();
+        let dir = match &project {
+            Some(name) => {
+                frontmatter.set_text("project", name.trim());
+                project_dir(&notes_dir, name)?
+            }
+            None => notes_dir.clone(),
+        };
+        fs::create_dir_all(&dir)?;
+
+        if settings.auto_tag_notes {
+            let tags: Vec<String> = extract_keywords(text, &settings.selected_language)
+                .iter()
+                .map(|k| keyword_tag(k))
+                .collect();
+            if !tags.is_empty() {
+                frontmatter.set_list("tags", tags);
+            }
+        }
+
+        let now = Local::now();
+        let content = with_frontmatter(&frontmatter, &format!("{}\n", text.trim()));
+        let path = write_new_note(&dir, &note_file_stem(&now, text), &content)?;
+        let info = note_info(&notes_dir, &path)?;
+
+        info!("Saved note {}", info.id);
+        let _ = self.app_handle.emit("note-saved", &info);
+
+        Ok(info)
+    }
+
+    /// Append a transcription under a timestamp to the Markdown file at
+    /// `path`, or to the most recently modified note when no path is given.
+    /// Relative paths are inside the notes directory. Without any notes yet,
+    /// a new one is saved instead. Returns the note when the file is inside
+    /// the notes directory.
+    pub fn append_transcription(&self, text: &str, path: Option<&str>) -> Result<Option<NoteInfo>> {
+        let notes_dir = self.notes_dir()?;
+        let path = match path.map(str::trim).filter(|p| !p.is_empty()) {
+            Some(path) => resolve_append_path(&notes_dir, path)?,
+            None => match self.list_notes()?.into_iter().next() {
+                Some(note) => resolve_note_path(&notes_dir, &note.id)?,
+                None => return self.save_transcription(text, None, None).map(Some),
+            },
+        };
+
+        let existed = path.is_file();
+        let content = if existed {
+            fs::read_to_string(&path)?
+        } else {
+            String::new()
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(&path, &append_entry(&content, text, &Local::now()))?;
+        info!("Appended transcription to {:?}", path);
+
+        if note_id(&notes_dir, &path).is_err() {
+            return Ok(None);
+        }
+        let info = note_info(&notes_dir, &path)?;
+        let event = if existed {
+            "note-updated"
+        } else {
+            "note-saved"
+        };
+        let _ = self.app_handle.emit(event, &info);
+        Ok(Some(info))
+    }
+
+    pub fn list_notes(&self) -> Result<Vec<NoteInfo>> {
+        let notes_dir = self.notes_dir()?;
+        if !notes_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut notes = Vec::new();
+        collect_notes(&notes_dir, &notes_dir, &mut notes)?;
+
+        // Newest first
+        notes.sort_by_key(|n| std::cmp::Reverse(n.modified_at));
+        Ok(notes)
+    }
+
+    pub fn get_note(&self, id: &str) -> Result<Note> {
+        let notes_dir = self.notes_dir()?;
+        let path = resolve_note_path(&notes_dir, id)?;
+        let content = fs::read_to_string(&path)?;
+        let info = note_info(&notes_dir, &path)?;
+        Ok(Note { info, content })
+    }
+
+    pub fn get_note_info(&self, id: &str) -> Result<NoteInfo> {
+        let notes_dir = self.notes_dir()?;
+        note_info(&notes_dir, &resolve_note_path(&notes_dir, id)?)
+    }
+
+    pub fn update_note(&self, id: &str, content: &str) -> Result<NoteInfo> {
+        let notes_dir = self.notes_dir()?;
+        let path = resolve_note_path(&notes_dir, id)?;
+        if !path.is_file() {
+            return Err(anyhow::anyhow!("Note not found: {}", id));
+        }
+
+        write_atomic(&path, content)?;
+
+        let info = note_info(&notes_dir, &path)?;
+        let _ = self.app_handle.emit("note-updated", &info);
+        Ok(info)
+    }
+
+    pub fn delete_note(&self, id: &str) -> Result<()> {
+        let notes_dir = self.notes_dir()?;
+        let path = resolve_note_path(&notes_dir, id)?;
+        if !path.is_file() {
+            return Err(anyhow::anyhow!("Note not found: {}", id));
+        }
+
+        fs::remove_file(&path)?;
+        info!("Deleted note {}", id);
+        let _ = self.app_handle.emit("note-deleted", id);
+        Ok(())
+    }
+
+    /// All projects that have notes or a folder, sorted by name.
+    pub fn list_projects(&self) -> Result<Vec<ProjectInfo>> {
+        let mut projects: Vec<ProjectInfo> = Vec::new();
+
+        // Notes are newest first, so each project keeps its most recent spelling
+        for note in self.list_notes()? {
+            let Some(name) = note.project.as_deref() else {
+                continue;
+            };
+            let Some(folder) = project_folder(name) else {
+                continue;
+            };
+            match projects.iter_mut().find(|p| p.folder == folder) {
+                Some(project) => {
+                    project.note_count += 1;
+                    project.last_modified_at = project.last_modified_at.max(note.modified_at);
+                }
+                None => projects.push(ProjectInfo {
+                    name: name.to_string(),
+                    folder,
+                    note_count: 1,
+                    last_modified_at: note.modified_at,
+                }),
+            }
+        }
+
+        // Folders created by hand show up even before they contain notes
+        for folder in self.project_folders()? {
+            if !projects.iter().any(|p| p.folder == folder) {
+                projects.push(ProjectInfo {
+                    name: folder.clone(),
+                    folder,
+                    note_count: 0,
+                    last_modified_at: 0,
+                });
+            }
+        }
+
+        projects.sort_by_key(|p| p.name.to_lowercase());
+        Ok(projects)
+    }
+
+    /// The project folders in the notes directory, without reading any note.
+    pub fn project_folders(&self) -> Result<Vec<String>> {
+        let notes_dir = self.notes_dir()?;
+        if !notes_dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut folders = Vec::new();
+        for entry in fs::read_dir(&notes_dir)?.flatten() {
+            let folder = entry.file_name().to_string_lossy().to_string();
+            if !folder.starts_with('.') && entry.path().is_dir() {
+                folders.push(folder);
+            }
+        }
+        Ok(folders)
+    }
+
+    /// Move a note into `project`'s folder, or back to the top level when
+    /// `project` is `None`, updating its frontmatter to match.
+    pub fn move_note(&self, id: &str, project: Option<&str>) -> Result<NoteInfo> {
+        let notes_dir = self.notes_dir()?;
+        let path = resolve_note_path(&notes_dir, id)?;
+        if !path.is_file() {
+            return Err(anyhow::anyhow!("Note not found: {}", id));
+        }
+
+        let project = project.map(str::trim).filter(|p| !p.is_empty());
+        let target_dir = match project {
+            Some(name) => project_dir(&notes_dir, name)?,
+            None => notes_dir.clone(),
+        };
+        fs::create_dir_all(&target_dir)?;
+
+        let content = fs::read_to_string(&path)?;
+        let (mut frontmatter, body) = Frontmatter::parse(&content);
+        match project {
+            Some(name) => frontmatter.set_text("project", name),
+            None => frontmatter.remove("project"),
+        }
+        let content = with_frontmatter(&frontmatter, body);
+
+        let new_path = if path.parent() == Some(target_dir.as_path()) {
+            write_atomic(&path, &content)?;
+            path
+        } else {
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let new_path = write_new_note(&target_dir, &stem, &content)?;
+            fs::remove_file(&path)?;
+            new_path
+        };
+
+        let info = note_info(&notes_dir, &new_path)?;
+        info!("Moved note {} to {}", id, info.id);
+        let _ = self.app_handle.emit(
+            "note-moved",
+            NoteMovedEvent {
+                from_id: id,
+                note: &info,
+            },
+        );
+        Ok(info)
+    }
+}
+
+fn project_dir(notes_dir: &Path, project: &str) -> Result<PathBuf> {
+    let folder = project_folder(project)
+        .ok_or_else(|| anyhow::anyhow!("Invalid project name: {}", project))?;
+    Ok(notes_dir.join(folder))
+}
+
+/// Write to a sibling temp file first so a crash never leaves a truncated note.
+fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let tmp_path = path.with_extension("md.tmp");
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Add `text` to the end of `content` below a timestamp, keeping any
+/// frontmatter first and a generated "Related" section last. The `---`
+/// separator only goes between entries; at the top of a note it would read
+/// as the start of frontmatter.
+fn append_entry(content: &str, text: &str, now: &DateTime<Local>) -> String {
+    let (_, after_frontmatter) = Frontmatter::parse(content);
+    let frontmatter = &content[..content.len() - after_frontmatter.len()];
+    let (body, links) = split_related(after_frontmatter);
+    let timestamp = format!("**{}**", now.format("%Y-%m-%d %H:%M"));
+    let body = body.trim_end();
+    let appended = if body.trim_start().is_empty() {
+        let gap = if frontmatter.is_empty() { "" } else { "\n" };
+        format!("{}{}{}\n\n{}\n", frontmatter, gap, timestamp, text.trim())
+    } else {
+        format!(
+            "{}{}\n\n---\n\n{}\n\n{}\n",
+            frontmatter,
+            body,
+            timestamp,
+            text.trim()
+        )
+    };
+    if links.is_empty() {
+        appended
+    } else {
+        with_related(&appended, &links)
+    }
+}
+
+/// Build a filename stem like `2025-01-31_142501-call-the-dentist-tomorrow`.
+fn note_file_stem(now: &DateTime<Local>, text: &str) -> String {
+    let timestamp = now.format("%Y-%m-%d_%H%M%S").to_string();
+    let slug = slugify(text);
+    if slug.is_empty() {
+        timestamp
+    } else {
+        format!("{}-{}", timestamp, slug)
+    }
+}
+
+/// Lowercase the leading words of `text` and join them with `-`, keeping only
+/// alphanumeric characters. Non-Latin scripts are kept as-is.
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for word in text.split_whitespace() {
+        let cleaned: String = word
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect();
+        if cleaned.is_empty() {
+            continue;
+        }
+        let separator = if slug.is_empty() { 0 } else { 1 };
+        if slug.chars().count() + separator + cleaned.chars().count() > MAX_SLUG_LEN {
+            if slug.is_empty() {
+                slug = cleaned.chars().take(MAX_SLUG_LEN).collect();
+            }
+            break;
+        }
+        if separator == 1 {
+            slug.push('-');
+        }
+        slug.push_str(&cleaned);
+    }
+    slug
+}
+
+/// Create `<stem>.md` in `dir`, appending `-2`, `-3`, ... if the name is taken.
+/// Uses `create_new` so two captures in the same second can never overwrite each other.
+fn write_new_note(dir: &Path, stem: &str, content: &str) -> Result<PathBuf> {
+    for n in 1..=1000 {
+        let filename = if n == 1 {
+            format!("{}.md", stem)
+        } else {
+            format!("{}-{}.md", stem, n)
+        };
+        let path = dir.join(filename);
+
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(content.as_bytes())?;
+                file.flush()?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Err(anyhow::anyhow!(
+        "Could not find a free filename for note '{}'",
+        stem
+    ))
+}
+
+/// Map a note id back to a path inside `notes_dir`, rejecting anything that
+/// would escape the directory.
+fn resolve_note_path(notes_dir: &Path, id: &str) -> Result<PathBuf> {
+    let relative = Path::new(id);
+    if id.is_empty()
+        || relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return Err(anyhow::anyhow!("Invalid note id: {}", id));
+    }
+    if relative.extension().and_then(|e| e.to_str()) != Some("md") {
+        return Err(anyhow::anyhow!("Not a Markdown note: {}", id));
+    }
+    Ok(notes_dir.join(relative))
+}
+
+/// Check that `path` names a Markdown file an append can target: either an
+/// absolute path or one relative to the notes directory that stays inside it.
+pub fn validate_append_target(path: &str) -> Result<()> {
+    let path = Path::new(path);
+    if path.extension().and_then(|e| e.to_str()) != Some("md") {
+        return Err(anyhow::anyhow!(
+            "Append target is not a Markdown file: {:?}",
+            path
+        ));
+    }
+    if !path.is_absolute()
+        && path
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return Err(anyhow::anyhow!(
+            "Append target must be absolute or inside the notes directory: {:?}",
+            path
+        ));
+    }
+    Ok(())
+}
+
+/// Resolve an append target against `notes_dir`. Folders are only created
+/// inside the notes directory, so an absolute path needs an existing folder.
+fn resolve_append_path(notes_dir: &Path, path: &str) -> Result<PathBuf> {
+    validate_append_target(path)?;
+    let path = Path::new(path);
+    if !path.is_absolute() {
+        return Ok(notes_dir.join(path));
+    }
+    if !path.parent().is_some_and(Path::is_dir) {
+        return Err(anyhow::anyhow!(
+            "Folder of append target does not exist: {:?}",
+            path
+        ));
+    }
+    Ok(path.to_path_buf())
+}
+
+fn note_id(notes_dir: &Path, path: &Path) -> Result<String> {
+    let relative = path
+        .strip_prefix(notes_dir)
+        .map_err(|_| anyhow::anyhow!("Note {:?} is outside the notes directory", path))?;
+    Ok(relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+fn to_millis(time: SystemTime) -> i64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn note_info(notes_dir: &Path, path: &Path) -> Result<NoteInfo> {
+    let metadata = fs::metadata(path)?;
+    let modified_at = metadata.modified().map(to_millis).unwrap_or(0);
+    // Not every filesystem records creation time
+    let created_at = metadata.created().map(to_millis).unwrap_or(modified_at);
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let id = note_id(notes_dir, path)?;
+
+    // Notes filed by hand into a folder belong to that folder's project
+    let (frontmatter, _) = Frontmatter::parse(&content);
+    let project = frontmatter
+        .get_text("project")
+        .map(str::to_string)
+        .or_else(|| id.split_once('/').map(|(folder, _)| folder.to_string()));
+
+    Ok(NoteInfo {
+        id,
+        title: note_title(&content, path),
+        path: path.to_string_lossy().to_string(),
+        created_at,
+        modified_at,
+        size_bytes: metadata.len(),
+        project,
+    })
+}
+
+/// Use the first Markdown heading or non-empty line as the title, falling back
+/// to the filename.
+fn note_title(content: &str, path: &Path) -> String {
+    let (_, body) = Frontmatter::parse(content);
+    let line = body
+        .lines()
+        .map(|l| l.trim().trim_start_matches('#').trim())
+        .find(|l| !l.is_empty());
+
+    match line {
+        Some(line) if line.chars().count() > MAX_TITLE_LEN => {
+            let truncated: String = line.chars().take(MAX_TITLE_LEN).collect();
+            format!("{}…", truncated.trim_end())
+        }
+        Some(line) => line.to_string(),
+        None => path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// Recursively collect `.md` files, skipping hidden files and directories.
+fn collect_notes(notes_dir: &Path, dir: &Path, notes: &mut Vec<NoteInfo>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = match entry {
+            Ok(e) => e,
+            Err(e) => {
+                warn!("Failed to read notes directory entry: {}", e);
+                continue;
+            }
+        };
+
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if hidden {
+            continue;
+        }
+
+        if path.is_dir() {
+            collect_notes(notes_dir, &path, notes)?;
+        } else if path.extension().and_then(|e| e.to_str()) == Some("md") {
+            match note_info(notes_dir, &path) {
+                Ok(info) => notes.push(info),
+                Err(e) => warn!("Skipping unreadable note {:?}: {}", path, e),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(
+            slugify("Call the dentist, tomorrow morning!"),
+            "call-the-dentist-tomorrow-morning"
+        );
+        assert_eq!(slugify("   "), "");
+        assert_eq!(slugify("明日は歯医者"), "明日は歯医者");
+        assert!(slugify(&"word ".repeat(40)).chars().count() <= MAX_SLUG_LEN);
+    }
+
+    #[test]
+    fn test_note_file_stem() {
+        let now = Local.with_ymd_and_hms(2025, 1, 31, 14, 25, 1).unwrap();
+        assert_eq!(
+            note_file_stem(&now, "Buy bread"),
+            "2025-01-31_142501-buy-bread"
+        );
+        assert_eq!(note_file_stem(&now, "..."), "2025-01-31_142501");
+    }
+
+    #[test]
+    fn test_append_entry_keeps_related_section_last() {
+        let now = Local.with_ymd_and_hms(2025, 1, 31, 14, 25, 1).unwrap();
+        assert_eq!(
+            append_entry("", " Buy bread ", &now),
+            "**2025-01-31 14:25**\n\nBuy bread\n"
+        );
+        assert_eq!(
+            append_entry(
+                "Groceries\n\n## Related\n\n- [[shopping]]\n",
+                "Buy bread",
+                &now
+            ),
+            "Groceries\n\n---\n\n**2025-01-31 14:25**\n\nBuy bread\n\n\
+             ## Related\n\n- [[shopping]]\n"
+        );
+    }
+
+    #[test]
+    fn test_append_entry_to_a_new_note_has_no_frontmatter() {
+        let now = Local.with_ymd_and_hms(2025, 1, 31, 14, 25, 1).unwrap();
+        let content = append_entry("", "Buy bread", &now);
+        let (frontmatter, body) = Frontmatter::parse(&content);
+        assert!(frontmatter.is_empty());
+        assert_eq!(body, content);
+
+        // Frontmatter stays in front, with no separator below it
+        let content = append_entry("---\nproject: home\n---\n", "Buy bread", &now);
+        assert_eq!(
+            content,
+            "---\nproject: home\n---\n\n**2025-01-31 14:25**\n\nBuy bread\n"
+        );
+        let (frontmatter, body) = Frontmatter::parse(&content);
+        assert_eq!(frontmatter.get_text("project"), Some("home"));
+        assert_eq!(body, "**2025-01-31 14:25**\n\nBuy bread\n");
+    }
+
+    #[test]
+    fn test_write_new_note_avoids_collisions() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = write_new_note(temp_dir.path(), "note", "one").unwrap();
+        let second = write_new_note(temp_dir.path(), "note", "two").unwrap();
+
+        assert_eq!(first.file_name().unwrap(), "note.md");
+        assert_eq!(second.file_name().unwrap(), "note-2.md");
+        assert_eq!(fs::read_to_string(first).unwrap(), "one");
+        assert_eq!(fs::read_to_string(second).unwrap(), "two");
+    }
+
+    #[test]
+    fn test_resolve_note_path_rejects_escapes() {
+        let root = Path::new("/notes");
+        assert!(resolve_note_path(root, "a.md").is_ok());
+        assert!(resolve_note_path(root, "project/a.md").is_ok());
+        assert!(resolve_note_path(root, "../a.md").is_err());
+        assert!(resolve_note_path(root, "/etc/passwd.md").is_err());
+        assert!(resolve_note_path(root, "a.txt").is_err());
+        assert!(resolve_note_path(root, "").is_err());
+    }
+
+    #[test]
+    fn test_resolve_append_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let outside = root.join("outside.md");
+        let outside = outside.to_str().unwrap();
+
+        assert_eq!(
+            resolve_append_path(root, "inbox/log.md").unwrap(),
+            root.join("inbox/log.md")
+        );
+        assert_eq!(
+            resolve_append_path(root, outside).unwrap(),
+            Path::new(outside)
+        );
+        assert!(resolve_append_path(root, "../log.md").is_err());
+        assert!(resolve_append_path(root, "log.txt").is_err());
+        assert!(resolve_append_path(root, root.join("missing/log.md").to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_collect_notes_recurses_and_skips_hidden() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::create_dir_all(root.join(".index")).unwrap();
+        fs::write(root.join("a.md"), "# First\nbody").unwrap();
+        fs::write(root.join("sub/b.md"), "second").unwrap();
+        fs::write(root.join(".index/c.md"), "hidden").unwrap();
+        fs::write(root.join("d.txt"), "not a note").unwrap();
+
+        let mut notes = Vec::new();
+        collect_notes(root, root, &mut notes).unwrap();
+        let mut ids: Vec<_> = notes.iter().map(|n| n.id.clone()).collect();
+        ids.sort();
+
+        assert_eq!(ids, vec!["a.md", "sub/b.md"]);
+        let first = notes.iter().find(|n| n.id == "a.md").unwrap();
+        assert_eq!(first.title, "First");
+    }
+
+    #[test]
+    fn test_note_project_from_frontmatter_or_folder() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("jotline")).unwrap();
+        fs::write(root.join("loose.md"), "no project").unwrap();
+        fs::write(root.join("jotline/filed.md"), "filed by hand").unwrap();
+        fs::write(
+            root.join("jotline/tagged.md"),
+            "---\nproject: Jotline\n---\n\ntagged",
+        )
+        .unwrap();
+
+        let project = |id: &str| note_info(root, &root.join(id)).unwrap().project;
+        assert_eq!(project("loose.md"), None);
+        assert_eq!(project("jotline/filed.md").as_deref(), Some("jotline"));
+        assert_eq!(project("jotline/tagged.md").as_deref(), Some("Jotline"));
+    }
+
+    #[test]
+    fn test_note_title_skips_frontmatter() {
+        let path = Path::new("2025-01-31_142501-note.md");
+        let content = "---\ncaptured_at: 2025-01-31T14:25:01+01:00\n---\n\nRemember the milk\n";
+        assert_eq!(note_title(content, path), "Remember the milk");
+        assert_eq!(note_title("", path), "2025-01-31_142501-note");
+    }
+}
@@ -0,0 +1,168 @@
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::managers::transcription::TranscriptionManager;
+use crate::settings::get_settings;
+use crate::utils;
+use log::{debug, warn};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+
+/// Streaming engines are cheap enough to re-run twice a second
+const STREAMING_INTERVAL_MS: usize = 500;
+/// Other engines get a fresh preview every few seconds
+const SLIDING_INTERVAL_MS: usize = 3000;
+/// Only the most recent audio is transcribed, so a preview costs the same
+/// however long the recording runs
+const WINDOW_MS: usize = 10_000;
+
+const fn samples(ms: usize) -> usize {
+    ms * WHISPER_SAMPLE_RATE as usize / 1000
+}
+
+enum PartialMsg {
+    Begin,
+    Samples(Vec<f32>),
+    End,
+}
+
+/// Live preview of what is being said, shown in the overlay while recording.
+///
+/// The recorder's consumer thread forwards each chunk of (VAD-filtered)
+/// 16 kHz audio here; a worker keeps the last [`WINDOW_MS`] of it and
+/// transcribes that window whenever enough new audio has arrived, emitting
+/// the text as `partial-transcript`. The final transcription still runs on
+/// the whole clip after the key is released.
+pub struct PartialTranscriber {
+    tx: Sender<PartialMsg>,
+}
+
+impl PartialTranscriber {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let app = app_handle.clone();
+        thread::spawn(move || run_worker(app, rx));
+        Self { tx }
+    }
+
+    pub fn begin(&self) {
+        let _ = self.tx.send(PartialMsg::Begin);
+    }
+
+    /// Audio recorded since the last call; called from the recorder thread.
+    pub fn push(&self, chunk: Vec<f32>) {
+        let _ = self.tx.send(PartialMsg::Samples(chunk));
+    }
+
+    pub fn end(&self) {
+        let _ = self.tx.send(PartialMsg::End);
+    }
+}
+
+struct Session {
+    window: Vec<f32>,
+    /// Samples received since the last preview
+    fresh: usize,
+    interval: usize,
+    last_text: String,
+}
+
+impl Session {
+    fn push(&mut self, chunk: &[f32]) {
+        self.window.extend_from_slice(chunk);
+        let excess = self.window.len().saturating_sub(samples(WINDOW_MS));
+        self.window.drain(..excess);
+        self.fresh += chunk.len();
+    }
+
+    fn due(&self) -> bool {
+        self.fresh >= self.interval
+    }
+}
+
+fn run_worker(app: AppHandle, rx: Receiver<PartialMsg>) {
+    let mut session: Option<Session> = None;
+
+    while let Ok(msg) = rx.recv() {
+        // Catch up on everything that arrived during the last preview before
+        // deciding whether another one is due
+        let mut next = Some(msg);
+        while let Some(msg) = next {
+            match msg {
+                PartialMsg::Begin => session = start_session(&app),
+                PartialMsg::Samples(chunk) => {
+                    if let Some(s) = session.as_mut() {
+                        s.push(&chunk);
+                    }
+                }
+                PartialMsg::End => session = None,
+            }
+            next = rx.try_recv().ok();
+        }
+
+        let Some(s) = session.as_mut().filter(|s| s.due()) else {
+            continue;
+        };
+        s.fresh = 0;
+        let Some(tm) = app.try_state::<Arc<TranscriptionManager>>() else {
+            continue;
+        };
+        let started = Instant::now();
+        match tm.transcribe_partial(s.window.clone()) {
+            Ok(Some(text)) if text != s.last_text => {
+                debug!("Partial transcript in {:?}: '{}'", started.elapsed(), text);
+                utils::emit_partial_transcript(&app, &text);
+                s.last_text = text;
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Partial transcription failed: {}", e),
+        }
+    }
+}
+
+fn start_session(app: &AppHandle) -> Option<Session> {
+    if !get_settings(app).live_transcription {
+        return None;
+    }
+    let streaming = app
+        .try_state::<Arc<TranscriptionManager>>()
+        .is_some_and(|tm| tm.is_streaming_model());
+    Some(Session {
+        window: Vec::with_capacity(samples(WINDOW_MS)),
+        fresh: 0,
+        interval: samples(if streaming {
+            STREAMING_INTERVAL_MS
+        } else {
+            SLIDING_INTERVAL_MS
+        }),
+        last_text: String::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_keeps_recent_window() {
+        let mut session = Session {
+            window: Vec::new(),
+            fresh: 0,
+            interval: samples(SLIDING_INTERVAL_MS),
+            last_text: String::new(),
+        };
+
+        session.push(&vec![0.0; samples(2000)]);
+        assert!(!session.due());
+
+        // Twelve seconds in total: the oldest two fall out of the window
+        for second in 0..10 {
+            session.push(&vec![second as f32; samples(1000)]);
+        }
+        assert!(session.due());
+        assert_eq!(session.window.len(), samples(WINDOW_MS));
+        assert_eq!(session.window[0], 0.0);
+        assert_eq!(*session.window.last().unwrap(), 9.0);
+    }
+}
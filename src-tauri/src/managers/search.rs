@@ -0,0 +1,461 @@
+use crate::managers::model::{ModelManager, EMBEDDING_MODEL_ID};
+use crate::managers::notes::{Note, NoteInfo, NoteManager};
+use crate::notes::{link_target, split_related, with_related, Frontmatter, RelatedLink};
+use crate::search::{SentenceEncoder, VectorIndex, EMBEDDING_DIM};
+use crate::settings::get_settings;
+use anyhow::Result;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+use tauri::{AppHandle, Listener, Manager};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct NoteSearchResult {
+    pub note: NoteInfo,
+    /// Cosine similarity between the query and the note, higher is closer
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SearchStatus {
+    pub model_id: String,
+    pub model_downloaded: bool,
+    pub indexed_notes: u32,
+}
+
+enum IndexJob {
+    Upsert(String),
+    Remove(String),
+    Move {
+        from: String,
+        to: String,
+    },
+    /// Reconcile the index with the notes directory
+    Sync,
+}
+
+#[derive(Deserialize)]
+struct NoteMovedPayload {
+    from_id: String,
+    note: NoteInfo,
+}
+
+/// Keeps an on-disk vector index of every note and answers semantic queries
+/// against it. Notes are embedded on a background thread as they are saved,
+/// edited, moved or deleted, so capturing never waits on the encoder.
+///
+/// The same thread maintains the "Related" links between notes, in both
+/// directions: a note is relinked whenever it is embedded, and links
+/// pointing at a note are fixed up when it is edited, moved or deleted.
+/// Notes embedded by a sync are not relinked, so downloading the model never
+/// rewrites the whole folder, and rewriting links does not count as an edit.
+/// The index records every note's links, so only the notes linking to a
+/// moved or deleted note are read.
+#[derive(Clone)]
+pub struct SearchManager {
+    app_handle: AppHandle,
+    model_manager: Arc<ModelManager>,
+    note_manager: Arc<NoteManager>,
+    encoder: Arc<Mutex<Option<SentenceEncoder>>>,
+    index: Arc<Mutex<VectorIndex>>,
+    index_path: PathBuf,
+    jobs: Sender<IndexJob>,
+    /// Notes whose last write only changed their links, so the
+    /// `note-updated` it caused is skipped
+    link_writes: Arc<Mutex<HashSet<String>>>,
+}
+
+impl SearchManager {
+    pub fn new(
+        app_handle: &AppHandle,
+        model_manager: Arc<ModelManager>,
+        note_manager: Arc<NoteManager>,
+    ) -> Result<Self> {
+        let index_path = app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?
+            .join("search")
+            .join("embeddings.bin");
+
+        let index = VectorIndex::load(&index_path, EMBEDDING_MODEL_ID, EMBEDDING_DIM)
+            .unwrap_or_else(|e| {
+                warn!("Discarding unreadable vector index: {}", e);
+                VectorIndex::new(EMBEDDING_MODEL_ID, EMBEDDING_DIM)
+            });
+        debug!("Loaded vector index with {} notes", index.count());
+
+        let (jobs, receiver) = mpsc::channel();
+        let manager = Self {
+            app_handle: app_handle.clone(),
+            model_manager,
+            note_manager,
+            encoder: Arc::new(Mutex::new(None)),
+            index: Arc::new(Mutex::new(index)),
+            index_path,
+            jobs,
+            link_writes: Arc::new(Mutex::new(HashSet::new())),
+        };
+
+        {
+            let worker = manager.clone();
+            thread::spawn(move || worker.run_indexer(receiver));
+        }
+        manager.listen_for_changes(app_handle);
+
+        // Pick up notes written or edited while the app was not running
+        let _ = manager.jobs.send(IndexJob::Sync);
+
+        Ok(manager)
+    }
+
+    fn listen_for_changes(&self, app_handle: &AppHandle) {
+        let jobs = self.jobs.clone();
+        app_handle.listen("note-saved", move |event| {
+            if let Ok(info) = serde_json::from_str::<NoteInfo>(event.payload()) {
+                let _ = jobs.send(IndexJob::Upsert(info.id));
+            }
+        });
+
+        let jobs = self.jobs.clone();
+        app_handle.listen("note-updated", move |event| {
+            if let Ok(info) = serde_json::from_str::<NoteInfo>(event.payload()) {
+                let _ = jobs.send(IndexJob::Upsert(info.id));
+            }
+        });
+
+        let jobs = self.jobs.clone();
+        app_handle.listen("note-deleted", move |event| {
+            if let Ok(id) = serde_json::from_str::<String>(event.payload()) {
+                let _ = jobs.send(IndexJob::Remove(id));
+            }
+        });
+
+        let jobs = self.jobs.clone();
+        app_handle.listen("note-moved", move |event| {
+            if let Ok(moved) = serde_json::from_str::<NoteMovedPayload>(event.payload()) {
+                let _ = jobs.send(IndexJob::Move {
+                    from: moved.from_id,
+                    to: moved.note.id,
+                });
+            }
+        });
+
+        // Index everything once the embedding model becomes available
+        let jobs = self.jobs.clone();
+        app_handle.listen("model-download-complete", move |event| {
+            if serde_json::from_str::<String>(event.payload())
+                .ok()
+                .as_deref()
+                == Some(EMBEDDING_MODEL_ID)
+            {
+                let _ = jobs.send(IndexJob::Sync);
+            }
+        });
+    }
+
+    fn run_indexer(&self, receiver: Receiver<IndexJob>) {
+        while let Ok(job) = receiver.recv() {
+            let mut changed = self.run_job(job);
+            // Batch whatever queued up meanwhile into a single save
+            while let Ok(job) = receiver.try_recv() {
+                changed |= self.run_job(job);
+            }
+            if changed {
+                let index = self.index.lock().unwrap();
+                if let Err(e) = index.save(&self.index_path) {
+                    warn!("Failed to save vector index: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Returns whether the index changed.
+    fn run_job(&self, job: IndexJob) -> bool {
+        let result = match job {
+            IndexJob::Upsert(id) if self.link_writes.lock().unwrap().remove(&id) => {
+                debug!("Skipping link-only update of {}", id);
+                Ok(true)
+            }
+            IndexJob::Upsert(id) => {
+                let result = self.index_note(&id);
+                if matches!(result, Ok(true)) {
+                    self.log_link_error(&id, self.link_note(&id));
+                }
+                result
+            }
+            IndexJob::Remove(id) => {
+                let removed = self.index.lock().unwrap().remove(&id);
+                self.log_link_error(&id, self.unlink_note(&id));
+                Ok(removed)
+            }
+            IndexJob::Move { from, to } => {
+                self.index.lock().unwrap().remove(&from);
+                self.log_link_error(&to, self.retarget_links(&from, &to));
+                let result = self.index_note(&to);
+                if matches!(result, Ok(true)) {
+                    self.log_link_error(&to, self.link_note(&to));
+                }
+                result.map(|_| true)
+            }
+            IndexJob::Sync => self.sync(),
+        };
+        result.unwrap_or_else(|e| {
+            warn!("Failed to update vector index: {}", e);
+            false
+        })
+    }
+
+    fn index_note(&self, id: &str) -> Result<bool> {
+        if !self.is_model_downloaded() {
+            // The sync after the model download will catch up
+            return Ok(false);
+        }
+        let note = self.note_manager.get_note(id)?;
+        // Leave out the generated links so rewriting them does not shift the vector
+        let (_, body) = Frontmatter::parse(&note.content);
+        let (body, links) = split_related(body);
+        let vector = self.embed(body)?;
+        let mut index = self.index.lock().unwrap();
+        index.upsert(id, note.info.modified_at, vector)?;
+        index.set_links(id, links.iter().map(RelatedLink::note_id).collect());
+        Ok(true)
+    }
+
+    fn sync(&self) -> Result<bool> {
+        if !self.is_model_downloaded() {
+            return Ok(false);
+        }
+
+        let start = Instant::now();
+        let notes = self.note_manager.list_notes()?;
+        let current: HashMap<&str, i64> = notes
+            .iter()
+            .map(|n| (n.id.as_str(), n.modified_at))
+            .collect();
+
+        let stale: Vec<&str> = {
+            let mut index = self.index.lock().unwrap();
+            index.retain(|id| current.contains_key(id));
+            current
+                .iter()
+                .filter(|(id, modified_at)| {
+                    index.get(id).map(|e| e.modified_at) != Some(**modified_at)
+                })
+                .map(|(id, _)| *id)
+                .collect()
+        };
+
+        for id in &stale {
+            if let Err(e) = self.index_note(id) {
+                warn!("Failed to index note {}: {}", id, e);
+            }
+        }
+
+        info!(
+            "Vector index synced: {} notes embedded in {:?}",
+            stale.len(),
+            start.elapsed()
+        );
+        Ok(true)
+    }
+
+    /// Rewrite the "Related" section of `id` from its nearest neighbours in
+    /// the index and mirror the change in them: new neighbours link back and
+    /// former ones drop their link. No other notes are read or written.
+    fn link_note(&self, id: &str) -> Result<()> {
+        let settings = get_settings(&self.app_handle);
+        if !settings.auto_link_notes {
+            return Ok(());
+        }
+        let threshold = settings.auto_link_threshold as f32;
+        let max_links = settings.auto_link_max_links as usize;
+
+        let related: Vec<String> = {
+            let index = self.index.lock().unwrap();
+            let Some(entry) = index.get(id) else {
+                return Ok(());
+            };
+            index
+                .top_k(&entry.vector, max_links + 1)
+                .into_iter()
+                .filter(|(other, score)| other != id && *score >= threshold)
+                .take(max_links)
+                .map(|(other, _)| other)
+                .collect()
+        };
+
+        let note = self.note_manager.get_note(id)?;
+        let (_, previous) = split_related(&note.content);
+        let backlink = RelatedLink::new(id, &note.info.title);
+
+        let mut links = Vec::new();
+        for other in &related {
+            let other_note = match self.note_manager.get_note(other) {
+                Ok(other_note) => other_note,
+                Err(e) => {
+                    debug!("Not linking {} to unreadable note {}: {}", id, other, e);
+                    continue;
+                }
+            };
+            links.push(RelatedLink::new(other, &other_note.info.title));
+            self.edit_note_links(&other_note, |links| {
+                match links.iter_mut().find(|l| l.target == backlink.target) {
+                    Some(link) => link.title = backlink.title.clone(),
+                    None => {
+                        links.push(backlink.clone());
+                        self.keep_closest(other, links, max_links);
+                    }
+                }
+            })?;
+        }
+
+        let kept: HashSet<&str> = links.iter().map(|l| l.target.as_str()).collect();
+        for stale in previous
+            .iter()
+            .filter(|l| !kept.contains(l.target.as_str()))
+        {
+            match self.note_manager.get_note(&stale.note_id()) {
+                Ok(other_note) => self.edit_note_links(&other_note, |links| {
+                    links.retain(|l| l.target != backlink.target)
+                })?,
+                Err(e) => debug!("Not unlinking {} from {}: {}", id, stale.target, e),
+            }
+        }
+
+        self.edit_note_links(&note, |current| *current = links)
+    }
+
+    /// Cut the links of note `id` down to the `max_links` closest to it.
+    fn keep_closest(&self, id: &str, links: &mut Vec<RelatedLink>, max_links: usize) {
+        if links.len() <= max_links {
+            return;
+        }
+        let index = self.index.lock().unwrap();
+        let score = |link: &RelatedLink| index.similarity(id, &link.note_id()).unwrap_or(f32::MIN);
+        links.sort_by(|a, b| score(b).total_cmp(&score(a)));
+        links.truncate(max_links);
+    }
+
+    /// Remove links to a deleted note.
+    fn unlink_note(&self, id: &str) -> Result<()> {
+        if !get_settings(&self.app_handle).auto_link_notes {
+            return Ok(());
+        }
+        let target = link_target(id);
+        let sources = self.index.lock().unwrap().linking_to(id);
+        for source in sources {
+            self.edit_links(&source, |links| links.retain(|l| l.target != target))?;
+        }
+        Ok(())
+    }
+
+    /// Point links at a moved note to its new location.
+    fn retarget_links(&self, from: &str, to: &str) -> Result<()> {
+        if !get_settings(&self.app_handle).auto_link_notes {
+            return Ok(());
+        }
+        let sources = self.index.lock().unwrap().linking_to(from);
+        let (from, to) = (link_target(from), link_target(to));
+        for source in sources {
+            self.edit_links(&source, |links| {
+                for link in links.iter_mut().filter(|l| l.target == from) {
+                    link.target = to.to_string();
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Apply `edit` to the links of note `id`, writing the note only if they
+    /// changed.
+    fn edit_links(&self, id: &str, edit: impl FnOnce(&mut Vec<RelatedLink>)) -> Result<()> {
+        self.edit_note_links(&self.note_manager.get_note(id)?, edit)
+    }
+
+    /// [`Self::edit_links`] for a note already read. The links are left out
+    /// of the vector, so the index only takes the new modification time and
+    /// links, and the `note-updated` the write causes is skipped.
+    fn edit_note_links(&self, note: &Note, edit: impl FnOnce(&mut Vec<RelatedLink>)) -> Result<()> {
+        let (_, current) = split_related(&note.content);
+        let mut links = current.clone();
+        edit(&mut links);
+        if links != current {
+            let id = &note.info.id;
+            debug!("Updating related links of {}", id);
+            let info = self
+                .note_manager
+                .update_note(id, &with_related(&note.content, &links))?;
+            let mut index = self.index.lock().unwrap();
+            index.touch(id, info.modified_at);
+            index.set_links(id, links.iter().map(RelatedLink::note_id).collect());
+            drop(index);
+            self.link_writes.lock().unwrap().insert(info.id);
+        }
+        Ok(())
+    }
+
+    fn log_link_error(&self, id: &str, result: Result<()>) {
+        if let Err(e) = result {
+            warn!("Failed to update related links for {}: {}", id, e);
+        }
+    }
+
+    fn is_model_downloaded(&self) -> bool {
+        self.model_manager
+            .get_model_info(EMBEDDING_MODEL_ID)
+            .map(|m| m.is_downloaded)
+            .unwrap_or(false)
+    }
+
+    /// Embed `text`, loading the encoder on first use.
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut encoder = self.encoder.lock().unwrap();
+        if encoder.is_none() {
+            let model_path = self.model_manager.get_model_path(EMBEDDING_MODEL_ID)?;
+            let load_start = Instant::now();
+            *encoder = Some(SentenceEncoder::load(&model_path)?);
+            info!("Loaded embedding model in {:?}", load_start.elapsed());
+        }
+        encoder.as_mut().unwrap().embed(text)
+    }
+
+    /// The `k` notes closest in meaning to `query`, best first.
+    pub fn search(&self, query: &str, k: usize) -> Result<Vec<NoteSearchResult>> {
+        let query = query.trim();
+        if query.is_empty() || k == 0 {
+            return Ok(Vec::new());
+        }
+        if !self.is_model_downloaded() {
+            return Err(anyhow::anyhow!("Embedding model is not downloaded"));
+        }
+
+        let vector = self.embed(query)?;
+        let hits = self.index.lock().unwrap().top_k(&vector, k);
+
+        // Notes removed since they were indexed are skipped
+        Ok(hits
+            .into_iter()
+            .filter_map(|(id, score)| {
+                self.note_manager
+                    .get_note_info(&id)
+                    .ok()
+                    .map(|note| NoteSearchResult { note, score })
+            })
+            .collect())
+    }
+
+    pub fn status(&self) -> SearchStatus {
+        SearchStatus {
+            model_id: EMBEDDING_MODEL_ID.to_string(),
+            model_downloaded: self.is_model_downloaded(),
+            indexed_notes: self.index.lock().unwrap().count() as u32,
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use crate::audio_toolkit::audio::{Segment, Segmenter};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::text::stitch_transcripts;
+use crate::audio_toolkit::vad::{VadFrame, VoiceActivityDetector};
+use crate::managers::transcription::TranscriptionManager;
+use crate::settings::get_settings;
+use crate::transcript::{Transcript, TranscriptSegment};
+use anyhow::Result;
+use log::{debug, warn};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+
+/// The VAD judges 30 ms of 16 kHz audio at a time
+const VAD_FRAME_SAMPLES: usize = 480;
+
+#[derive(Default)]
+struct SegmentsState {
+    /// Transcript of each piece in recording order; `None` until transcribed
+    texts: Vec<Option<Transcript>>,
+    /// Samples at the end of each piece that the next one repeats
+    overlaps: Vec<usize>,
+    /// Samples each piece adds to the recording, i.e. without its repeat
+    lengths: Vec<usize>,
+    /// Audio of the pieces without the repeats, kept only when the recording
+    /// will be saved to history
+    audio: Option<Vec<f32>>,
+    samples: usize,
+    cancelled: bool,
+}
+
+/// The pieces cut off one recording while it ran.
+#[derive(Default)]
+struct Segments {
+    state: Mutex<SegmentsState>,
+    transcribed: Condvar,
+}
+
+impl Segments {
+    fn new(keep_audio: bool) -> Self {
+        let segments = Self::default();
+        if keep_audio {
+            segments.state.lock().unwrap().audio = Some(Vec::new());
+        }
+        segments
+    }
+
+    /// Register the next piece and return its index.
+    fn add(&self, segment: &Segment) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let own = segment.samples.len() - segment.overlap;
+        if let Some(audio) = state.audio.as_mut() {
+            audio.extend_from_slice(&segment.samples[..own]);
+        }
+        state.samples += own;
+        state.texts.push(None);
+        state.overlaps.push(segment.overlap);
+        state.lengths.push(own);
+        state.texts.len() - 1
+    }
+
+    fn set_text(&self, index: usize, text: Transcript) {
+        self.state.lock().unwrap().texts[index] = Some(text);
+        self.transcribed.notify_all();
+    }
+
+    fn cancel(&self) {
+        self.state.lock().unwrap().cancelled = true;
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.state.lock().unwrap().cancelled
+    }
+}
+
+struct SegmentJob {
+    segments: Arc<Segments>,
+    index: usize,
+    samples: Vec<f32>,
+}
+
+/// Transcribes long recordings piece by piece while they are still going.
+///
+/// The recorder cuts the audio at pauses (see
+/// [`Segmenter`](crate::audio_toolkit::audio::Segmenter)) and hands each
+/// finished piece over; a worker transcribes it right away. When the key is
+/// released only the audio after the last cut is left to transcribe, so the
+/// wait for the text no longer grows with the length of the recording.
+pub struct SegmentTranscriber {
+    app_handle: AppHandle,
+    jobs: Sender<SegmentJob>,
+    current: Mutex<Option<Arc<Segments>>>,
+}
+
+impl SegmentTranscriber {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        let (jobs, receiver) = mpsc::channel();
+        let app = app_handle.clone();
+        thread::spawn(move || run_worker(app, receiver));
+        Self {
+            app_handle: app_handle.clone(),
+            jobs,
+            current: Mutex::new(None),
+        }
+    }
+
+    pub fn begin(&self) {
+        let settings = get_settings(&self.app_handle);
+        let keep_audio = settings.history_enabled && settings.history_save_audio;
+        *self.current.lock().unwrap() = Some(Arc::new(Segments::new(keep_audio)));
+    }
+
+    /// A piece the recorder has cut off; called from the recorder thread.
+    pub fn push(&self, segment: Segment) {
+        let Some(segments) = self.current.lock().unwrap().clone() else {
+            return;
+        };
+        let index = segments.add(&segment);
+        debug!(
+            "Recording piece {} cut at {:.1}s",
+            index,
+            segment.samples.len() as f32 / WHISPER_SAMPLE_RATE as f32
+        );
+        let _ = self.jobs.send(SegmentJob {
+            segments,
+            index,
+            samples: segment.samples,
+        });
+    }
+
+    /// Finish the current recording; `tail` is the audio after the last cut.
+    pub fn end(&self, tail: Vec<f32>) -> Recording {
+        Recording {
+            segments: self.current.lock().unwrap().take(),
+            tail,
+        }
+    }
+
+    /// Drop the current recording, skipping pieces not yet transcribed.
+    pub fn cancel(&self) {
+        if let Some(segments) = self.current.lock().unwrap().take() {
+            segments.cancel();
+        }
+    }
+}
+
+fn run_worker(app: AppHandle, receiver: Receiver<SegmentJob>) {
+    while let Ok(job) = receiver.recv() {
+        if job.segments.is_cancelled() {
+            continue;
+        }
+        let Some(tm) = app.try_state::<Arc<TranscriptionManager>>() else {
+            job.segments.set_text(job.index, Transcript::default());
+            continue;
+        };
+        // With immediate unload a finished dictation may have unloaded the model
+        tm.initiate_model_load();
+        let started = Instant::now();
+        let text = match tm.transcribe_segment(job.samples) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("Failed to transcribe recording piece {}: {}", job.index, e);
+                Transcript::default()
+            }
+        };
+        debug!(
+            "Recording piece {} transcribed in {:?}",
+            job.index,
+            started.elapsed()
+        );
+        job.segments.set_text(job.index, text);
+    }
+}
+
+/// A stopped recording: the pieces transcribed while it ran, if it was long
+/// enough to be cut, and the audio after the last cut.
+pub struct Recording {
+    segments: Option<Arc<Segments>>,
+    tail: Vec<f32>,
+}
+
+impl Recording {
+    pub fn duration_ms(&self) -> u64 {
+        let cut = self
+            .segments
+            .as_ref()
+            .map_or(0, |s| s.state.lock().unwrap().samples);
+        samples_to_ms(cut + self.tail.len())
+    }
+
+    /// The audio of the whole recording, unless it was cut into pieces that
+    /// were not kept.
+    pub fn audio(&self) -> Option<Vec<f32>> {
+        let Some(segments) = &self.segments else {
+            return Some(self.tail.clone());
+        };
+        let state = segments.state.lock().unwrap();
+        if state.texts.is_empty() {
+            return Some(self.tail.clone());
+        }
+        state.audio.as_ref().map(|audio| {
+            let mut audio = audio.clone();
+            audio.extend_from_slice(&self.tail);
+            audio
+        })
+    }
+
+    /// Wait for the pieces still being transcribed, transcribe the tail and
+    /// join the text, moving segment timestamps onto the recording's timeline.
+    pub fn transcribe(self, tm: &TranscriptionManager) -> Result<Transcript> {
+        let Some(segments) = self.segments else {
+            return tm.transcribe_timed(self.tail);
+        };
+
+        let pieces: Vec<Piece> = {
+            let state = segments
+                .transcribed
+                .wait_while(segments.state.lock().unwrap(), |state| {
+                    state.texts.iter().any(Option::is_none)
+                })
+                .unwrap();
+            state
+                .texts
+                .iter()
+                .flatten()
+                .zip(&state.overlaps)
+                .zip(&state.lengths)
+                .map(|((transcript, &overlap), &length)| Piece {
+                    transcript: transcript.clone(),
+                    overlap,
+                    length,
+                })
+                .collect()
+        };
+        if pieces.is_empty() {
+            return tm.transcribe_timed(self.tail);
+        }
+
+        let tail = match tm.transcribe_timed(self.tail) {
+            Ok(transcript) => transcript,
+            Err(e) => {
+                warn!("Failed to transcribe the end of the recording: {}", e);
+                Transcript::default()
+            }
+        };
+        Ok(join_pieces(pieces, tail))
+    }
+}
+
+/// Transcribe decoded 16 kHz audio the way a recording is: only what `vad`
+/// hears as speech is kept (everything without one), and long audio is cut
+/// with `segmenter` and transcribed piece by piece.
+pub fn transcribe_audio(
+    tm: &TranscriptionManager,
+    samples: &[f32],
+    mut vad: Option<&mut dyn VoiceActivityDetector>,
+    segmenter: &Segmenter,
+) -> Result<Transcript> {
+    let mut buf = Vec::new();
+    let mut pieces = Vec::new();
+    for frame in samples.chunks(VAD_FRAME_SAMPLES) {
+        let decision = match vad.as_mut() {
+            Some(vad) => vad.push_frame(frame).unwrap_or(VadFrame::Speech(frame)),
+            None => VadFrame::Speech(frame),
+        };
+        let speech = match decision {
+            VadFrame::Speech(speech) => {
+                buf.extend_from_slice(speech);
+                true
+            }
+            VadFrame::Noise => false,
+        };
+        if let Some(segment) = segmenter.push(&mut buf, speech) {
+            debug!(
+                "Audio piece {} cut at {:.1}s",
+                pieces.len(),
+                segment.samples.len() as f32 / WHISPER_SAMPLE_RATE as f32
+            );
+            let length = segment.samples.len() - segment.overlap;
+            pieces.push(Piece {
+                transcript: tm.transcribe_segment(segment.samples)?,
+                overlap: segment.overlap,
+                length,
+            });
+        }
+    }
+    let tail = tm.transcribe_timed(buf)?;
+    Ok(join_pieces(pieces, tail))
+}
+
+/// A transcribed piece of a longer recording.
+struct Piece {
+    transcript: Transcript,
+    /// Samples at the end that the next piece repeats
+    overlap: usize,
+    /// Samples the piece adds to the recording, without the repeat
+    length: usize,
+}
+
+/// Join the pieces and the tail after them into one transcript, moving
+/// segment timestamps onto the recording's timeline.
+fn join_pieces(pieces: Vec<Piece>, tail: Transcript) -> Transcript {
+    let mut joined = Transcript::default();
+    let mut start_ms = 0;
+    let mut repeated_ms = 0;
+    let mut overlapping = false;
+    let pieces = pieces
+        .into_iter()
+        .map(|piece| (piece.transcript, Some((piece.overlap, piece.length))))
+        .chain(std::iter::once((tail, None)));
+    for (piece, cut) in pieces {
+        joined.text = stitch_transcripts(&joined.text, &piece.text, overlapping);
+        // Segments inside the audio the previous piece already covered
+        // were transcribed there
+        joined.segments.extend(
+            piece
+                .segments
+                .into_iter()
+                .filter(|segment| segment.end_ms > repeated_ms)
+                .map(|segment| TranscriptSegment {
+                    start_ms: start_ms + segment.start_ms,
+                    end_ms: start_ms + segment.end_ms,
+                    text: segment.text,
+                }),
+        );
+        let Some((overlap, length)) = cut else {
+            break;
+        };
+        overlapping = overlap > 0;
+        repeated_ms = samples_to_ms(overlap);
+        start_ms += samples_to_ms(length);
+    }
+    joined
+}
+
+fn samples_to_ms(samples: usize) -> u64 {
+    samples as u64 * 1000 / WHISPER_SAMPLE_RATE as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start_ms: u64, end_ms: u64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_join_pieces_on_one_timeline() {
+        let second = WHISPER_SAMPLE_RATE as usize;
+        // Cut at 30 s without a pause, so the tail repeats the last second
+        let piece = Piece {
+            transcript: Transcript {
+                text: "one two three four".to_string(),
+                segments: vec![
+                    segment(0, 1_000, "one two"),
+                    segment(27_000, 29_500, "three four"),
+                ],
+            },
+            overlap: second,
+            length: 29 * second,
+        };
+        let tail = Transcript {
+            text: "four five".to_string(),
+            segments: vec![segment(200, 900, "four"), segment(1_200, 2_000, "five")],
+        };
+
+        let joined = join_pieces(vec![piece], tail);
+        assert_eq!(joined.text, "one two three four five");
+        assert_eq!(
+            joined.segments,
+            vec![
+                segment(0, 1_000, "one two"),
+                segment(27_000, 29_500, "three four"),
+                segment(30_200, 31_000, "five"),
+            ]
+        );
+    }
+}
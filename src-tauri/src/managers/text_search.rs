@@ -0,0 +1,260 @@
+use crate::managers::history::{HistoryCursor, HistoryEntry, HistoryManager};
+use crate::managers::notes::{NoteInfo, NoteManager};
+use crate::notes::{split_related, Frontmatter};
+use crate::search::{DocKey, SnippetPart, TextIndex};
+use anyhow::Result;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+use tauri::{AppHandle, Listener};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct TextSearchResult {
+    /// Set when the match is in a note
+    pub note: Option<NoteInfo>,
+    /// Set when the match is in a history entry
+    pub history_entry: Option<HistoryEntry>,
+    /// Number of places the query matched
+    pub matches: u32,
+    pub snippet: Vec<SnippetPart>,
+}
+
+enum TextIndexJob {
+    IndexNote(String),
+    RemoveNote(String),
+    /// Pick up new and re-transcribed history entries and drop deleted ones
+    SyncHistory,
+    /// Index every note and history entry from scratch
+    Rebuild,
+}
+
+#[derive(Deserialize)]
+struct NoteMovedPayload {
+    from_id: String,
+    note: NoteInfo,
+}
+
+/// Keyword search over notes and history. The inverted index lives in
+/// memory and is rebuilt on startup, which takes milliseconds even for
+/// thousands of transcriptions; afterwards it follows note and history
+/// changes on a background thread, so every dictation is searchable as soon
+/// as it is saved.
+#[derive(Clone)]
+pub struct TextSearchManager {
+    note_manager: Arc<NoteManager>,
+    history_manager: Arc<HistoryManager>,
+    index: Arc<Mutex<TextIndex>>,
+    /// How much of the history is in the index
+    history_cursor: Arc<Mutex<HistoryCursor>>,
+    jobs: Sender<TextIndexJob>,
+}
+
+impl TextSearchManager {
+    pub fn new(
+        app_handle: &AppHandle,
+        note_manager: Arc<NoteManager>,
+        history_manager: Arc<HistoryManager>,
+    ) -> Result<Self> {
+        let (jobs, receiver) = mpsc::channel();
+        let manager = Self {
+            note_manager,
+            history_manager,
+            index: Arc::new(Mutex::new(TextIndex::new())),
+            history_cursor: Arc::new(Mutex::new(HistoryCursor::default())),
+            jobs,
+        };
+
+        {
+            let worker = manager.clone();
+            thread::spawn(move || worker.run_indexer(receiver));
+        }
+        manager.listen_for_changes(app_handle);
+        let _ = manager.jobs.send(TextIndexJob::Rebuild);
+
+        Ok(manager)
+    }
+
+    fn listen_for_changes(&self, app_handle: &AppHandle) {
+        for event in ["note-saved", "note-updated"] {
+            let jobs = self.jobs.clone();
+            app_handle.listen(event, move |event| {
+                if let Ok(info) = serde_json::from_str::<NoteInfo>(event.payload()) {
+                    let _ = jobs.send(TextIndexJob::IndexNote(info.id));
+                }
+            });
+        }
+
+        let jobs = self.jobs.clone();
+        app_handle.listen("note-deleted", move |event| {
+            if let Ok(id) = serde_json::from_str::<String>(event.payload()) {
+                let _ = jobs.send(TextIndexJob::RemoveNote(id));
+            }
+        });
+
+        let jobs = self.jobs.clone();
+        app_handle.listen("note-moved", move |event| {
+            if let Ok(moved) = serde_json::from_str::<NoteMovedPayload>(event.payload()) {
+                let _ = jobs.send(TextIndexJob::RemoveNote(moved.from_id));
+                let _ = jobs.send(TextIndexJob::IndexNote(moved.note.id));
+            }
+        });
+
+        let jobs = self.jobs.clone();
+        app_handle.listen("history-updated", move |_| {
+            let _ = jobs.send(TextIndexJob::SyncHistory);
+        });
+    }
+
+    fn run_indexer(&self, receiver: Receiver<TextIndexJob>) {
+        while let Ok(job) = receiver.recv() {
+            let result = match job {
+                TextIndexJob::IndexNote(id) => self.index_note(&id),
+                TextIndexJob::RemoveNote(id) => {
+                    self.index.lock().unwrap().remove(&DocKey::Note(id));
+                    Ok(())
+                }
+                TextIndexJob::SyncHistory => self.sync_history(),
+                TextIndexJob::Rebuild => self.rebuild(),
+            };
+            if let Err(e) = result {
+                warn!("Failed to update text index: {}", e);
+            }
+        }
+    }
+
+    fn index_note(&self, id: &str) -> Result<()> {
+        let note = self.note_manager.get_note(id)?;
+        let (_, body) = Frontmatter::parse(&note.content);
+        let (body, _) = split_related(body);
+        self.index
+            .lock()
+            .unwrap()
+            .upsert(DocKey::Note(id.to_string()), note.info.created_at, body);
+        Ok(())
+    }
+
+    fn sync_history(&self) -> Result<()> {
+        let mut cursor = self.history_cursor.lock().unwrap();
+        let (changed, next) = self.history_manager.changed_since(*cursor)?;
+        let mut documents = Vec::with_capacity(changed.len());
+        for id in changed {
+            if let Some(entry) = self.history_manager.get_entry(id)? {
+                documents.push((id, entry.created_at, self.history_text(&entry)?));
+            }
+        }
+        let count = self.history_manager.count_entries()?;
+
+        let mut index = self.index.lock().unwrap();
+        for (id, created_at, text) in documents {
+            index.upsert(DocKey::History(id), created_at, &text);
+        }
+        // Only deletions leave more entries in the index than in the history
+        let indexed = index
+            .keys()
+            .filter(|key| matches!(key, DocKey::History(_)))
+            .count();
+        if indexed != count {
+            let current: HashSet<i64> = self.history_manager.entry_ids()?.into_iter().collect();
+            let deleted: Vec<DocKey> = index
+                .keys()
+                .filter(|key| matches!(key, DocKey::History(id) if !current.contains(id)))
+                .cloned()
+                .collect();
+            for key in &deleted {
+                index.remove(key);
+            }
+        }
+        *cursor = next;
+        Ok(())
+    }
+
+    /// An entry's text and that of its re-transcriptions, searched as one
+    /// document.
+    fn history_text(&self, entry: &HistoryEntry) -> Result<String> {
+        let mut text = entry.final_text().to_string();
+        if entry.revision_count > 0 {
+            for revision in self.history_manager.list_revisions(entry.id)? {
+                text.push('\n');
+                text.push_str(
+                    revision
+                        .processed_text
+                        .as_deref()
+                        .unwrap_or(&revision.raw_text),
+                );
+            }
+        }
+        Ok(text)
+    }
+
+    fn rebuild(&self) -> Result<()> {
+        let start = Instant::now();
+        *self.index.lock().unwrap() = TextIndex::new();
+        *self.history_cursor.lock().unwrap() = HistoryCursor::default();
+        for note in self.note_manager.list_notes()? {
+            if let Err(e) = self.index_note(&note.id) {
+                warn!("Failed to index note {}: {}", note.id, e);
+            }
+        }
+        self.sync_history()?;
+        info!(
+            "Text index built: {} documents in {:?}",
+            self.index.lock().unwrap().count(),
+            start.elapsed()
+        );
+        Ok(())
+    }
+
+    /// Notes and history entries containing every word of `query`, with the
+    /// last word matched as a prefix. Best matches first.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<TextSearchResult>> {
+        let hits = self.index.lock().unwrap().search(query, limit);
+        if hits.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut notes: HashMap<String, NoteInfo> =
+            if hits.iter().any(|hit| matches!(hit.key, DocKey::Note(_))) {
+                self.note_manager
+                    .list_notes()?
+                    .into_iter()
+                    .map(|n| (n.id.clone(), n))
+                    .collect()
+            } else {
+                HashMap::new()
+            };
+
+        let mut results = Vec::with_capacity(hits.len());
+        let mut stale = Vec::new();
+        for hit in hits {
+            let (note, history_entry) = match &hit.key {
+                DocKey::Note(id) => (notes.remove(id), None),
+                DocKey::History(id) => (None, self.history_manager.get_entry(*id)?),
+            };
+            if note.is_none() && history_entry.is_none() {
+                stale.push(hit.key);
+                continue;
+            }
+            results.push(TextSearchResult {
+                note,
+                history_entry,
+                matches: hit.matches,
+                snippet: hit.snippet,
+            });
+        }
+
+        // Changed on disk behind our back; forget them until they reappear
+        if !stale.is_empty() {
+            debug!("Dropping {} stale documents from text index", stale.len());
+            let mut index = self.index.lock().unwrap();
+            for key in &stale {
+                index.remove(key);
+            }
+        }
+        Ok(results)
+    }
+}
@@ -1,6 +1,8 @@
 use crate::audio_toolkit::{apply_custom_words, filter_transcription_output};
+use crate::host::Host;
 use crate::managers::model::{EngineType, ModelManager};
-use crate::settings::{get_settings, ModelUnloadTimeout};
+use crate::settings::{AppSettings, ModelUnloadTimeout};
+use crate::transcript::{Transcript, TranscriptSegment};
 use anyhow::Result;
 use log::{debug, error, info, warn};
 use serde::Serialize;
@@ -9,7 +11,6 @@ use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Condvar, Mutex, MutexGuard};
 use std::thread;
 use std::time::{Duration, SystemTime};
-use tauri::{AppHandle, Emitter};
 use transcribe_rs::{
     engines::{
         moonshine::{
@@ -48,21 +49,24 @@ enum LoadedEngine {
 pub struct TranscriptionManager {
     engine: Arc<Mutex<Option<LoadedEngine>>>,
     model_manager: Arc<ModelManager>,
-    app_handle: AppHandle,
+    host: Arc<dyn Host>,
     current_model_id: Arc<Mutex<Option<String>>>,
     last_activity: Arc<AtomicU64>,
     shutdown_signal: Arc<AtomicBool>,
     watcher_handle: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
     is_loading: Arc<Mutex<bool>>,
     loading_condvar: Arc<Condvar>,
+    /// Held while the engine runs, so final transcriptions and live previews
+    /// take turns instead of finding the engine missing
+    transcribe_lock: Arc<Mutex<()>>,
 }
 
 impl TranscriptionManager {
-    pub fn new(app_handle: &AppHandle, model_manager: Arc<ModelManager>) -> Result<Self> {
+    pub fn new(host: Arc<dyn Host>, model_manager: Arc<ModelManager>) -> Result<Self> {
         let manager = Self {
             engine: Arc::new(Mutex::new(None)),
             model_manager,
-            app_handle: app_handle.clone(),
+            host: Arc::clone(&host),
             current_model_id: Arc::new(Mutex::new(None)),
             last_activity: Arc::new(AtomicU64::new(
                 SystemTime::now()
@@ -74,11 +78,11 @@ impl TranscriptionManager {
             watcher_handle: Arc::new(Mutex::new(None)),
             is_loading: Arc::new(Mutex::new(false)),
             loading_condvar: Arc::new(Condvar::new()),
+            transcribe_lock: Arc::new(Mutex::new(())),
         };
 
         // Start the idle watcher
         {
-            let app_handle_cloned = app_handle.clone();
             let manager_cloned = manager.clone();
             let shutdown_signal = manager.shutdown_signal.clone();
             let handle = thread::spawn(move || {
@@ -90,7 +94,7 @@ impl TranscriptionManager {
                         break;
                     }
 
-                    let settings = get_settings(&app_handle_cloned);
+                    let settings = host.settings();
                     let timeout_seconds = settings.model_unload_timeout.to_seconds();
 
                     if let Some(limit_seconds) = timeout_seconds {
@@ -112,7 +116,7 @@ impl TranscriptionManager {
                                 debug!("Starting to unload model due to inactivity");
 
                                 if let Ok(()) = manager_cloned.unload_model() {
-                                    let _ = app_handle_cloned.emit(
+                                    host.emit(
                                         "model-state-changed",
                                         ModelStateEvent {
                                             event_type: "unloaded".to_string(),
@@ -175,7 +179,7 @@ impl TranscriptionManager {
         }
 
         // Emit unloaded event
-        let _ = self.app_handle.emit(
+        self.host.emit(
             "model-state-changed",
             ModelStateEvent {
                 event_type: "unloaded".to_string(),
@@ -195,7 +199,7 @@ impl TranscriptionManager {
 
     /// Unloads the model immediately if the setting is enabled and the model is loaded
     pub fn maybe_unload_immediately(&self, context: &str) {
-        let settings = get_settings(&self.app_handle);
+        let settings = self.host.settings();
         if settings.model_unload_timeout == ModelUnloadTimeout::Immediately
             && self.is_model_loaded()
         {
@@ -211,7 +215,7 @@ impl TranscriptionManager {
         debug!("Starting to load model: {}", model_id);
 
         // Emit loading started event
-        let _ = self.app_handle.emit(
+        self.host.emit(
             "model-state-changed",
             ModelStateEvent {
                 event_type: "loading_started".to_string(),
@@ -228,7 +232,7 @@ impl TranscriptionManager {
 
         if !model_info.is_downloaded {
             let error_msg = "Model not downloaded";
-            let _ = self.app_handle.emit(
+            self.host.emit(
                 "model-state-changed",
                 ModelStateEvent {
                     event_type: "loading_failed".to_string(),
@@ -248,7 +252,7 @@ impl TranscriptionManager {
                 let mut engine = WhisperEngine::new();
                 engine.load_model(&model_path).map_err(|e| {
                     let error_msg = format!("Failed to load whisper model {}: {}", model_id, e);
-                    let _ = self.app_handle.emit(
+                    self.host.emit(
                         "model-state-changed",
                         ModelStateEvent {
                             event_type: "loading_failed".to_string(),
@@ -268,7 +272,7 @@ impl TranscriptionManager {
                     .map_err(|e| {
                         let error_msg =
                             format!("Failed to load parakeet model {}: {}", model_id, e);
-                        let _ = self.app_handle.emit(
+                        self.host.emit(
                             "model-state-changed",
                             ModelStateEvent {
                                 event_type: "loading_failed".to_string(),
@@ -291,7 +295,7 @@ impl TranscriptionManager {
                     .map_err(|e| {
                         let error_msg =
                             format!("Failed to load moonshine model {}: {}", model_id, e);
-                        let _ = self.app_handle.emit(
+                        self.host.emit(
                             "model-state-changed",
                             ModelStateEvent {
                                 event_type: "loading_failed".to_string(),
@@ -313,7 +317,7 @@ impl TranscriptionManager {
                             "Failed to load moonshine streaming model {}: {}",
                             model_id, e
                         );
-                        let _ = self.app_handle.emit(
+                        self.host.emit(
                             "model-state-changed",
                             ModelStateEvent {
                                 event_type: "loading_failed".to_string(),
@@ -333,7 +337,7 @@ impl TranscriptionManager {
                     .map_err(|e| {
                         let error_msg =
                             format!("Failed to load SenseVoice model {}: {}", model_id, e);
-                        let _ = self.app_handle.emit(
+                        self.host.emit(
                             "model-state-changed",
                             ModelStateEvent {
                                 event_type: "loading_failed".to_string(),
@@ -346,6 +350,19 @@ impl TranscriptionManager {
                     })?;
                 LoadedEngine::SenseVoice(engine)
             }
+            EngineType::Embedding => {
+                let error_msg = format!("{} is not a transcription model", model_info.name);
+                self.host.emit(
+                    "model-state-changed",
+                    ModelStateEvent {
+                        event_type: "loading_failed".to_string(),
+                        model_id: Some(model_id.to_string()),
+                        model_name: Some(model_info.name.clone()),
+                        error: Some(error_msg.clone()),
+                    },
+                );
+                return Err(anyhow::anyhow!(error_msg));
+            }
         };
 
         // Update the current engine and model ID
@@ -359,7 +376,7 @@ impl TranscriptionManager {
         }
 
         // Emit loading completed event
-        let _ = self.app_handle.emit(
+        self.host.emit(
             "model-state-changed",
             ModelStateEvent {
                 event_type: "loading_completed".to_string(),
@@ -388,7 +405,7 @@ impl TranscriptionManager {
         *is_loading = true;
         let self_clone = self.clone();
         thread::spawn(move || {
-            let settings = get_settings(&self_clone.app_handle);
+            let settings = self_clone.host.settings();
             if let Err(e) = self_clone.load_model(&settings.selected_model) {
                 error!("Failed to load model: {}", e);
             }
@@ -403,7 +420,185 @@ impl TranscriptionManager {
         current_model.clone()
     }
 
+    /// Whether the loaded model is built for streaming, making frequent live
+    /// previews affordable.
+    pub fn is_streaming_model(&self) -> bool {
+        self.get_current_model()
+            .and_then(|id| self.model_manager.get_model_info(&id))
+            .is_some_and(|info| matches!(info.engine_type, EngineType::MoonshineStreaming))
+    }
+
+    /// Transcribe the latest audio of a recording still in progress for the
+    /// live preview. Gives up with `None` rather than waiting when no model is
+    /// loaded or a final transcription has the engine, and never unloads the
+    /// model afterwards.
+    pub fn transcribe_partial(&self, audio: Vec<f32>) -> Result<Option<String>> {
+        let Ok(_transcribing) = self.transcribe_lock.try_lock() else {
+            return Ok(None);
+        };
+        if audio.is_empty() || *self.is_loading.lock().unwrap() || !self.is_model_loaded() {
+            return Ok(None);
+        }
+        let settings = self.host.settings();
+        let result = self.run_engine(audio, &settings)?;
+        Ok(Some(filter_transcription_output(&result.text)))
+    }
+
+    /// Run the loaded engine on `audio`.
+    ///
+    /// We use catch_unwind to prevent engine panics from poisoning the mutex,
+    /// which would make the app hang indefinitely on subsequent operations.
+    fn run_engine(
+        &self,
+        audio: Vec<f32>,
+        settings: &AppSettings,
+    ) -> Result<transcribe_rs::TranscriptionResult> {
+        let mut engine_guard = self.lock_engine();
+
+        // Take the engine out so we own it during transcription.
+        // If the engine panics, we simply don't put it back (effectively unloading it)
+        // instead of poisoning the mutex.
+        let mut engine = match engine_guard.take() {
+            Some(e) => e,
+            None => {
+                return Err(anyhow::anyhow!(
+                    "Model failed to load after auto-load attempt. Please check your model settings."
+                ));
+            }
+        };
+
+        // Release the lock before transcribing — no mutex held during the engine call
+        drop(engine_guard);
+
+        let transcribe_result = catch_unwind(AssertUnwindSafe(
+            || -> Result<transcribe_rs::TranscriptionResult> {
+                match &mut engine {
+                    LoadedEngine::Whisper(whisper_engine) => {
+                        let whisper_language = if settings.selected_language == "auto" {
+                            None
+                        } else {
+                            let normalized = if settings.selected_language == "zh-Hans"
+                                || settings.selected_language == "zh-Hant"
+                            {
+                                "zh".to_string()
+                            } else {
+                                settings.selected_language.clone()
+                            };
+                            Some(normalized)
+                        };
+
+                        let params = WhisperInferenceParams {
+                            language: whisper_language,
+                            translate: settings.translate_to_english,
+                            ..Default::default()
+                        };
+
+                        whisper_engine
+                            .transcribe_samples(audio, Some(params))
+                            .map_err(|e| anyhow::anyhow!("Whisper transcription failed: {}", e))
+                    }
+                    LoadedEngine::Parakeet(parakeet_engine) => {
+                        let params = ParakeetInferenceParams {
+                            timestamp_granularity: TimestampGranularity::Segment,
+                        };
+                        parakeet_engine
+                            .transcribe_samples(audio, Some(params))
+                            .map_err(|e| anyhow::anyhow!("Parakeet transcription failed: {}", e))
+                    }
+                    LoadedEngine::Moonshine(moonshine_engine) => moonshine_engine
+                        .transcribe_samples(audio, None)
+                        .map_err(|e| anyhow::anyhow!("Moonshine transcription failed: {}", e)),
+                    LoadedEngine::MoonshineStreaming(streaming_engine) => streaming_engine
+                        .transcribe_samples(audio, None)
+                        .map_err(|e| {
+                            anyhow::anyhow!("Moonshine streaming transcription failed: {}", e)
+                        }),
+                    LoadedEngine::SenseVoice(sense_voice_engine) => {
+                        let language = match settings.selected_language.as_str() {
+                            "zh" | "zh-Hans" | "zh-Hant" => SenseVoiceLanguage::Chinese,
+                            "en" => SenseVoiceLanguage::English,
+                            "ja" => SenseVoiceLanguage::Japanese,
+                            "ko" => SenseVoiceLanguage::Korean,
+                            "yue" => SenseVoiceLanguage::Cantonese,
+                            _ => SenseVoiceLanguage::Auto,
+                        };
+                        let params = SenseVoiceInferenceParams {
+                            language,
+                            use_itn: true,
+                        };
+                        sense_voice_engine
+                            .transcribe_samples(audio, Some(params))
+                            .map_err(|e| anyhow::anyhow!("SenseVoice transcription failed: {}", e))
+                    }
+                }
+            },
+        ));
+
+        match transcribe_result {
+            Ok(inner_result) => {
+                // Success or normal error — put the engine back
+                let mut engine_guard = self.lock_engine();
+                *engine_guard = Some(engine);
+                inner_result
+            }
+            Err(panic_payload) => {
+                // Engine panicked — do NOT put it back (it's in an unknown state).
+                // The engine is dropped here, effectively unloading it.
+                let panic_msg = if let Some(s) = panic_payload.downcast_ref::<&str>() {
+                    s.to_string()
+                } else if let Some(s) = panic_payload.downcast_ref::<String>() {
+                    s.clone()
+                } else {
+                    "unknown panic".to_string()
+                };
+                error!(
+                    "Transcription engine panicked: {}. Model has been unloaded.",
+                    panic_msg
+                );
+
+                // Clear the model ID so it will be reloaded on next attempt
+                {
+                    let mut current_model = self
+                        .current_model_id
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner());
+                    *current_model = None;
+                }
+
+                self.host.emit(
+                    "model-state-changed",
+                    ModelStateEvent {
+                        event_type: "unloaded".to_string(),
+                        model_id: None,
+                        model_name: None,
+                        error: Some(format!("Engine panicked: {}", panic_msg)),
+                    },
+                );
+
+                return Err(anyhow::anyhow!(
+                    "Transcription engine panicked: {}. The model has been unloaded and will reload on next attempt.",
+                    panic_msg
+                ));
+            }
+        }
+    }
+
     pub fn transcribe(&self, audio: Vec<f32>) -> Result<String> {
+        self.transcribe_timed(audio)
+            .map(|transcript| transcript.text)
+    }
+
+    /// Like [`Self::transcribe`], keeping the segment timestamps of engines
+    /// that report them.
+    pub fn transcribe_timed(&self, audio: Vec<f32>) -> Result<Transcript> {
+        let result = self.transcribe_segment(audio);
+        self.maybe_unload_immediately("transcription");
+        result
+    }
+
+    /// Transcribe one piece of a recording that is still going. Unlike
+    /// [`Self::transcribe`] this keeps the model loaded for the pieces after it.
+    pub fn transcribe_segment(&self, audio: Vec<f32>) -> Result<Transcript> {
         // Update last activity timestamp
         self.last_activity.store(
             SystemTime::now()
@@ -419,8 +614,7 @@ impl TranscriptionManager {
 
         if audio.is_empty() {
             debug!("Empty audio vector");
-            self.maybe_unload_immediately("empty audio");
-            return Ok(String::new());
+            return Ok(Transcript::default());
         }
 
         // Check if model is loaded, if not try to load it
@@ -438,159 +632,46 @@ impl TranscriptionManager {
         }
 
         // Get current settings for configuration
-        let settings = get_settings(&self.app_handle);
+        let settings = self.host.settings();
 
-        // Perform transcription with the appropriate engine.
-        // We use catch_unwind to prevent engine panics from poisoning the mutex,
-        // which would make the app hang indefinitely on subsequent operations.
+        // Perform transcription with the appropriate engine, holding the transcribe lock
+        // so a live preview cannot take the engine out from under us.
         let result = {
-            let mut engine_guard = self.lock_engine();
-
-            // Take the engine out so we own it during transcription.
-            // If the engine panics, we simply don't put it back (effectively unloading it)
-            // instead of poisoning the mutex.
-            let mut engine = match engine_guard.take() {
-                Some(e) => e,
-                None => {
-                    return Err(anyhow::anyhow!(
-                        "Model failed to load after auto-load attempt. Please check your model settings."
-                    ));
-                }
-            };
-
-            // Release the lock before transcribing — no mutex held during the engine call
-            drop(engine_guard);
-
-            let transcribe_result = catch_unwind(AssertUnwindSafe(
-                || -> Result<transcribe_rs::TranscriptionResult> {
-                    match &mut engine {
-                        LoadedEngine::Whisper(whisper_engine) => {
-                            let whisper_language = if settings.selected_language == "auto" {
-                                None
-                            } else {
-                                let normalized = if settings.selected_language == "zh-Hans"
-                                    || settings.selected_language == "zh-Hant"
-                                {
-                                    "zh".to_string()
-                                } else {
-                                    settings.selected_language.clone()
-                                };
-                                Some(normalized)
-                            };
-
-                            let params = WhisperInferenceParams {
-                                language: whisper_language,
-                                translate: settings.translate_to_english,
-                                ..Default::default()
-                            };
-
-                            whisper_engine
-                                .transcribe_samples(audio, Some(params))
-                                .map_err(|e| anyhow::anyhow!("Whisper transcription failed: {}", e))
-                        }
-                        LoadedEngine::Parakeet(parakeet_engine) => {
-                            let params = ParakeetInferenceParams {
-                                timestamp_granularity: TimestampGranularity::Segment,
-                            };
-                            parakeet_engine
-                                .transcribe_samples(audio, Some(params))
-                                .map_err(|e| {
-                                    anyhow::anyhow!("Parakeet transcription failed: {}", e)
-                                })
-                        }
-                        LoadedEngine::Moonshine(moonshine_engine) => moonshine_engine
-                            .transcribe_samples(audio, None)
-                            .map_err(|e| anyhow::anyhow!("Moonshine transcription failed: {}", e)),
-                        LoadedEngine::MoonshineStreaming(streaming_engine) => streaming_engine
-                            .transcribe_samples(audio, None)
-                            .map_err(|e| {
-                                anyhow::anyhow!("Moonshine streaming transcription failed: {}", e)
-                            }),
-                        LoadedEngine::SenseVoice(sense_voice_engine) => {
-                            let language = match settings.selected_language.as_str() {
-                                "zh" | "zh-Hans" | "zh-Hant" => SenseVoiceLanguage::Chinese,
-                                "en" => SenseVoiceLanguage::English,
-                                "ja" => SenseVoiceLanguage::Japanese,
-                                "ko" => SenseVoiceLanguage::Korean,
-                                "yue" => SenseVoiceLanguage::Cantonese,
-                                _ => SenseVoiceLanguage::Auto,
-                            };
-                            let params = SenseVoiceInferenceParams {
-                                language,
-                                use_itn: true,
-                            };
-                            sense_voice_engine
-                                .transcribe_samples(audio, Some(params))
-                                .map_err(|e| {
-                                    anyhow::anyhow!("SenseVoice transcription failed: {}", e)
-                                })
-                        }
-                    }
-                },
-            ));
-
-            match transcribe_result {
-                Ok(inner_result) => {
-                    // Success or normal error — put the engine back
-                    let mut engine_guard = self.lock_engine();
-                    *engine_guard = Some(engine);
-                    inner_result?
-                }
-                Err(panic_payload) => {
-                    // Engine panicked — do NOT put it back (it's in an unknown state).
-                    // The engine is dropped here, effectively unloading it.
-                    let panic_msg = if let Some(s) = panic_payload.downcast_ref::<&str>() {
-                        s.to_string()
-                    } else if let Some(s) = panic_payload.downcast_ref::<String>() {
-                        s.clone()
-                    } else {
-                        "unknown panic".to_string()
-                    };
-                    error!(
-                        "Transcription engine panicked: {}. Model has been unloaded.",
-                        panic_msg
-                    );
-
-                    // Clear the model ID so it will be reloaded on next attempt
-                    {
-                        let mut current_model = self
-                            .current_model_id
-                            .lock()
-                            .unwrap_or_else(|e| e.into_inner());
-                        *current_model = None;
-                    }
-
-                    let _ = self.app_handle.emit(
-                        "model-state-changed",
-                        ModelStateEvent {
-                            event_type: "unloaded".to_string(),
-                            model_id: None,
-                            model_name: None,
-                            error: Some(format!("Engine panicked: {}", panic_msg)),
-                        },
-                    );
-
-                    return Err(anyhow::anyhow!(
-                        "Transcription engine panicked: {}. The model has been unloaded and will reload on next attempt.",
-                        panic_msg
-                    ));
-                }
-            }
+            let _transcribing = self
+                .transcribe_lock
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            self.run_engine(audio, &settings)?
         };
 
-        // Apply word correction if custom words are configured
-        let corrected_result = if !settings.custom_words.is_empty() {
-            apply_custom_words(
-                &result.text,
-                &settings.custom_words,
-                settings.word_correction_threshold,
-            )
-        } else {
-            result.text
+        // Apply word correction if custom words are configured, then filter out
+        // filler words and hallucinations
+        let clean = |text: &str| {
+            let corrected = if !settings.custom_words.is_empty() {
+                apply_custom_words(
+                    text,
+                    &settings.custom_words,
+                    settings.word_correction_threshold,
+                )
+            } else {
+                text.to_string()
+            };
+            filter_transcription_output(&corrected)
         };
-
-        // Filter out filler words and hallucinations
-        let filtered_result = filter_transcription_output(&corrected_result);
+        let filtered_result = clean(&result.text);
+        let segments: Vec<TranscriptSegment> = result
+            .segments
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|segment| {
+                let text = clean(&segment.text);
+                (!text.is_empty()).then(|| TranscriptSegment {
+                    start_ms: (segment.start.max(0.0) * 1000.0) as u64,
+                    end_ms: (segment.end.max(0.0) * 1000.0) as u64,
+                    text,
+                })
+            })
+            .collect();
 
         let et = std::time::Instant::now();
         let translation_note = if settings.translate_to_english {
@@ -612,9 +693,10 @@ impl TranscriptionManager {
             info!("Transcription result: {}", final_result);
         }
 
-        self.maybe_unload_immediately("transcription");
-
-        Ok(final_result)
+        Ok(Transcript {
+            text: final_result,
+            segments,
+        })
     }
 }
 
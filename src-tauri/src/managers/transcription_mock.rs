@@ -1,22 +1,21 @@
 // Mock TranscriptionManager — compiled when the `transcription` feature is disabled.
 // Avoids pulling in whisper/Vulkan/ONNX dependencies (used in CI).
 
+use crate::host::Host;
 use crate::managers::model::ModelManager;
+use crate::transcript::Transcript;
 use anyhow::Result;
 use std::sync::Arc;
-use tauri::AppHandle;
 
 #[derive(Clone)]
 pub struct TranscriptionManager {
     #[allow(dead_code)]
-    app_handle: AppHandle,
+    host: Arc<dyn Host>,
 }
 
 impl TranscriptionManager {
-    pub fn new(app_handle: &AppHandle, _model_manager: Arc<ModelManager>) -> Result<Self> {
-        Ok(Self {
-            app_handle: app_handle.clone(),
-        })
+    pub fn new(host: Arc<dyn Host>, _model_manager: Arc<ModelManager>) -> Result<Self> {
+        Ok(Self { host })
     }
 
     pub fn is_model_loaded(&self) -> bool {
@@ -42,4 +41,20 @@ impl TranscriptionManager {
     pub fn transcribe(&self, _audio: Vec<f32>) -> Result<String> {
         Ok(String::new())
     }
+
+    pub fn transcribe_timed(&self, _audio: Vec<f32>) -> Result<Transcript> {
+        Ok(Transcript::default())
+    }
+
+    pub fn transcribe_segment(&self, _audio: Vec<f32>) -> Result<Transcript> {
+        Ok(Transcript::default())
+    }
+
+    pub fn is_streaming_model(&self) -> bool {
+        false
+    }
+
+    pub fn transcribe_partial(&self, _audio: Vec<f32>) -> Result<Option<String>> {
+        Ok(None)
+    }
 }
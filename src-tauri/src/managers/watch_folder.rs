@@ -0,0 +1,396 @@
+use crate::actions::transcribe_audio_file;
+use crate::audio_toolkit::audio::is_audio_file;
+use crate::settings::get_settings;
+use anyhow::Result;
+use chrono::Utc;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// A file is left alone until its size and modification time have not
+/// changed for this long, so memos still being synced are not read half-way
+const SETTLE_MS: i64 = 10_000;
+/// Failed files are retried after this, doubling with every attempt…
+const RETRY_BASE_MS: i64 = 30_000;
+/// …up to this many attempts
+const MAX_ATTEMPTS: u32 = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum IngestState {
+    /// Waiting for the file to stop changing
+    Settling {
+        since: i64,
+    },
+    Done {
+        note_id: String,
+        finished_at: i64,
+    },
+    /// `retry_at` is `None` once the file has been given up on
+    Failed {
+        error: String,
+        attempts: u32,
+        retry_at: Option<i64>,
+    },
+}
+
+/// What the watcher knows about one file in the watch folder. Times are
+/// Unix timestamps in milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct WatchedFile {
+    pub path: String,
+    pub size: u64,
+    pub modified_at: i64,
+    pub state: IngestState,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct WatchFolderStatus {
+    pub folder: Option<String>,
+    /// Files seen so far, most recently modified first
+    pub files: Vec<WatchedFile>,
+}
+
+/// Every file the watcher has seen, keyed by path. Saved after each change,
+/// so a file is transcribed once however often the app restarts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Ledger {
+    files: BTreeMap<String, WatchedFile>,
+}
+
+impl Ledger {
+    /// Note the current size and modification time of a file in the folder.
+    /// Returns whether the ledger changed.
+    fn observe(&mut self, path: &str, size: u64, modified_at: i64, now: i64) -> bool {
+        match self.files.get_mut(path) {
+            None => {
+                self.files.insert(
+                    path.to_string(),
+                    WatchedFile {
+                        path: path.to_string(),
+                        size,
+                        modified_at,
+                        state: IngestState::Settling { since: now },
+                    },
+                );
+                true
+            }
+            Some(file) if file.size != size || file.modified_at != modified_at => {
+                file.size = size;
+                file.modified_at = modified_at;
+                // A finished file stays finished; anything else starts over
+                if !matches!(file.state, IngestState::Done { .. }) {
+                    file.state = IngestState::Settling { since: now };
+                }
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    /// The next file to transcribe, oldest first.
+    fn next_due(&self, now: i64) -> Option<&WatchedFile> {
+        self.files
+            .values()
+            .filter(|file| match file.state {
+                IngestState::Settling { since } => {
+                    now - since >= SETTLE_MS && now - file.modified_at >= SETTLE_MS
+                }
+                IngestState::Failed {
+                    retry_at: Some(at), ..
+                } => now >= at,
+                _ => false,
+            })
+            .min_by_key(|file| file.modified_at)
+    }
+
+    fn finish(&mut self, path: &str, result: Result<String, String>, now: i64) {
+        let Some(file) = self.files.get_mut(path) else {
+            return;
+        };
+        file.state = match result {
+            Ok(note_id) => IngestState::Done {
+                note_id,
+                finished_at: now,
+            },
+            Err(error) => {
+                let attempts = match file.state {
+                    IngestState::Failed { attempts, .. } => attempts + 1,
+                    _ => 1,
+                };
+                let retry_at =
+                    (attempts < MAX_ATTEMPTS).then(|| now + RETRY_BASE_MS * (1 << (attempts - 1)));
+                IngestState::Failed {
+                    error,
+                    attempts,
+                    retry_at,
+                }
+            }
+        };
+    }
+}
+
+/// Transcribes audio files that appear in the `watch_folder` setting into
+/// notes, e.g. voice memos synced from a phone.
+///
+/// A background thread polls the folder. New files are transcribed once they
+/// have stopped changing; files that fail (often because they were still
+/// being written) are retried with exponential backoff.
+pub struct WatchFolderManager {
+    watcher: Arc<Watcher>,
+    watcher_handle: Mutex<Option<thread::JoinHandle<()>>>,
+}
+
+/// The state shared with the polling thread.
+struct Watcher {
+    app_handle: AppHandle,
+    ledger: Mutex<Ledger>,
+    ledger_path: PathBuf,
+    shutdown_signal: AtomicBool,
+}
+
+impl WatchFolderManager {
+    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+        let app_data_dir = app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?;
+        fs::create_dir_all(&app_data_dir)?;
+        let ledger_path = app_data_dir.join("watch_folder.json");
+        let ledger = match fs::read_to_string(&ledger_path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ledger::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let watcher = Arc::new(Watcher {
+            app_handle: app_handle.clone(),
+            ledger: Mutex::new(ledger),
+            ledger_path,
+            shutdown_signal: AtomicBool::new(false),
+        });
+
+        let watcher_cloned = Arc::clone(&watcher);
+        let handle = thread::spawn(move || {
+            while !watcher_cloned.shutdown_signal.load(Ordering::Relaxed) {
+                thread::sleep(POLL_INTERVAL);
+                if watcher_cloned.shutdown_signal.load(Ordering::Relaxed) {
+                    break;
+                }
+                if let Err(e) = watcher_cloned.poll() {
+                    warn!("Watch folder poll failed: {}", e);
+                }
+            }
+            debug!("Watch folder thread shutting down gracefully");
+        });
+
+        Ok(Self {
+            watcher,
+            watcher_handle: Mutex::new(Some(handle)),
+        })
+    }
+
+    pub fn status(&self) -> WatchFolderStatus {
+        let folder = get_settings(&self.watcher.app_handle).watch_folder;
+        let mut files: Vec<WatchedFile> = self
+            .watcher
+            .ledger
+            .lock()
+            .unwrap()
+            .files
+            .values()
+            .filter(|file| {
+                folder
+                    .as_deref()
+                    .is_some_and(|folder| Path::new(&file.path).parent() == Some(Path::new(folder)))
+            })
+            .cloned()
+            .collect();
+        files.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+        WatchFolderStatus { folder, files }
+    }
+
+    /// Try a file that was given up on (or is waiting to be retried) again now.
+    pub fn retry(&self, path: &str) -> Result<()> {
+        {
+            let mut ledger = self.watcher.ledger.lock().unwrap();
+            let file = ledger
+                .files
+                .get_mut(path)
+                .ok_or_else(|| anyhow::anyhow!("File is not in the watch folder: {}", path))?;
+            if !matches!(file.state, IngestState::Failed { .. }) {
+                return Err(anyhow::anyhow!("File has not failed: {}", path));
+            }
+            file.state = IngestState::Settling { since: 0 };
+        }
+        self.watcher.save_and_notify()
+    }
+}
+
+impl Watcher {
+    /// Pick up changes in the folder and transcribe the files that are due,
+    /// one at a time.
+    fn poll(&self) -> Result<()> {
+        let settings = get_settings(&self.app_handle);
+        let Some(folder) = settings.watch_folder else {
+            return Ok(());
+        };
+
+        let entries = match fs::read_dir(&folder) {
+            Ok(entries) => entries,
+            // An unmounted or not yet synced folder is not worth a warning
+            // every few seconds
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                debug!("Watch folder {} does not exist", folder);
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let now = Utc::now().timestamp_millis();
+        let mut changed = false;
+        for entry in entries {
+            let path = entry?.path();
+            if !is_audio_file(&path) {
+                continue;
+            }
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let modified_at = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_millis() as i64);
+            changed |= self.ledger.lock().unwrap().observe(
+                &path.to_string_lossy(),
+                metadata.len(),
+                modified_at,
+                now,
+            );
+        }
+        if changed {
+            self.save_and_notify()?;
+        }
+
+        loop {
+            let now = Utc::now().timestamp_millis();
+            let Some(path) = self
+                .ledger
+                .lock()
+                .unwrap()
+                .next_due(now)
+                .map(|file| file.path.clone())
+            else {
+                return Ok(());
+            };
+            if self.shutdown_signal.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            // Settings may have changed while the last file was transcribed
+            let settings = get_settings(&self.app_handle);
+            if settings.watch_folder.as_deref() != Some(folder.as_str()) {
+                return Ok(());
+            }
+
+            info!("Transcribing {} from the watch folder", path);
+            let result = tauri::async_runtime::block_on(transcribe_audio_file(
+                &self.app_handle,
+                PathBuf::from(&path),
+                settings.watch_folder_post_process,
+            ))
+            .map(|note| note.id);
+            if let Err(e) = &result {
+                warn!("Failed to transcribe {} from the watch folder: {}", path, e);
+            }
+            self.ledger
+                .lock()
+                .unwrap()
+                .finish(&path, result, Utc::now().timestamp_millis());
+            self.save_and_notify()?;
+        }
+    }
+
+    fn save_and_notify(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&*self.ledger.lock().unwrap())?;
+        // Write a sibling and rename it over, so a crash cannot leave half a ledger
+        let tmp = self.ledger_path.with_extension("json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &self.ledger_path)?;
+        let _ = self.app_handle.emit("watch-folder-updated", ());
+        Ok(())
+    }
+}
+
+impl Drop for WatchFolderManager {
+    fn drop(&mut self) {
+        debug!("Shutting down WatchFolderManager");
+        self.watcher.shutdown_signal.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.watcher_handle.lock().unwrap().take() {
+            if let Err(e) = handle.join() {
+                warn!("Failed to join watch folder thread: {:?}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ledger_settles_and_backs_off() {
+        let mut ledger = Ledger::default();
+        assert!(ledger.observe("/memos/a.m4a", 100, 0, 1_000));
+        assert!(ledger.next_due(5_000).is_none());
+
+        // Still growing: the wait starts over
+        assert!(ledger.observe("/memos/a.m4a", 200, 6_000, 6_000));
+        assert!(!ledger.observe("/memos/a.m4a", 200, 6_000, 7_000));
+        assert!(ledger.next_due(15_000).is_none());
+        assert_eq!(ledger.next_due(16_000).unwrap().path, "/memos/a.m4a");
+
+        ledger.finish("/memos/a.m4a", Err("truncated".to_string()), 16_000);
+        assert!(ledger.next_due(16_000 + RETRY_BASE_MS - 1).is_none());
+        assert!(ledger.next_due(16_000 + RETRY_BASE_MS).is_some());
+        ledger.finish("/memos/a.m4a", Err("truncated".to_string()), 50_000);
+        assert!(ledger.next_due(50_000 + RETRY_BASE_MS).is_none());
+        assert!(ledger.next_due(50_000 + 2 * RETRY_BASE_MS).is_some());
+
+        ledger.finish("/memos/a.m4a", Ok("memo.md".to_string()), 200_000);
+        assert!(ledger.next_due(i64::MAX).is_none());
+        // Done is done, even if the file is touched again
+        ledger.observe("/memos/a.m4a", 300, 300_000, 300_000);
+        assert!(ledger.next_due(i64::MAX).is_none());
+    }
+
+    #[test]
+    fn test_ledger_gives_up_after_max_attempts() {
+        let mut ledger = Ledger::default();
+        ledger.observe("/memos/b.wav", 10, 0, 0);
+        for _ in 0..MAX_ATTEMPTS {
+            ledger.finish("/memos/b.wav", Err("not audio".to_string()), 0);
+        }
+        assert_eq!(
+            ledger.files["/memos/b.wav"].state,
+            IngestState::Failed {
+                error: "not audio".to_string(),
+                attempts: MAX_ATTEMPTS,
+                retry_at: None,
+            }
+        );
+        assert!(ledger.next_due(i64::MAX).is_none());
+    }
+}
@@ -0,0 +1,322 @@
+//! Minimal YAML frontmatter support for notes.
+//!
+//! Only the subset Jotline writes itself is supported: flat `key: value`
+//! scalars and lists of scalars (flow `[a, b]` or block `- a` style).
+//! Unknown keys written by the user are preserved in order.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrontmatterValue {
+    Text(String),
+    List(Vec<String>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frontmatter {
+    entries: Vec<(String, FrontmatterValue)>,
+}
+
+const DELIMITER: &str = "---";
+
+impl Frontmatter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Insert or replace a value, keeping the original position of existing keys.
+    pub fn set(&mut self, key: &str, value: FrontmatterValue) {
+        match self.entries.iter_mut().find(|(k, _)| k == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.entries.push((key.to_string(), value)),
+        }
+    }
+
+    pub fn set_text(&mut self, key: &str, value: impl Into<String>) {
+        self.set(key, FrontmatterValue::Text(value.into()));
+    }
+
+    pub fn set_list(&mut self, key: &str, values: Vec<String>) {
+        self.set(key, FrontmatterValue::List(values));
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.entries.retain(|(k, _)| k != key);
+    }
+
+    pub fn get_text(&self, key: &str) -> Option<&str> {
+        self.entries.iter().find_map(|(k, v)| match v {
+            FrontmatterValue::Text(text) if k == key => Some(text.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Render as a `---` delimited block, including the trailing newline.
+    /// Returns an empty string when there are no entries.
+    pub fn render(&self) -> String {
+        if self.entries.is_empty() {
+            return String::new();
+        }
+
+        let mut out = String::from(DELIMITER);
+        out.push('\n');
+        for (key, value) in &self.entries {
+            out.push_str(key);
+            out.push(':');
+            match value {
+                FrontmatterValue::Text(text) => {
+                    out.push(' ');
+                    out.push_str(&yaml_scalar(text));
+                }
+                FrontmatterValue::List(items) => {
+                    let items: Vec<String> = items.iter().map(|i| yaml_scalar(i)).collect();
+                    out.push_str(" [");
+                    out.push_str(&items.join(", "));
+                    out.push(']');
+                }
+            }
+            out.push('\n');
+        }
+        out.push_str(DELIMITER);
+        out.push('\n');
+        out
+    }
+
+    /// Split a note into its frontmatter and body. Content without a leading
+    /// frontmatter block yields an empty `Frontmatter` and the full content.
+    pub fn parse(content: &str) -> (Frontmatter, &str) {
+        let Some(rest) = content
+            .strip_prefix("---\n")
+            .or_else(|| content.strip_prefix("---\r\n"))
+        else {
+            return (Frontmatter::new(), content);
+        };
+
+        let mut frontmatter = Frontmatter::new();
+        let mut offset = content.len() - rest.len();
+        let mut pending_list: Option<String> = None;
+
+        for line in rest.split_inclusive('\n') {
+            offset += line.len();
+            let trimmed = line.trim_end_matches(['\r', '\n']);
+
+            if trimmed == DELIMITER {
+                let body = &content[offset..];
+                return (frontmatter, body.strip_prefix('\n').unwrap_or(body));
+            }
+
+            if let Some(item) = trimmed.trim_start().strip_prefix("- ") {
+                if let Some(key) = &pending_list {
+                    if let Some((_, FrontmatterValue::List(items))) =
+                        frontmatter.entries.iter_mut().find(|(k, _)| k == key)
+                    {
+                        items.push(unquote(item.trim()));
+                    }
+                }
+                continue;
+            }
+
+            let Some((key, value)) = trimmed.split_once(':') else {
+                continue;
+            };
+            let key = key.trim();
+            let value = value.trim();
+            if key.is_empty() {
+                continue;
+            }
+
+            if value.is_empty() {
+                // Either an empty scalar or the start of a block list
+                frontmatter.set_list(key, Vec::new());
+                pending_list = Some(key.to_string());
+            } else if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                frontmatter.set_list(key, split_flow_list(inner));
+                pending_list = None;
+            } else {
+                frontmatter.set_text(key, unquote(value));
+                pending_list = None;
+            }
+        }
+
+        // No closing delimiter: treat the whole thing as body
+        (Frontmatter::new(), content)
+    }
+}
+
+/// Prepend `frontmatter` to `body`, replacing any frontmatter already present.
+pub fn with_frontmatter(frontmatter: &Frontmatter, body: &str) -> String {
+    let (_, body) = Frontmatter::parse(body);
+    if frontmatter.is_empty() {
+        return body.to_string();
+    }
+    format!("{}\n{}", frontmatter.render(), body)
+}
+
+fn needs_quotes(value: &str) -> bool {
+    if value.is_empty() || value.trim() != value {
+        return true;
+    }
+    let lower = value.to_lowercase();
+    if matches!(
+        lower.as_str(),
+        "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "~"
+    ) || value.parse::<f64>().is_ok()
+    {
+        return true;
+    }
+    if value.starts_with([
+        '-', '?', ':', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%', '@', '`',
+    ]) {
+        return true;
+    }
+    value.contains(": ")
+        || value.contains(" #")
+        || value.ends_with(':')
+        || value.contains([',', '[', ']', '{', '}', '\n', '\r', '\t', '"', '\\'])
+}
+
+fn yaml_scalar(value: &str) -> String {
+    if !needs_quotes(value) {
+        return value.to_string();
+    }
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn unquote(value: &str) -> String {
+    if let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        let mut out = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some('t') => out.push('\t'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            }
+        }
+        return out;
+    }
+    if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        return inner.replace("''", "'");
+    }
+    value.to_string()
+}
+
+/// Split the inside of a flow list on commas that are not inside quotes.
+fn split_flow_list(inner: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+
+    for c in inner.chars() {
+        match quote {
+            Some(q) => {
+                current.push(c);
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' && q == '"' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                current.push(c);
+            }
+            None if c == ',' => {
+                items.push(std::mem::take(&mut current));
+            }
+            None => current.push(c),
+        }
+    }
+    items.push(current);
+
+    items
+        .iter()
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(unquote)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_quotes_when_needed() {
+        let mut fm = Frontmatter::new();
+        fm.set_text("captured_at", "2025-01-31T14:25:01+01:00");
+        fm.set_text("window_title", "main.rs: jotline - Code");
+        fm.set_text("git_branch", "123");
+        fm.set_list("tags", vec!["rust".to_string(), "a, b".to_string()]);
+
+        assert_eq!(
+            fm.render(),
+            "---\ncaptured_at: 2025-01-31T14:25:01+01:00\nwindow_title: \"main.rs: jotline - Code\"\ngit_branch: \"123\"\ntags: [rust, \"a, b\"]\n---\n"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut fm = Frontmatter::new();
+        fm.set_text("title", "He said \"hi\"\nthen left");
+        fm.set_text("cwd", "/home/me/src/jotline");
+        fm.set_list("tags", vec!["one".to_string(), "two words".to_string()]);
+
+        let content = with_frontmatter(&fm, "Body text\n");
+        let (parsed, body) = Frontmatter::parse(&content);
+
+        assert_eq!(parsed, fm);
+        assert_eq!(body, "Body text\n");
+    }
+
+    #[test]
+    fn test_parse_block_list_and_missing_frontmatter() {
+        let content = "---\ntags:\n  - alpha\n  - 'beta'\nproject: jotline\n---\nHello";
+        let (fm, body) = Frontmatter::parse(content);
+        let mut expected = Frontmatter::new();
+        expected.set_list("tags", vec!["alpha".to_string(), "beta".to_string()]);
+        expected.set_text("project", "jotline");
+        assert_eq!(fm, expected);
+        assert_eq!(body, "Hello");
+
+        let (fm, body) = Frontmatter::parse("Just text");
+        assert!(fm.is_empty());
+        assert_eq!(body, "Just text");
+
+        let (fm, body) = Frontmatter::parse("---\nunterminated: yes\n");
+        assert!(fm.is_empty());
+        assert_eq!(body, "---\nunterminated: yes\n");
+    }
+
+    #[test]
+    fn test_with_frontmatter_replaces_existing_block() {
+        let mut fm = Frontmatter::new();
+        fm.set_text("project", "new");
+        let content = with_frontmatter(&fm, "---\nproject: old\n---\nBody\n");
+        assert_eq!(content, "---\nproject: new\n---\n\nBody\n");
+    }
+}
@@ -0,0 +1,686 @@
+//! Keyword extraction for note tags.
+//!
+//! A port of the RAKE+IDF prototype in `poc/eval-rake-idf.py`. Space-separated
+//! languages get RAKE-style candidates: runs of words between stopwords, with
+//! long runs broken into shorter phrases. Japanese is split on script changes
+//! so kanji and katakana runs become candidates. Chinese, Cantonese, Thai and
+//! the other scripts written without spaces are segmented with the ICU
+//! dictionary segmenter instead of pythainlp, and Chinese with the frequency
+//! table the way jieba does when there is one.
+//!
+//! Candidates are ranked by rarity, the mean `-log10(frequency)` of their
+//! words, from the tables in `resources/wordfreq` that
+//! `poc/export-wordfreq.py` writes. The bundled `zh.tsv` comes from jieba's
+//! dictionary. Languages without a table use the prototype's fallback score:
+//! stopwords score zero and longer words score higher.
+
+use icu_segmenter::WordSegmenter;
+use log::{debug, warn};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+const MAX_KEYWORDS: usize = 3;
+
+/// Longer keywords do not fit in a tag chip
+const MAX_KEYWORD_CHARS: usize = 30;
+
+/// Longer stopword-free runs are split into sub-phrases of this many words
+const MAX_PHRASE_TOKENS: usize = 3;
+
+/// Candidates scoring below this are not confident enough to become tags:
+/// words more common than one in a hundred, or two-letter words without a
+/// frequency table
+const MIN_IDF: f32 = 2.0;
+const MIN_FALLBACK_SCORE: f32 = 0.5;
+
+/// Words missing from a frequency table are rare
+const DEFAULT_IDF: f32 = 7.0;
+
+/// Languages with a stopword list in `stop-words`.
+const STOPWORD_LANGUAGES: &[&str] = &[
+    "af", "ar", "bg", "bn", "br", "ca", "cs", "da", "de", "el", "en", "eo", "es", "et", "eu", "fa",
+    "fi", "fr", "ga", "gl", "gu", "ha", "he", "hi", "hr", "hu", "hy", "id", "it", "ja", "ko", "ku",
+    "la", "lt", "lv", "mr", "ms", "nl", "no", "pl", "pt", "ro", "ru", "sk", "sl", "so", "st", "sv",
+    "sw", "th", "tl", "tr", "uk", "ur", "vi", "yo", "zh", "zu",
+];
+
+/// Includes combining marks so Brahmic scripts are not cut at vowel signs.
+static TOKEN_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[\w\p{M}]+").unwrap());
+
+static STOPWORDS: Lazy<HashMap<&'static str, HashSet<String>>> = Lazy::new(|| {
+    STOPWORD_LANGUAGES
+        .iter()
+        .map(|&lang| {
+            let words = stop_words::get(lang).iter().map(|w| w.to_lowercase());
+            (lang, words.collect())
+        })
+        .collect()
+});
+
+static NO_STOPWORDS: Lazy<HashSet<String>> = Lazy::new(HashSet::new);
+
+static FREQUENCY_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Tables read so far by language, `None` for languages without one
+static IDF_TABLES: Lazy<Mutex<HashMap<String, Option<Arc<IdfTable>>>>> =
+    Lazy::new(Default::default);
+
+/// IDF of the words in one language's frequency table.
+struct IdfTable {
+    idf: HashMap<String, f32>,
+    /// Characters in the longest word
+    max_word_chars: usize,
+}
+
+impl IdfTable {
+    /// Lines of `word<TAB>frequency`; `#` starts a comment.
+    fn parse(tsv: &str) -> Self {
+        let idf = tsv
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('\t'))
+            .filter_map(|(word, freq)| Some((word, freq.trim().parse::<f64>().ok()?)))
+            .filter(|&(_, freq)| freq > 0.0)
+            .map(|(word, freq)| (word.to_lowercase(), -freq.log10() as f32))
+            .collect::<HashMap<_, _>>();
+        let max_word_chars = idf.keys().map(|w| w.chars().count()).max().unwrap_or(0);
+        Self {
+            idf,
+            max_word_chars,
+        }
+    }
+
+    fn get(&self, word: &str) -> Option<f32> {
+        self.idf.get(&word.to_lowercase()).copied()
+    }
+}
+
+/// How candidates are scored: by rarity where the language has a frequency
+/// table, otherwise by the prototype's fallback.
+#[derive(Clone, Copy)]
+enum Scorer<'a> {
+    Idf(&'a IdfTable),
+    Fallback(&'a HashSet<String>),
+}
+
+impl Scorer<'_> {
+    fn score(&self, word: &str) -> f32 {
+        match self {
+            Scorer::Idf(table) => table.get(word).unwrap_or(DEFAULT_IDF),
+            Scorer::Fallback(stopwords) => fallback_score(word, stopwords),
+        }
+    }
+
+    fn min_score(&self) -> f32 {
+        match self {
+            Scorer::Idf(_) => MIN_IDF,
+            Scorer::Fallback(_) => MIN_FALLBACK_SCORE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Kanji,
+    Hiragana,
+    Katakana,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    /// Words separated by spaces
+    Rake,
+    /// Kanji and katakana runs
+    Japanese,
+    /// Dictionary word segmentation for scripts written without spaces
+    Segmented,
+}
+
+#[derive(Debug, Clone)]
+struct Candidate {
+    text: String,
+    score: f32,
+}
+
+/// Up to three keywords found in `text`, best first. `locale` is a
+/// transcription language code such as `en`, `zh-Hant` or `yue`; with `auto`
+/// the language is guessed from the text.
+pub fn extract_keywords(text: &str, locale: &str) -> Vec<String> {
+    let locale = if locale == "auto" {
+        detect_locale(text)
+    } else {
+        locale
+    };
+    let table = idf_table(locale);
+    keywords_with(text, locale, table.as_deref())
+}
+
+/// Where the frequency tables are; until this is set every language uses
+/// the fallback score.
+pub fn set_frequency_dir(dir: PathBuf) {
+    let _ = FREQUENCY_DIR.set(dir);
+}
+
+fn keywords_with(text: &str, locale: &str, table: Option<&IdfTable>) -> Vec<String> {
+    let stopwords = stopwords_for(locale);
+    let scorer = match table {
+        Some(table) if strategy(locale) != Strategy::Segmented || knows_han(table, text) => {
+            Scorer::Idf(table)
+        }
+        _ => Scorer::Fallback(stopwords),
+    };
+
+    let candidates = match strategy(locale) {
+        Strategy::Rake => rake_candidates(text, stopwords, scorer),
+        Strategy::Japanese => japanese_candidates(text, scorer),
+        Strategy::Segmented => segmented_candidates(text, stopwords, scorer),
+    };
+    select_keywords(candidates, scorer.min_score())
+}
+
+/// Turn a keyword into a frontmatter tag: lowercase, with dashes for spaces.
+pub fn keyword_tag(keyword: &str) -> String {
+    keyword
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+fn strategy(locale: &str) -> Strategy {
+    match base_language(locale) {
+        "ja" => Strategy::Japanese,
+        "zh" | "yue" | "th" | "lo" | "km" | "my" | "bo" => Strategy::Segmented,
+        _ => Strategy::Rake,
+    }
+}
+
+fn base_language(locale: &str) -> &str {
+    locale.split(['-', '_']).next().unwrap_or(locale)
+}
+
+/// The table for `locale`, its base language, or Chinese for Cantonese, as
+/// the prototype resolves `wordfreq` languages.
+fn idf_table(locale: &str) -> Option<Arc<IdfTable>> {
+    let dir = FREQUENCY_DIR.get()?;
+    let base = base_language(locale);
+    let mut names = vec![locale, base];
+    if base == "yue" {
+        names.push("zh");
+    }
+
+    let mut tables = IDF_TABLES.lock().unwrap();
+    for name in names {
+        let table = tables.entry(name.to_string()).or_insert_with(|| {
+            let path = dir.join(format!("{}.tsv", name));
+            match fs::read_to_string(&path) {
+                Ok(tsv) => {
+                    debug!("Loaded word frequencies from {:?}", path);
+                    Some(Arc::new(IdfTable::parse(&tsv)))
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => {
+                    warn!("Failed to read word frequencies from {:?}: {}", path, e);
+                    None
+                }
+            }
+        });
+        if let Some(table) = table {
+            return Some(table.clone());
+        }
+    }
+    None
+}
+
+fn stopwords_for(locale: &str) -> &'static HashSet<String> {
+    let lang = match base_language(locale) {
+        "yue" => "zh",
+        "nn" | "nb" => "no",
+        lang => lang,
+    };
+    STOPWORDS.get(lang).unwrap_or(&NO_STOPWORDS)
+}
+
+/// Guess a locale from the script, falling back to the language whose
+/// stopwords occur most often in the text.
+fn detect_locale(text: &str) -> &'static str {
+    let has = |f: fn(char) -> bool| text.chars().any(f);
+    if has(|c| matches!(char_script(c), Script::Hiragana | Script::Katakana)) {
+        return "ja";
+    }
+    if has(|c| char_script(c) == Script::Kanji) {
+        return "zh";
+    }
+    if has(|c| ('\u{0E00}'..='\u{0E7F}').contains(&c)) {
+        return "th";
+    }
+    if has(|c| ('\u{0E80}'..='\u{0EFF}').contains(&c)) {
+        return "lo";
+    }
+    if has(|c| ('\u{1780}'..='\u{17FF}').contains(&c)) {
+        return "km";
+    }
+    if has(|c| ('\u{1000}'..='\u{109F}').contains(&c)) {
+        return "my";
+    }
+
+    let words: Vec<String> = TOKEN_RE
+        .find_iter(text)
+        .map(|m| m.as_str().to_lowercase())
+        .collect();
+    STOPWORD_LANGUAGES
+        .iter()
+        .map(|&lang| {
+            let stopwords = &STOPWORDS[lang];
+            let hits = words.iter().filter(|w| stopwords.contains(*w)).count();
+            (lang, hits)
+        })
+        // Ties keep the earliest language, so plain text without hits is "af";
+        // that only affects which (empty) stopwords apply
+        .fold(
+            ("", 0),
+            |best, (lang, hits)| {
+                if hits > best.1 {
+                    (lang, hits)
+                } else {
+                    best
+                }
+            },
+        )
+        .0
+}
+
+/// Prototype fallback score: zero for stopwords, otherwise scaled by length
+/// from 0.5 for two characters up to 2.0 for eight or more.
+fn fallback_score(word: &str, stopwords: &HashSet<String>) -> f32 {
+    if stopwords.contains(&word.to_lowercase()) {
+        return 0.0;
+    }
+    (word.chars().count() as f32 / 4.0).min(2.0)
+}
+
+fn phrase_candidate(text: &str, tokens: &[(usize, usize)], scorer: Scorer) -> Candidate {
+    let (start, end) = (tokens[0].0, tokens[tokens.len() - 1].1);
+    let score = tokens
+        .iter()
+        .map(|&(s, e)| scorer.score(&text[s..e]))
+        .sum::<f32>()
+        / tokens.len() as f32;
+    Candidate {
+        text: text[start..end].to_string(),
+        score,
+    }
+}
+
+fn rake_candidates(text: &str, stopwords: &HashSet<String>, scorer: Scorer) -> Vec<Candidate> {
+    let tokens: Vec<(usize, usize)> = TOKEN_RE
+        .find_iter(text)
+        .map(|m| (m.start(), m.end()))
+        .collect();
+
+    // Phrases are maximal runs of tokens between stopwords
+    let mut phrases: Vec<&[(usize, usize)]> = Vec::new();
+    let mut phrase_start = None;
+    for (i, &(s, e)) in tokens.iter().enumerate() {
+        let word = &text[s..e];
+        let is_stop = word.chars().count() < 2 || stopwords.contains(&word.to_lowercase());
+        match (is_stop, phrase_start) {
+            (true, Some(start)) => {
+                phrases.push(&tokens[start..i]);
+                phrase_start = None;
+            }
+            (false, None) => phrase_start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(start) = phrase_start {
+        phrases.push(&tokens[start..]);
+    }
+
+    let mut candidates = Vec::new();
+    for phrase in phrases {
+        if phrase.len() <= MAX_PHRASE_TOKENS {
+            candidates.push(phrase_candidate(text, phrase, scorer));
+            continue;
+        }
+        // Too long for a chip: offer every window of the maximum length,
+        // then every single word
+        for window in phrase.windows(MAX_PHRASE_TOKENS) {
+            candidates.push(phrase_candidate(text, window, scorer));
+        }
+        for token in phrase {
+            candidates.push(phrase_candidate(text, std::slice::from_ref(token), scorer));
+        }
+    }
+    candidates
+}
+
+fn char_script(c: char) -> Script {
+    match c as u32 {
+        0x4E00..=0x9FFF | 0x3400..=0x4DBF | 0xF900..=0xFAFF | 0x2E80..=0x2FDF => Script::Kanji,
+        0x3040..=0x309F => Script::Hiragana,
+        0x30A0..=0x30FF | 0xFF66..=0xFF9F => Script::Katakana,
+        _ => Script::Other,
+    }
+}
+
+fn japanese_candidates(text: &str, scorer: Scorer) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+    let mut run = String::new();
+    let mut run_script = Script::Other;
+
+    for c in text.chars() {
+        let mut script = char_script(c);
+        // The long vowel mark belongs to the katakana word it follows
+        if c == 'ー' && run_script == Script::Katakana {
+            script = Script::Katakana;
+        }
+        if script != run_script {
+            flush_japanese_run(&mut candidates, &run, run_script, scorer);
+            run.clear();
+            run_script = script;
+        }
+        if matches!(script, Script::Kanji | Script::Katakana) {
+            run.push(c);
+        }
+    }
+    flush_japanese_run(&mut candidates, &run, run_script, scorer);
+    candidates
+}
+
+fn flush_japanese_run(candidates: &mut Vec<Candidate>, run: &str, script: Script, scorer: Scorer) {
+    let len = run.chars().count();
+    let mut push = |word: &str| {
+        candidates.push(Candidate {
+            text: word.to_string(),
+            score: scorer.score(word),
+        })
+    };
+    match script {
+        // A lone kanji is too ambiguous to be a keyword
+        Script::Kanji if len < 2 => {}
+        // Long kanji runs are often several words run together (来月切 is
+        // 来月 + 切). A run the frequency table knows is one word; otherwise
+        // keep the known bigrams inside it
+        Script::Kanji if len > 2 => match scorer {
+            Scorer::Idf(table) if table.get(run).is_some() => push(run),
+            Scorer::Idf(table) => {
+                let chars: Vec<char> = run.chars().collect();
+                for pair in chars.windows(2) {
+                    let bigram: String = pair.iter().collect();
+                    if table.get(&bigram).is_some() {
+                        push(&bigram);
+                    }
+                }
+            }
+            // Without a table, the segmenter's dictionary finds the words
+            Scorer::Fallback(_) => {
+                for word in segment(run) {
+                    if word.chars().count() >= 2 {
+                        push(word);
+                    }
+                }
+            }
+        },
+        Script::Kanji | Script::Katakana => push(run),
+        _ => {}
+    }
+}
+
+fn segment(text: &str) -> Vec<&str> {
+    // Backed by compiled-in data, so building one is cheap
+    let breaks: Vec<usize> = WordSegmenter::new_auto().segment_str(text).collect();
+    breaks.windows(2).map(|w| &text[w[0]..w[1]]).collect()
+}
+
+/// Whether `table` knows nearly every Han character in `text`. Chinese tables
+/// only hold simplified characters, so traditional text scored with one would
+/// be all unknown words.
+fn knows_han(table: &IdfTable, text: &str) -> bool {
+    let mut buf = [0; 4];
+    let (han, unknown) = text
+        .chars()
+        .filter(|&c| char_script(c) == Script::Kanji)
+        .fold((0, 0), |(han, unknown), c| {
+            let known = table.idf.contains_key(&*c.encode_utf8(&mut buf));
+            (han + 1, unknown + usize::from(!known))
+        });
+    unknown * 10 <= han
+}
+
+/// Split runs of Han characters the way jieba does without its HMM: into the
+/// table words, or single characters, with the highest combined frequency.
+/// Everything else goes to the ICU segmenter.
+fn segment_with_table<'a>(text: &'a str, table: &IdfTable) -> Vec<&'a str> {
+    let mut words = Vec::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let is_han = char_script(c) == Script::Kanji;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| (char_script(c) == Script::Kanji) != is_han)
+            .map_or(rest.len(), |(i, _)| i);
+        let (run, next) = rest.split_at(end);
+        if is_han {
+            words.extend(segment_han(run, table));
+        } else {
+            words.extend(segment(run));
+        }
+        rest = next;
+    }
+    words
+}
+
+fn segment_han<'a>(run: &'a str, table: &IdfTable) -> Vec<&'a str> {
+    let bounds: Vec<usize> = run
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([run.len()])
+        .collect();
+    let n = bounds.len() - 1;
+
+    // best[i]: lowest total IDF of the text from character i on, and where
+    // its first word ends
+    let mut best = vec![(0.0f32, n); n + 1];
+    for i in (0..n).rev() {
+        best[i] = (1..=table.max_word_chars.max(1).min(n - i))
+            .filter_map(|len| {
+                let idf = table.idf.get(&run[bounds[i]..bounds[i + len]]).copied();
+                // An unknown character still has to be a word of its own
+                let idf = idf.or((len == 1).then_some(DEFAULT_IDF))?;
+                Some((idf + best[i + len].0, i + len))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .unwrap_or((DEFAULT_IDF, i + 1));
+    }
+
+    let mut words = Vec::new();
+    let mut i = 0;
+    while i < n {
+        let end = best[i].1;
+        words.push(&run[bounds[i]..bounds[end]]);
+        i = end;
+    }
+    words
+}
+
+fn segmented_candidates(text: &str, stopwords: &HashSet<String>, scorer: Scorer) -> Vec<Candidate> {
+    let words = match scorer {
+        Scorer::Idf(table) => segment_with_table(text, table),
+        Scorer::Fallback(_) => segment(text),
+    };
+    words
+        .into_iter()
+        .map(str::trim)
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        // Single characters are mostly particles and function words
+        .filter(|word| word.chars().count() > 1)
+        .filter(|word| !stopwords.contains(&word.to_lowercase()))
+        .map(|word| Candidate {
+            text: word.to_string(),
+            score: scorer.score(word),
+        })
+        .collect()
+}
+
+/// Best-scoring candidates first, skipping ones that overlap a keyword
+/// already chosen.
+fn select_keywords(mut candidates: Vec<Candidate>, min_score: f32) -> Vec<String> {
+    // Stable, so equal scores keep their order in the text
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let mut selected: Vec<String> = Vec::new();
+    for candidate in candidates {
+        if selected.len() >= MAX_KEYWORDS {
+            break;
+        }
+        let text = candidate.text.trim();
+        let chars = text.chars().count();
+        if !(2..=MAX_KEYWORD_CHARS).contains(&chars) || candidate.score < min_score {
+            continue;
+        }
+        let lower = text.to_lowercase();
+        if selected
+            .iter()
+            .any(|s| s.contains(&lower) || lower.contains(s.as_str()))
+        {
+            continue;
+        }
+        selected.push(lower);
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[derive(serde::Deserialize)]
+    struct TestCase {
+        id: String,
+        locale: String,
+        transcript: String,
+    }
+
+    const DENTIST: &str = "I need to remember to call the dentist tomorrow morning and \
+                           reschedule my appointment because I have a conflict with the \
+                           team meeting";
+
+    fn bundled_tables() {
+        set_frequency_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/wordfreq"));
+    }
+
+    #[test]
+    fn test_extract_keywords() {
+        // Common words rank below rare ones, and words missing from the table
+        // count as rare
+        let table = IdfTable::parse(
+            "# test table\nremember\t2e-4\ncall\t5e-4\ndentist\t1e-5\ntomorrow\t2e-4\n\
+             morning\t3e-4\nappointment\t3e-5\nconflict\t5e-5\nteam\t4e-4\nmeeting\t2e-4\n",
+        );
+        assert_eq!(
+            keywords_with(DENTIST, "en", Some(&table)),
+            vec!["reschedule", "appointment", "conflict"]
+        );
+        // Words more common than one in a hundred are never keywords
+        let table = IdfTable::parse("remember\t0.02\ndentist\t0.5\n");
+        assert!(keywords_with("remember dentist", "en", Some(&table)).is_empty());
+
+        // Without a table, longer words win
+        assert_eq!(
+            keywords_with(DENTIST, "en", None),
+            vec!["remember", "reschedule", "appointment"]
+        );
+
+        // Kanji runs the table does not know keep their known bigrams
+        let table = IdfTable::parse("来月\t1e-4\n冷蔵庫\t1e-5\n牛乳\t2e-5\n");
+        assert_eq!(
+            keywords_with("来月切れる冷蔵庫の牛乳を買う", "ja", Some(&table)),
+            vec!["冷蔵庫", "牛乳", "来月"]
+        );
+
+        assert_eq!(
+            extract_keywords("来月切れる冷蔵庫の牛乳を買う", "ja"),
+            extract_keywords("来月切れる冷蔵庫の牛乳を買う", "auto")
+        );
+        assert!(extract_keywords("", "en").is_empty());
+        assert_eq!(keyword_tag("Team  Meeting"), "team-meeting");
+    }
+
+    #[test]
+    fn test_chinese_keywords_use_bundled_table() {
+        bundled_tables();
+        let cases = [
+            (
+                "明天下午三点要去银行办理新的信用卡申请手续",
+                vec!["三点", "信用卡", "手续"],
+            ),
+            (
+                "家里空调又不制冷了得找人来修一下趁还没到最热的时候",
+                vec!["制冷", "空调", "家里"],
+            ),
+            (
+                "快递到了放在菜鸟驿站了下班后过去取一下",
+                vec!["快递", "驿站", "下班"],
+            ),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(extract_keywords(text, "zh"), expected, "{}", text);
+        }
+
+        // The table has no traditional characters, so these are segmented and
+        // scored without it
+        assert_eq!(
+            extract_keywords("禮拜三要去醫院掛號看牙齒記得帶健保卡", "zh-Hant"),
+            vec!["健保卡", "禮拜", "醫院"]
+        );
+    }
+
+    /// Every labelled transcript from the prototype's evaluation set must give
+    /// keywords that appear verbatim in the transcript.
+    #[test]
+    fn test_poc_cases_keywords_come_from_transcript() {
+        bundled_tables();
+        let cases: Vec<TestCase> = include_str!("../../../poc/test-cases.jsonl")
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(cases.len(), 510);
+
+        let mut with_keywords = 0;
+        for case in &cases {
+            let keywords = extract_keywords(&case.transcript, &case.locale);
+            let transcript = case.transcript.to_lowercase();
+            assert!(
+                keywords.len() <= MAX_KEYWORDS,
+                "{}: {:?}",
+                case.id,
+                keywords
+            );
+            for keyword in &keywords {
+                assert!(
+                    transcript.contains(keyword.as_str()),
+                    "{}: {:?} is not in the transcript",
+                    case.id,
+                    keyword
+                );
+            }
+            if !keywords.is_empty() {
+                with_keywords += 1;
+            }
+        }
+        assert!(
+            with_keywords * 100 >= cases.len() * 95,
+            "only {} of {} transcripts produced keywords",
+            with_keywords,
+            cases.len()
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! The generated "Related" section of `[[wikilinks]]` at the end of a note.
+//!
+//! The section is owned by Jotline: it is rewritten whenever the note or its
+//! neighbours change, so hand edits inside it do not survive. Anything after
+//! the heading that is not a wikilink list item means the heading belongs to
+//! the user, and the note is treated as having no generated section.
+
+pub const RELATED_HEADING: &str = "## Related";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelatedLink {
+    /// Note id without the `.md` extension, as wikilinks expect
+    pub target: String,
+    pub title: String,
+}
+
+impl RelatedLink {
+    pub fn new(id: &str, title: &str) -> Self {
+        Self {
+            target: link_target(id).to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    /// Id of the note the link points at.
+    pub fn note_id(&self) -> String {
+        format!("{}.md", self.target)
+    }
+}
+
+/// The wikilink target for a note id.
+pub fn link_target(id: &str) -> &str {
+    id.strip_suffix(".md").unwrap_or(id)
+}
+
+/// Split `content` into the text before the generated section and the links
+/// listed in it.
+pub fn split_related(content: &str) -> (&str, Vec<RelatedLink>) {
+    let mut offset = 0;
+    let mut heading_at = None;
+    for line in content.split_inclusive('\n') {
+        if line.trim_end() == RELATED_HEADING {
+            heading_at = Some(offset);
+        }
+        offset += line.len();
+    }
+
+    let Some(start) = heading_at else {
+        return (content, Vec::new());
+    };
+    let mut links = Vec::new();
+    for line in content[start..].lines().skip(1) {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match parse_link_item(line) {
+            Some(link) => links.push(link),
+            None => return (content, Vec::new()),
+        }
+    }
+    (&content[..start], links)
+}
+
+/// Replace the generated section of `content` with `links`, removing it
+/// entirely when there are none.
+pub fn with_related(content: &str, links: &[RelatedLink]) -> String {
+    let (text, _) = split_related(content);
+    let mut out = format!("{}\n", text.trim_end());
+    if !links.is_empty() {
+        out.push('\n');
+        out.push_str(RELATED_HEADING);
+        out.push_str("\n\n");
+        for link in links {
+            out.push_str(&format!("- {}\n", render_link(link)));
+        }
+    }
+    out
+}
+
+fn render_link(link: &RelatedLink) -> String {
+    // `[`, `]` and `|` would end the link early
+    let title: String = link
+        .title
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | '|'))
+        .collect();
+    let title = title.trim();
+    if title.is_empty() || title == link.target {
+        format!("[[{}]]", link.target)
+    } else {
+        format!("[[{}|{}]]", link.target, title)
+    }
+}
+
+fn parse_link_item(line: &str) -> Option<RelatedLink> {
+    let inner = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))?
+        .trim()
+        .strip_prefix("[[")?
+        .strip_suffix("]]")?;
+    let (target, title) = inner.split_once('|').unwrap_or((inner, inner));
+    let target = target.trim();
+    if target.is_empty() {
+        return None;
+    }
+    Some(RelatedLink {
+        target: target.to_string(),
+        title: title.trim().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_related_appends_and_replaces() {
+        let links = vec![
+            RelatedLink::new(
+                "jotline/2025-01-31_142501-fix-overlay.md",
+                "Fix the [overlay]",
+            ),
+            RelatedLink::new(
+                "2025-02-01_090000-groceries.md",
+                "2025-02-01_090000-groceries",
+            ),
+        ];
+        let content = with_related("Call the dentist.\n", &links);
+        assert_eq!(
+            content,
+            "Call the dentist.\n\n## Related\n\n\
+             - [[jotline/2025-01-31_142501-fix-overlay|Fix the overlay]]\n\
+             - [[2025-02-01_090000-groceries]]\n"
+        );
+
+        let (text, parsed) = split_related(&content);
+        assert_eq!(text, "Call the dentist.\n\n");
+        assert_eq!(parsed[0].target, "jotline/2025-01-31_142501-fix-overlay");
+        assert_eq!(
+            parsed[0].note_id(),
+            "jotline/2025-01-31_142501-fix-overlay.md"
+        );
+        assert_eq!(parsed[1].title, "2025-02-01_090000-groceries");
+
+        let replaced = with_related(&content, &links[1..]);
+        assert_eq!(
+            replaced,
+            "Call the dentist.\n\n## Related\n\n- [[2025-02-01_090000-groceries]]\n"
+        );
+        assert_eq!(with_related(&replaced, &[]), "Call the dentist.\n");
+    }
+
+    #[test]
+    fn test_user_heading_is_not_replaced() {
+        let content = "Notes\n\n## Related\n\nSee the design doc.\n";
+        let (text, links) = split_related(content);
+        assert_eq!(text, content);
+        assert!(links.is_empty());
+    }
+}
@@ -0,0 +1,11 @@
+//! Helpers for the Markdown notes written by the note manager.
+
+pub mod frontmatter;
+pub mod keywords;
+pub mod links;
+pub mod project;
+
+pub use frontmatter::{with_frontmatter, Frontmatter};
+pub use keywords::{extract_keywords, keyword_tag, set_frequency_dir};
+pub use links::{link_target, split_related, with_related, RelatedLink};
+pub use project::{parse_voice_override, project_folder};
@@ -0,0 +1,157 @@
+//! Project names and folders for notes.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Spoken project names are kept short so ordinary sentences that happen to
+/// start with "project" are not mistaken for an override.
+const MAX_PROJECT_WORDS: usize = 2;
+
+/// "Project jotline. Fix the overlay flicker." or "For project jotline, ..."
+static LEADING_OVERRIDE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(for\s+)?project[\s:]+([^.,:;!?\n]+)[.,:;!?]+\s*(.*)$").unwrap()
+});
+
+/// "Fix the overlay flicker. Project jotline." or "..., for project jotline."
+static TRAILING_OVERRIDE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(.*[.!?,])\s*(for\s+)?project[\s:]+([^.,:;!?\n]+)[.!?]?\s*$").unwrap()
+});
+
+/// Look for a spoken "project X" at the start or end of a dictation.
+/// Returns the project name and the text with the phrase removed.
+///
+/// "Project update: ..." is an ordinary sentence as often as an override,
+/// so a bare "project X" only counts when X names one of `known_folders`
+/// (see [`project_folder`]); "for project X" always does.
+pub fn parse_voice_override(text: &str, known_folders: &[String]) -> Option<(String, String)> {
+    let text = text.trim();
+    let accepted = |explicit: bool, raw: &str| {
+        clean_name(raw).filter(|name| {
+            explicit || project_folder(name).is_some_and(|folder| known_folders.contains(&folder))
+        })
+    };
+
+    if let Some(caps) = LEADING_OVERRIDE.captures(text) {
+        let rest = caps[3].trim();
+        if let Some(name) = accepted(caps.get(1).is_some(), &caps[2]) {
+            if !rest.is_empty() {
+                return Some((name, rest.to_string()));
+            }
+        }
+    }
+
+    if let Some(caps) = TRAILING_OVERRIDE.captures(text) {
+        let rest = caps[1].trim().trim_end_matches(',').trim_end();
+        if let Some(name) = accepted(caps.get(2).is_some(), &caps[3]) {
+            if !rest.is_empty() {
+                return Some((name, rest.to_string()));
+            }
+        }
+    }
+
+    None
+}
+
+fn clean_name(raw: &str) -> Option<String> {
+    let words: Vec<&str> = raw.split_whitespace().collect();
+    if words.is_empty() || words.len() > MAX_PROJECT_WORDS {
+        return None;
+    }
+    Some(words.join(" "))
+}
+
+/// Folder name for a project: lowercase, with anything that is not
+/// alphanumeric, `-` or `_` collapsed into single dashes. Two spellings of the
+/// same name ("Jotline", "jotline") therefore share a folder.
+pub fn project_folder(name: &str) -> Option<String> {
+    let mut folder = String::new();
+    for c in name.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() || c == '_' {
+            folder.push(c);
+        } else if !folder.is_empty() && !folder.ends_with('-') {
+            folder.push('-');
+        }
+    }
+    let folder = folder.trim_end_matches('-').to_string();
+    (!folder.is_empty()).then_some(folder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn known(folders: &[&str]) -> Vec<String> {
+        folders.iter().map(|f| f.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_voice_override() {
+        let known = known(&["jotline", "home-lab", "q3-roadmap"]);
+        assert_eq!(
+            parse_voice_override("Project Jotline. Fix the overlay flicker.", &known),
+            Some((
+                "Jotline".to_string(),
+                "Fix the overlay flicker.".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_voice_override("Call the vendor about invoices, project home lab.", &known),
+            Some((
+                "home lab".to_string(),
+                "Call the vendor about invoices".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_voice_override("Project: q3-roadmap, draft the goals", &known),
+            Some(("q3-roadmap".to_string(), "draft the goals".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_unknown_projects_need_for_project() {
+        let known = known(&["jotline"]);
+        assert_eq!(
+            parse_voice_override("Project update: the launch slipped a week.", &known),
+            None
+        );
+        assert_eq!(
+            parse_voice_override("Ship it on Friday, project garden.", &known),
+            None
+        );
+        assert_eq!(
+            parse_voice_override("For project garden, order the seeds.", &known),
+            Some(("garden".to_string(), "order the seeds.".to_string()))
+        );
+        assert_eq!(
+            parse_voice_override("Order the seeds, for project garden.", &known),
+            Some(("garden".to_string(), "Order the seeds".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_ordinary_sentences_are_not_overrides() {
+        let known = known(&["meeting", "jotline"]);
+        assert_eq!(
+            parse_voice_override("Project meeting went well.", &known),
+            None
+        );
+        assert_eq!(
+            parse_voice_override("Project meeting went well. Next steps are unclear.", &known),
+            None
+        );
+        assert_eq!(
+            parse_voice_override("The project timeline slipped.", &known),
+            None
+        );
+        assert_eq!(parse_voice_override("Project jotline.", &known), None);
+    }
+
+    #[test]
+    fn test_project_folder() {
+        assert_eq!(project_folder("Jotline").as_deref(), Some("jotline"));
+        assert_eq!(project_folder("Home Lab!").as_deref(), Some("home-lab"));
+        assert_eq!(project_folder("../etc").as_deref(), Some("etc"));
+        assert_eq!(project_folder("my_repo").as_deref(), Some("my_repo"));
+        assert_eq!(project_folder("..."), None);
+    }
+}
@@ -1,6 +1,9 @@
 use crate::input;
 use crate::settings;
 use crate::settings::OverlayPosition;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize};
 
 #[cfg(not(target_os = "macos"))]
@@ -33,6 +36,23 @@ tauri_panel! {
 const OVERLAY_WIDTH: f64 = 172.0;
 const OVERLAY_HEIGHT: f64 = 36.0;
 
+/// How long the overlay keeps naming the post-processing provider, saying
+/// the raw text was kept or that the queue is full, before it moves on
+const SOURCE_MIN_DISPLAY: Duration = Duration::from_millis(1500);
+
+/// Orders changes to the overlay. A change that has to wait, for the
+/// post-processing source to be read or the fade-out to finish, is dropped
+/// once a newer change came in.
+#[derive(Default)]
+pub struct OverlayState {
+    /// Counts changes
+    generation: AtomicU64,
+    /// Until when the post-processing source or queue notice stays up. Held
+    /// while a change is applied, so a delayed one cannot slip in after a
+    /// newer one.
+    source_until: Mutex<Option<Instant>>,
+}
+
 #[cfg(target_os = "macos")]
 const OVERLAY_TOP_OFFSET: f64 = 46.0;
 #[cfg(any(target_os = "windows", target_os = "linux"))]
@@ -288,6 +308,51 @@ pub fn create_recording_overlay(app_handle: &AppHandle) {
     }
 }
 
+/// Apply `change` to the overlay once the post-processing source has been
+/// up for [`SOURCE_MIN_DISPLAY`], or at once for a recording.
+fn change_overlay(app_handle: &AppHandle, recording: bool, change: fn(&AppHandle)) {
+    let Some(state) = app_handle.try_state::<OverlayState>() else {
+        change(app_handle);
+        return;
+    };
+    let mut source_until = state.source_until.lock().unwrap();
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    if recording {
+        *source_until = None;
+    }
+    let remaining = source_until.map_or(Duration::ZERO, |until| {
+        until.saturating_duration_since(Instant::now())
+    });
+    if remaining.is_zero() {
+        change(app_handle);
+        return;
+    }
+    drop(source_until);
+
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(remaining);
+        change_if_current(&app_handle, generation, change);
+    });
+}
+
+/// Apply `change` unless the overlay changed again since `generation`.
+fn change_if_current(app_handle: &AppHandle, generation: u64, change: impl FnOnce(&AppHandle)) {
+    let Some(state) = app_handle.try_state::<OverlayState>() else {
+        return;
+    };
+    let _applying = state.source_until.lock().unwrap();
+    if state.generation.load(Ordering::SeqCst) == generation {
+        change(app_handle);
+    }
+}
+
+fn overlay_generation(app_handle: &AppHandle) -> u64 {
+    app_handle
+        .try_state::<OverlayState>()
+        .map_or(0, |state| state.generation.load(Ordering::SeqCst))
+}
+
 fn show_overlay_state(app_handle: &AppHandle, state: &str) {
     // Check if overlay should be shown based on position setting
     let settings = settings::get_settings(app_handle);
@@ -310,17 +375,31 @@ fn show_overlay_state(app_handle: &AppHandle, state: &str) {
 
 /// Shows the recording overlay window with fade-in animation
 pub fn show_recording_overlay(app_handle: &AppHandle) {
-    show_overlay_state(app_handle, "recording");
+    change_overlay(app_handle, true, |app| show_overlay_state(app, "recording"));
 }
 
 /// Shows the transcribing overlay window
 pub fn show_transcribing_overlay(app_handle: &AppHandle) {
-    show_overlay_state(app_handle, "transcribing");
+    change_overlay(app_handle, false, |app| {
+        show_overlay_state(app, "transcribing")
+    });
 }
 
 /// Shows the processing overlay window
 pub fn show_processing_overlay(app_handle: &AppHandle) {
-    show_overlay_state(app_handle, "processing");
+    change_overlay(app_handle, false, |app| {
+        show_overlay_state(app, "processing")
+    });
+}
+
+/// Shows that a recording was refused because the dictation queue is full
+pub fn show_queue_full_overlay(app_handle: &AppHandle) {
+    change_overlay(app_handle, true, |app| {
+        show_overlay_state(app, "queue_full")
+    });
+    if let Some(state) = app_handle.try_state::<OverlayState>() {
+        *state.source_until.lock().unwrap() = Some(Instant::now() + SOURCE_MIN_DISPLAY);
+    }
 }
 
 /// Updates the overlay window position based on current settings
@@ -340,20 +419,55 @@ pub fn update_overlay_position(app_handle: &AppHandle) {
 
 /// Hides the recording overlay window with fade-out animation
 pub fn hide_recording_overlay(app_handle: &AppHandle) {
+    change_overlay(app_handle, false, hide_overlay);
+}
+
+fn hide_overlay(app_handle: &AppHandle) {
     // Always hide the overlay regardless of settings - if setting was changed while recording,
     // we still want to hide it properly
     if let Some(overlay_window) = app_handle.get_webview_window("recording_overlay") {
         // Emit event to trigger fade-out animation
         let _ = overlay_window.emit("hide-overlay", ());
-        // Hide the window after a short delay to allow animation to complete
-        let window_clone = overlay_window.clone();
+        // Hide the window after a short delay to allow animation to complete,
+        // unless it was shown again meanwhile
+        let generation = overlay_generation(app_handle);
+        let app_handle = app_handle.clone();
         std::thread::spawn(move || {
             std::thread::sleep(std::time::Duration::from_millis(300));
-            let _ = window_clone.hide();
+            change_if_current(&app_handle, generation, |_| {
+                let _ = overlay_window.hide();
+            });
         });
     }
 }
 
+/// Sends the live preview of the recording to the overlay
+pub fn emit_partial_transcript(app_handle: &AppHandle, text: &str) {
+    let _ = app_handle.emit("partial-transcript", text);
+
+    if let Some(overlay_window) = app_handle.get_webview_window("recording_overlay") {
+        let _ = overlay_window.emit("partial-transcript", text);
+    }
+}
+
+/// Sends the post-processed text streamed so far to the overlay
+pub fn emit_post_process_preview(app_handle: &AppHandle, text: &str) {
+    if let Some(overlay_window) = app_handle.get_webview_window("recording_overlay") {
+        let _ = overlay_window.emit("post-process-preview", text);
+    }
+}
+
+/// Tells the overlay which provider and model post-processing is using, or
+/// with `None` that they all failed and the raw transcription is kept
+pub fn emit_post_process_source(app_handle: &AppHandle, source: Option<&str>) {
+    if let Some(state) = app_handle.try_state::<OverlayState>() {
+        *state.source_until.lock().unwrap() = Some(Instant::now() + SOURCE_MIN_DISPLAY);
+    }
+    if let Some(overlay_window) = app_handle.get_webview_window("recording_overlay") {
+        let _ = overlay_window.emit("post-process-source", source);
+    }
+}
+
 pub fn emit_levels(app_handle: &AppHandle, levels: &Vec<f32>) {
     // emit levels to main app
     let _ = app_handle.emit("mic-level", levels);
@@ -0,0 +1,82 @@
+//! The command palette, opened by tapping the transcribe hotkey instead of
+//! holding it.
+//!
+//! The coordinator owns when the palette is open; the frontend renders it on
+//! `palette-opened`, hides it on `palette-closed` and reports the picked
+//! entry through `run_palette_entry`.
+
+use crate::managers::notes::NoteManager;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_opener::OpenerExt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum PaletteEntry {
+    /// Record and transcribe, as holding the hotkey does
+    Voice,
+    /// Type a note instead of dictating it
+    Text,
+    /// Record and append to the last or configured note
+    Append,
+    /// Search notes and history
+    Search,
+    /// Save the clipboard text as a note
+    Clipboard,
+    /// Notes related to what is on screen
+    Relevant,
+    /// Open the notes directory
+    Directory,
+}
+
+impl PaletteEntry {
+    /// Every entry, in palette order.
+    pub const ALL: [PaletteEntry; 7] = [
+        PaletteEntry::Voice,
+        PaletteEntry::Text,
+        PaletteEntry::Append,
+        PaletteEntry::Search,
+        PaletteEntry::Clipboard,
+        PaletteEntry::Relevant,
+        PaletteEntry::Directory,
+    ];
+
+    /// The binding whose action records for this entry, if it records.
+    pub fn binding_id(self) -> Option<&'static str> {
+        match self {
+            PaletteEntry::Voice => Some("transcribe"),
+            PaletteEntry::Append => Some("append"),
+            _ => None,
+        }
+    }
+}
+
+/// Carry out an entry that does not record. Text, Search and Relevant are
+/// views the frontend opens itself, so there is nothing to do for them here.
+pub fn run_entry(app: &AppHandle, entry: PaletteEntry) -> Result<()> {
+    match entry {
+        PaletteEntry::Clipboard => {
+            let text = app
+                .clipboard()
+                .read_text()
+                .map_err(|e| anyhow::anyhow!("Failed to read clipboard: {}", e))?;
+            if text.trim().is_empty() {
+                return Err(anyhow::anyhow!("The clipboard has no text"));
+            }
+            app.state::<Arc<NoteManager>>()
+                .save_transcription(&text, None, None)?;
+        }
+        PaletteEntry::Directory => {
+            let notes_dir = app.state::<Arc<NoteManager>>().notes_dir()?;
+            app.opener()
+                .open_path(notes_dir.to_string_lossy(), None::<String>)
+                .map_err(|e| anyhow::anyhow!("Failed to open notes directory: {}", e))?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
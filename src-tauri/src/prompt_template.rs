@@ -0,0 +1,367 @@
+//! Post-processing prompts as templates.
+//!
+//! `${name}` is replaced with one of [`VARIABLES`], and sections can depend
+//! on them:
+//!
+//! ```text
+//! ${if app_name ~ "terminal|iterm"}Write it as a commit message.${else}Fix the punctuation.${end}
+//! ```
+//!
+//! A condition is a variable on its own (true when it is not empty), or a
+//! variable compared with a quoted value: `==` and `!=` compare exactly and
+//! `~` is true when the value contains any of the `|`-separated words,
+//! ignoring case. Sections nest.
+
+use std::collections::HashMap;
+
+/// Every variable a prompt may use
+pub const VARIABLES: &[&str] = &[
+    "output",
+    "date",
+    "time",
+    "language",
+    "clipboard",
+    "selected_text",
+    "app_name",
+    "window_title",
+    "custom_words",
+    "previous_transcription",
+];
+
+/// Values for a prompt's variables; unset ones are empty.
+#[derive(Debug, Default, Clone)]
+pub struct PromptVariables {
+    values: HashMap<&'static str, String>,
+}
+
+impl PromptVariables {
+    pub fn set(&mut self, name: &'static str, value: impl Into<String>) {
+        self.values.insert(name, value.into());
+    }
+
+    fn get(&self, name: &str) -> &str {
+        self.values
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Node {
+    Text(String),
+    Variable(String),
+    Section {
+        condition: Condition,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+enum Condition {
+    NotEmpty(String),
+    Equals(String, String),
+    NotEquals(String, String),
+    ContainsAny(String, Vec<String>),
+}
+
+impl Condition {
+    fn holds(&self, variables: &PromptVariables) -> bool {
+        match self {
+            Condition::NotEmpty(name) => !variables.get(name).trim().is_empty(),
+            Condition::Equals(name, value) => variables.get(name) == value,
+            Condition::NotEquals(name, value) => variables.get(name) != value,
+            Condition::ContainsAny(name, words) => {
+                let haystack = variables.get(name).to_lowercase();
+                words.iter().any(|word| haystack.contains(word.as_str()))
+            }
+        }
+    }
+}
+
+/// Check that `template` parses and uses only known variables, listing
+/// every problem found.
+pub fn validate(template: &str) -> Result<(), String> {
+    parse(template)
+        .map(|_| ())
+        .map_err(|errors| errors.join("; "))
+}
+
+/// Whether `template` refers to `name` anywhere, so that costly or
+/// intrusive values such as the selected text are only read when needed.
+pub fn uses(template: &str, name: &str) -> bool {
+    fn any(nodes: &[Node], name: &str) -> bool {
+        nodes.iter().any(|node| match node {
+            Node::Text(_) => false,
+            Node::Variable(variable) => variable == name,
+            Node::Section {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let variable = match condition {
+                    Condition::NotEmpty(v)
+                    | Condition::Equals(v, _)
+                    | Condition::NotEquals(v, _)
+                    | Condition::ContainsAny(v, _) => v,
+                };
+                variable == name || any(then, name) || any(otherwise, name)
+            }
+        })
+    }
+    parse(template).is_ok_and(|nodes| any(&nodes, name))
+}
+
+/// Fill in `template`. Prompts saved before templating existed may not
+/// validate; they get `${output}` replaced and are otherwise left as they
+/// are, as before.
+pub fn render(template: &str, variables: &PromptVariables) -> String {
+    fn write(nodes: &[Node], variables: &PromptVariables, out: &mut String) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Variable(name) => out.push_str(variables.get(name)),
+                Node::Section {
+                    condition,
+                    then,
+                    otherwise,
+                } => {
+                    let branch = if condition.holds(variables) {
+                        then
+                    } else {
+                        otherwise
+                    };
+                    write(branch, variables, out);
+                }
+            }
+        }
+    }
+
+    match parse(template) {
+        Ok(nodes) => {
+            let mut out = String::with_capacity(template.len());
+            write(&nodes, variables, &mut out);
+            out
+        }
+        Err(_) => template.replace("${output}", variables.get("output")),
+    }
+}
+
+/// A section being filled while parsing
+struct Open {
+    condition: Condition,
+    then: Vec<Node>,
+    /// Set once `${else}` is seen
+    otherwise: Option<Vec<Node>>,
+}
+
+fn parse(template: &str) -> Result<Vec<Node>, Vec<String>> {
+    let mut errors = Vec::new();
+    let mut root = Vec::new();
+    let mut open: Vec<Open> = Vec::new();
+
+    fn current<'a>(root: &'a mut Vec<Node>, open: &'a mut [Open]) -> &'a mut Vec<Node> {
+        match open.last_mut() {
+            Some(Open {
+                otherwise: Some(nodes),
+                ..
+            }) => nodes,
+            Some(section) => &mut section.then,
+            None => root,
+        }
+    }
+
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        if start > 0 {
+            current(&mut root, &mut open).push(Node::Text(rest[..start].to_string()));
+        }
+        let Some(len) = rest[start..].find('}') else {
+            errors.push(format!("Unclosed '${{' in \"{}\"", excerpt(&rest[start..])));
+            rest = "";
+            break;
+        };
+        let tag = rest[start + 2..start + len].trim();
+        rest = &rest[start + len + 1..];
+
+        if let Some(condition) = tag.strip_prefix("if ") {
+            match parse_condition(condition.trim()) {
+                Ok(condition) => open.push(Open {
+                    condition,
+                    then: Vec::new(),
+                    otherwise: None,
+                }),
+                Err(e) => {
+                    errors.push(e);
+                    // Keep the nesting straight so later tags are checked
+                    open.push(Open {
+                        condition: Condition::NotEmpty(String::new()),
+                        then: Vec::new(),
+                        otherwise: None,
+                    });
+                }
+            }
+        } else if tag == "else" {
+            match open.last_mut() {
+                Some(section) if section.otherwise.is_none() => {
+                    section.otherwise = Some(Vec::new())
+                }
+                Some(_) => errors.push("Second ${else} in one section".to_string()),
+                None => errors.push("${else} outside an ${if ...} section".to_string()),
+            }
+        } else if tag == "end" {
+            match open.pop() {
+                Some(section) => current(&mut root, &mut open).push(Node::Section {
+                    condition: section.condition,
+                    then: section.then,
+                    otherwise: section.otherwise.unwrap_or_default(),
+                }),
+                None => errors.push("${end} without an ${if ...}".to_string()),
+            }
+        } else {
+            match check_variable(tag) {
+                Ok(()) => current(&mut root, &mut open).push(Node::Variable(tag.to_string())),
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+    if !rest.is_empty() {
+        current(&mut root, &mut open).push(Node::Text(rest.to_string()));
+    }
+    if !open.is_empty() {
+        errors.push(format!(
+            "{} ${{if ...}} section(s) missing ${{end}}",
+            open.len()
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(root)
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse_condition(condition: &str) -> Result<Condition, String> {
+    // The first operator wins, so quoted values may contain the others
+    let operator = ["==", "!=", "~"]
+        .into_iter()
+        .filter_map(|operator| condition.find(operator).map(|at| (at, operator)))
+        .min();
+    if let Some((at, operator)) = operator {
+        let name = condition[..at].trim();
+        let value = &condition[at + operator.len()..];
+        check_variable(name)?;
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .ok_or_else(|| format!("Expected a quoted value in \"${{if {}}}\"", condition))?
+            .to_string();
+        return Ok(match operator {
+            "==" => Condition::Equals(name.to_string(), value),
+            "!=" => Condition::NotEquals(name.to_string(), value),
+            _ => Condition::ContainsAny(
+                name.to_string(),
+                value
+                    .split('|')
+                    .map(|word| word.trim().to_lowercase())
+                    .filter(|word| !word.is_empty())
+                    .collect(),
+            ),
+        });
+    }
+    check_variable(condition)?;
+    Ok(Condition::NotEmpty(condition.to_string()))
+}
+
+fn check_variable(name: &str) -> Result<(), String> {
+    if VARIABLES.contains(&name) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown variable '${{{}}}'; available: {}",
+            name,
+            VARIABLES.join(", ")
+        ))
+    }
+}
+
+/// The start of `text`, for error messages
+fn excerpt(text: &str) -> String {
+    text.chars().take(20).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(pairs: &[(&'static str, &str)]) -> PromptVariables {
+        let mut variables = PromptVariables::default();
+        for (name, value) in pairs {
+            variables.set(*name, *value);
+        }
+        variables
+    }
+
+    #[test]
+    fn renders_variables_and_sections() {
+        let template = "${if app_name ~ \"Terminal|iTerm\"}Commit message${else}Prose${end} in ${language}: ${output}";
+        let terminal = variables(&[
+            ("app_name", "iTerm2"),
+            ("language", "en"),
+            ("output", "fix bug"),
+        ]);
+        assert_eq!(render(template, &terminal), "Commit message in en: fix bug");
+
+        let editor = variables(&[("app_name", "Zed"), ("language", "de")]);
+        assert_eq!(render(template, &editor), "Prose in de: ");
+    }
+
+    #[test]
+    fn sections_nest_and_compare_exactly() {
+        let template =
+            "${if selected_text}Rewrite: ${selected_text}${if language == \"fr\"} (fr)${end}${end}";
+        assert_eq!(
+            render(
+                template,
+                &variables(&[("selected_text", "hi"), ("language", "fr")])
+            ),
+            "Rewrite: hi (fr)"
+        );
+        assert_eq!(render(template, &variables(&[("selected_text", "  ")])), "");
+    }
+
+    #[test]
+    fn validation_reports_every_problem() {
+        assert!(validate("Clean this: ${output}").is_ok());
+        let error = validate("${weather} ${if mood}x${else}y${else}z").unwrap_err();
+        assert!(error.contains("Unknown variable '${weather}'"));
+        assert!(error.contains("Unknown variable '${mood}'"));
+        assert!(error.contains("Second ${else}"));
+        assert!(error.contains("missing ${end}"));
+        assert!(validate("${if app_name ~ terminal}x${end}")
+            .unwrap_err()
+            .contains("quoted value"));
+        assert!(validate("${end}").is_err());
+        assert!(validate("${output").is_err());
+    }
+
+    #[test]
+    fn finds_the_variables_a_template_uses() {
+        let template = "${if clipboard}Context: ${window_title}${end}";
+        assert!(uses(template, "clipboard"));
+        assert!(uses(template, "window_title"));
+        assert!(!uses(template, "selected_text"));
+    }
+
+    #[test]
+    fn invalid_templates_still_get_the_output() {
+        let template = "Fix the grammar of ${output}; keep ${this} and ${ as written";
+        assert_eq!(
+            render(template, &variables(&[("output", "hello world")])),
+            "Fix the grammar of hello world; keep ${this} and ${ as written"
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Sentence encoder that turns note text into embedding vectors.
+//!
+//! Runs a MiniLM-style ONNX export with mean pooling over the token
+//! embeddings.
+
+use super::tokenizer::WordPieceTokenizer;
+use super::{MODEL_FILE, VOCAB_FILE};
+use anyhow::Result;
+use ort::session::{builder::GraphOptimizationLevel, Session};
+use ort::value::Tensor;
+use std::path::Path;
+
+/// Longer inputs are truncated; MiniLM was trained on sequences of this length
+const MAX_TOKENS: usize = 256;
+
+pub struct SentenceEncoder {
+    session: Session,
+    tokenizer: WordPieceTokenizer,
+}
+
+impl SentenceEncoder {
+    pub fn load(model_dir: &Path) -> Result<Self> {
+        let tokenizer = WordPieceTokenizer::from_vocab_file(&model_dir.join(VOCAB_FILE))?;
+        let session = Session::builder()?
+            .with_optimization_level(GraphOptimizationLevel::Level3)?
+            .with_intra_threads(2)?
+            .commit_from_file(model_dir.join(MODEL_FILE))?;
+
+        Ok(Self { session, tokenizer })
+    }
+
+    pub fn embed(&mut self, text: &str) -> Result<Vec<f32>> {
+        let input_ids: Vec<i64> = self
+            .tokenizer
+            .encode(text, MAX_TOKENS)
+            .into_iter()
+            .map(i64::from)
+            .collect();
+        let len = input_ids.len();
+        let attention_mask = vec![1i64; len];
+        let token_type_ids = vec![0i64; len];
+
+        let outputs = self.session.run(ort::inputs![
+            "input_ids" => Tensor::from_array(([1usize, len], input_ids))?,
+            "attention_mask" => Tensor::from_array(([1usize, len], attention_mask))?,
+            "token_type_ids" => Tensor::from_array(([1usize, len], token_type_ids))?,
+        ])?;
+
+        // last_hidden_state: [1, tokens, dim]
+        let (shape, hidden) = outputs[0].try_extract_tensor::<f32>()?;
+        let dim = shape.last().copied().unwrap_or(0) as usize;
+        if dim == 0 || hidden.len() != len * dim {
+            return Err(anyhow::anyhow!(
+                "Unexpected encoder output shape {:?}",
+                shape.to_vec()
+            ));
+        }
+
+        Ok(mean_pool(hidden, dim))
+    }
+}
+
+/// Average the token vectors of a `[tokens, dim]` row-major matrix.
+/// Every token is attended to, so no mask is needed.
+fn mean_pool(hidden: &[f32], dim: usize) -> Vec<f32> {
+    let tokens = hidden.len() / dim;
+    let mut pooled = vec![0.0f32; dim];
+    for row in hidden.chunks_exact(dim) {
+        for (acc, value) in pooled.iter_mut().zip(row) {
+            *acc += value;
+        }
+    }
+    if tokens > 0 {
+        for value in &mut pooled {
+            *value /= tokens as f32;
+        }
+    }
+    pooled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mean_pool() {
+        let hidden = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        assert_eq!(mean_pool(&hidden, 2), vec![3.0, 4.0]);
+        assert_eq!(mean_pool(&hidden, 3), vec![2.5, 3.5, 4.5]);
+    }
+}
@@ -0,0 +1,19 @@
+// Mock SentenceEncoder — compiled when the `transcription` feature is disabled.
+// Avoids pulling in the ONNX runtime (used in CI).
+
+use anyhow::Result;
+use std::path::Path;
+
+pub struct SentenceEncoder;
+
+impl SentenceEncoder {
+    pub fn load(_model_dir: &Path) -> Result<Self> {
+        Err(anyhow::anyhow!(
+            "Semantic search is not available in this build"
+        ))
+    }
+
+    pub fn embed(&mut self, _text: &str) -> Result<Vec<f32>> {
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,22 @@
+//! Local search over notes: semantic via embeddings, and by keyword.
+
+#[cfg(feature = "transcription")]
+mod encoder;
+#[cfg(not(feature = "transcription"))]
+#[path = "encoder_mock.rs"]
+mod encoder;
+pub mod text_index;
+#[cfg(feature = "transcription")]
+mod tokenizer;
+pub mod vector_index;
+
+pub use encoder::SentenceEncoder;
+pub use text_index::{DocKey, SnippetPart, TextIndex};
+pub use vector_index::VectorIndex;
+
+/// Output dimension of the embedding model
+pub const EMBEDDING_DIM: usize = 384;
+
+/// Files making up an embedding model directory
+pub const MODEL_FILE: &str = "model.onnx";
+pub const VOCAB_FILE: &str = "vocab.txt";
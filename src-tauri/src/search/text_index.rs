@@ -0,0 +1,453 @@
+//! In-memory inverted index for exact and prefix keyword search.
+//!
+//! Complements the vector index: embeddings find notes by meaning, but
+//! ticket ids, names and other rare strings are better matched literally.
+//! Text is split into lowercase word tokens; runs of Chinese, Japanese and
+//! Korean characters, which are not separated by spaces, are indexed as
+//! overlapping character bigrams followed by the final character on its own,
+//! so any substring of a run can be found.
+//!
+//! A query matches a document when every whitespace-separated query word
+//! occurs in it, with the word's tokens at consecutive positions and the last
+//! token matched as a prefix.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+
+/// Characters of context shown around the first match in a snippet
+const SNIPPET_CONTEXT_CHARS: usize = 60;
+const SNIPPET_MAX_CHARS: usize = 160;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DocKey {
+    Note(String),
+    History(i64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub term: String,
+    /// Byte range of the token in the original text
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A piece of a snippet, highlighted when it matched the query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct SnippetPart {
+    pub text: String,
+    pub highlight: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct TextHit {
+    pub key: DocKey,
+    /// Number of places the query matched
+    pub matches: u32,
+    pub snippet: Vec<SnippetPart>,
+}
+
+struct Document {
+    text: String,
+    tokens: Vec<Token>,
+    /// Unix timestamp in milliseconds, newer documents rank first on ties
+    created_at: i64,
+}
+
+#[derive(Default)]
+pub struct TextIndex {
+    docs: HashMap<DocKey, Document>,
+    /// Term to the positions it occurs at in each document
+    postings: BTreeMap<String, HashMap<DocKey, Vec<u32>>>,
+}
+
+impl TextIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn count(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn contains(&self, key: &DocKey) -> bool {
+        self.docs.contains_key(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &DocKey> {
+        self.docs.keys()
+    }
+
+    /// Insert or replace the text of `key`.
+    pub fn upsert(&mut self, key: DocKey, created_at: i64, text: &str) {
+        self.remove(&key);
+        let tokens = tokenize(text);
+        for (position, token) in tokens.iter().enumerate() {
+            self.postings
+                .entry(token.term.clone())
+                .or_default()
+                .entry(key.clone())
+                .or_default()
+                .push(position as u32);
+        }
+        self.docs.insert(
+            key,
+            Document {
+                text: text.to_string(),
+                tokens,
+                created_at,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, key: &DocKey) -> bool {
+        let Some(doc) = self.docs.remove(key) else {
+            return false;
+        };
+        for token in &doc.tokens {
+            if let Some(docs) = self.postings.get_mut(&token.term) {
+                docs.remove(key);
+                if docs.is_empty() {
+                    self.postings.remove(&token.term);
+                }
+            }
+        }
+        true
+    }
+
+    /// Documents matching every word of `query`, most matches first and
+    /// newest first among equals.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<TextHit> {
+        let words: Vec<Vec<Token>> = query
+            .split_whitespace()
+            .map(tokenize)
+            .filter(|tokens| !tokens.is_empty())
+            .collect();
+        if words.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        // Byte ranges of every match, per document
+        let mut matched: Option<HashMap<&DocKey, Vec<(usize, usize)>>> = None;
+        for word in &words {
+            let spans = self.match_word(word);
+            matched = Some(match matched {
+                None => spans,
+                Some(mut previous) => {
+                    previous.retain(|key, _| spans.contains_key(key));
+                    for (key, ranges) in previous.iter_mut() {
+                        ranges.extend_from_slice(&spans[key]);
+                    }
+                    previous
+                }
+            });
+        }
+
+        let mut hits: Vec<(&DocKey, Vec<(usize, usize)>)> =
+            matched.unwrap_or_default().into_iter().collect();
+        hits.sort_by(|a, b| {
+            b.1.len()
+                .cmp(&a.1.len())
+                .then_with(|| self.docs[b.0].created_at.cmp(&self.docs[a.0].created_at))
+                .then_with(|| a.0.cmp(b.0))
+        });
+        hits.truncate(limit);
+
+        hits.into_iter()
+            .map(|(key, mut ranges)| {
+                ranges.sort_unstable();
+                TextHit {
+                    key: key.clone(),
+                    matches: ranges.len() as u32,
+                    snippet: snippet(&self.docs[key].text, &ranges),
+                }
+            })
+            .collect()
+    }
+
+    /// Where the tokens of one query word occur consecutively, as byte
+    /// ranges per document.
+    fn match_word(&self, word: &[Token]) -> HashMap<&DocKey, Vec<(usize, usize)>> {
+        let last = word.len() - 1;
+        let mut spans = HashMap::new();
+        for (key, starts) in self.positions(&word[0].term, last == 0) {
+            let doc = &self.docs[key];
+            for start in starts {
+                let start = start as usize;
+                let Some(tokens) = doc.tokens.get(start..=start + last) else {
+                    continue;
+                };
+                let is_match = word
+                    .iter()
+                    .zip(tokens)
+                    .enumerate()
+                    .all(|(i, (query, token))| {
+                        if i == last {
+                            token.term.starts_with(&query.term)
+                        } else {
+                            token.term == query.term
+                        }
+                    });
+                if !is_match {
+                    continue;
+                }
+                // Highlight only the matched part of a prefix
+                let end_token = &tokens[last];
+                let end = end_token.start + word[last].term.len();
+                let end = if end < end_token.end && doc.text.is_char_boundary(end) {
+                    end
+                } else {
+                    end_token.end
+                };
+                spans
+                    .entry(key)
+                    .or_insert_with(Vec::new)
+                    .push((tokens[0].start, end));
+            }
+        }
+        spans
+    }
+
+    fn positions(&self, term: &str, prefix: bool) -> HashMap<&DocKey, Vec<u32>> {
+        let mut positions: HashMap<&DocKey, Vec<u32>> = HashMap::new();
+        if prefix {
+            for (_, docs) in self
+                .postings
+                .range::<str, _>((Bound::Included(term), Bound::Unbounded))
+                .take_while(|(t, _)| t.starts_with(term))
+            {
+                for (key, p) in docs {
+                    positions.entry(key).or_default().extend_from_slice(p);
+                }
+            }
+        } else if let Some(docs) = self.postings.get(term) {
+            for (key, p) in docs {
+                positions.insert(key, p.clone());
+            }
+        }
+        positions
+    }
+}
+
+/// Split `text` into lowercase word tokens and CJK bigrams.
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word_start = None;
+    let mut cjk_run: Vec<(usize, char)> = Vec::new();
+
+    for (i, c) in text.char_indices() {
+        if is_cjk(c) {
+            if let Some(start) = word_start.take() {
+                push_word(&mut tokens, text, start, i);
+            }
+            cjk_run.push((i, c));
+        } else {
+            push_cjk_run(&mut tokens, &mut cjk_run, i);
+            if c.is_alphanumeric() || is_combining_mark(c) {
+                word_start.get_or_insert(i);
+            } else if let Some(start) = word_start.take() {
+                push_word(&mut tokens, text, start, i);
+            }
+        }
+    }
+    if let Some(start) = word_start {
+        push_word(&mut tokens, text, start, text.len());
+    }
+    push_cjk_run(&mut tokens, &mut cjk_run, text.len());
+    tokens
+}
+
+fn push_word(tokens: &mut Vec<Token>, text: &str, start: usize, end: usize) {
+    tokens.push(Token {
+        term: text[start..end].to_lowercase(),
+        start,
+        end,
+    });
+}
+
+fn push_cjk_run(tokens: &mut Vec<Token>, run: &mut Vec<(usize, char)>, run_end: usize) {
+    for (i, &(start, c)) in run.iter().enumerate() {
+        let end = run.get(i + 1).map_or(run_end, |&(next, _)| next);
+        match run.get(i + 1) {
+            Some(&(_, next)) => tokens.push(Token {
+                term: format!("{}{}", c, next),
+                start,
+                end: end + next.len_utf8(),
+            }),
+            None => tokens.push(Token {
+                term: c.to_string(),
+                start,
+                end,
+            }),
+        }
+    }
+    run.clear();
+}
+
+/// Han, kana and Hangul: the scripts SenseVoice transcribes without spaces
+/// between words.
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // Hiragana, Katakana
+        | 0x31F0..=0x31FF   // Katakana Phonetic Extensions
+        | 0x3400..=0x4DBF   // CJK Extension A
+        | 0x4E00..=0x9FFF   // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF   // Hangul Syllables
+        | 0x1100..=0x11FF   // Hangul Jamo
+        | 0x3130..=0x318F   // Hangul Compatibility Jamo
+        | 0xF900..=0xFAFF   // CJK Compatibility Ideographs
+        | 0xFF66..=0xFF9F   // Halfwidth Katakana
+        | 0x20000..=0x2FA1F // CJK Extensions B-F, Compatibility Supplement
+    )
+}
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(c as u32, 0x0300..=0x036F | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x20D0..=0x20FF)
+}
+
+/// A window of `text` around the first match, split into highlighted and
+/// plain parts. `ranges` must be sorted.
+fn snippet(text: &str, ranges: &[(usize, usize)]) -> Vec<SnippetPart> {
+    let Some(&(first, _)) = ranges.first() else {
+        return Vec::new();
+    };
+    let start = text[..first]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT_CHARS - 1)
+        .map_or(0, |(i, _)| i);
+    let end = text[start..]
+        .char_indices()
+        .nth(SNIPPET_MAX_CHARS)
+        .map_or(text.len(), |(i, _)| start + i);
+
+    let mut parts = Vec::new();
+    let mut push = |from: usize, to: usize, highlight: bool| {
+        if from < to {
+            parts.push(SnippetPart {
+                text: text[from..to].replace(['\n', '\r', '\t'], " "),
+                highlight,
+            });
+        }
+    };
+    let mut cursor = start;
+    for &(from, to) in ranges {
+        let (from, to) = (from.max(cursor), to.min(end));
+        if from >= to {
+            continue;
+        }
+        push(cursor, from, false);
+        push(from, to, true);
+        cursor = to;
+    }
+    push(cursor, end, false);
+
+    if start > 0 {
+        parts.insert(
+            0,
+            SnippetPart {
+                text: "…".to_string(),
+                highlight: false,
+            },
+        );
+    }
+    if end < text.len() {
+        parts.push(SnippetPart {
+            text: "…".to_string(),
+            highlight: false,
+        });
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(text: &str) -> Vec<String> {
+        tokenize(text).into_iter().map(|t| t.term).collect()
+    }
+
+    fn highlighted(hit: &TextHit) -> Vec<&str> {
+        hit.snippet
+            .iter()
+            .filter(|p| p.highlight)
+            .map(|p| p.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_words_and_cjk() {
+        assert_eq!(
+            terms("Ask Zoë about JL-1234!"),
+            vec!["ask", "zoë", "about", "jl", "1234"]
+        );
+        assert_eq!(terms("東京駅で"), vec!["東京", "京駅", "駅で", "で"]);
+        assert_eq!(terms("会议ABC"), vec!["会议", "议", "abc"]);
+        assert_eq!(
+            terms("서울에서 만나요"),
+            vec!["서울", "울에", "에서", "서", "만나", "나요", "요"]
+        );
+
+        let tokens = tokenize("a 東京");
+        assert_eq!(&"a 東京"[tokens[1].start..tokens[1].end], "東京");
+        assert_eq!(&"a 東京"[tokens[2].start..tokens[2].end], "京");
+    }
+
+    #[test]
+    fn test_search_exact_prefix_and_cjk() {
+        let mut index = TextIndex::new();
+        index.upsert(
+            DocKey::Note("a.md".to_string()),
+            1,
+            "Follow up on JL-1234 with Priya before Friday.",
+        );
+        index.upsert(DocKey::History(7), 2, "明天在东京站见面。JL-1235 is done.");
+        index.upsert(DocKey::History(8), 3, "Priya and priya again");
+
+        let hits = index.search("jl-1234", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].key, DocKey::Note("a.md".to_string()));
+        assert_eq!(highlighted(&hits[0]), vec!["JL-1234"]);
+
+        // The last token matches as a prefix
+        assert_eq!(index.search("JL-123", 10).len(), 2);
+        let hits = index.search("pri", 10);
+        assert_eq!(hits[0].key, DocKey::History(8));
+        assert_eq!(hits[0].matches, 2);
+        assert_eq!(highlighted(&hits[0]), vec!["Pri", "pri"]);
+
+        // Every word must match
+        assert!(index.search("priya tokyo", 10).is_empty());
+
+        let hits = index.search("东京", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(highlighted(&hits[0]), vec!["东京"]);
+        assert_eq!(index.search("站", 10).len(), 1);
+        assert!(index.search("京东", 10).is_empty());
+
+        assert!(index.remove(&DocKey::History(7)));
+        assert!(index.search("东京", 10).is_empty());
+        assert!(!index.postings.contains_key("东京"));
+        index.upsert(DocKey::Note("a.md".to_string()), 1, "Nothing left");
+        assert!(index.search("jl", 10).is_empty());
+    }
+
+    #[test]
+    fn test_snippet_is_cut_around_first_match() {
+        let text = format!("{} needle {}", "x ".repeat(100), "y ".repeat(100));
+        let mut index = TextIndex::new();
+        index.upsert(DocKey::History(1), 1, &text);
+        let snippet = &index.search("needle", 1)[0].snippet;
+        assert_eq!(snippet.first().unwrap().text, "…");
+        assert_eq!(snippet.last().unwrap().text, "…");
+        let len: usize = snippet[1..snippet.len() - 1]
+            .iter()
+            .map(|p| p.text.chars().count())
+            .sum();
+        assert_eq!(len, SNIPPET_MAX_CHARS);
+        assert_eq!(snippet[1].text.chars().count(), SNIPPET_CONTEXT_CHARS);
+    }
+}
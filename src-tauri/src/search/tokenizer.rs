@@ -0,0 +1,216 @@
+//! BERT-style WordPiece tokenizer for the sentence encoder.
+//!
+//! Implements the uncased pipeline used by MiniLM-family models: lowercase,
+//! strip accents, split on whitespace and punctuation, give every CJK
+//! character its own token, then greedily match the longest vocabulary
+//! pieces with `##` continuations.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const CLS: &str = "[CLS]";
+const SEP: &str = "[SEP]";
+const UNK: &str = "[UNK]";
+
+/// Words longer than this are mapped to `[UNK]` instead of being split
+const MAX_WORD_CHARS: usize = 100;
+
+pub struct WordPieceTokenizer {
+    vocab: HashMap<String, u32>,
+    cls_id: u32,
+    sep_id: u32,
+    unk_id: u32,
+}
+
+impl WordPieceTokenizer {
+    /// Load a `vocab.txt` with one token per line; the line number is the id.
+    pub fn from_vocab_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read vocabulary {:?}: {}", path, e))?;
+        Self::from_tokens(content.lines())
+    }
+
+    pub fn from_tokens<'a>(tokens: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let vocab: HashMap<String, u32> = tokens
+            .into_iter()
+            .enumerate()
+            .map(|(id, token)| (token.trim_end_matches('\r').to_string(), id as u32))
+            .collect();
+
+        let lookup = |token: &str| {
+            vocab
+                .get(token)
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("Vocabulary is missing {}", token))
+        };
+        let cls_id = lookup(CLS)?;
+        let sep_id = lookup(SEP)?;
+        let unk_id = lookup(UNK)?;
+
+        Ok(Self {
+            vocab,
+            cls_id,
+            sep_id,
+            unk_id,
+        })
+    }
+
+    /// Encode `text` as `[CLS] ... [SEP]`, truncated to at most `max_len` ids.
+    pub fn encode(&self, text: &str, max_len: usize) -> Vec<u32> {
+        let budget = max_len.saturating_sub(2);
+        let mut ids = Vec::with_capacity(budget.min(128) + 2);
+        ids.push(self.cls_id);
+
+        'words: for word in basic_tokenize(text) {
+            for id in self.word_pieces(&word) {
+                if ids.len() > budget {
+                    break 'words;
+                }
+                ids.push(id);
+            }
+        }
+
+        ids.push(self.sep_id);
+        ids
+    }
+
+    fn word_pieces(&self, word: &str) -> Vec<u32> {
+        let chars: Vec<char> = word.chars().collect();
+        if chars.len() > MAX_WORD_CHARS {
+            return vec![self.unk_id];
+        }
+
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        while start < chars.len() {
+            let mut end = chars.len();
+            let mut matched = None;
+            while start < end {
+                let piece: String = chars[start..end].iter().collect();
+                let candidate = if start > 0 {
+                    format!("##{}", piece)
+                } else {
+                    piece
+                };
+                if let Some(&id) = self.vocab.get(&candidate) {
+                    matched = Some(id);
+                    break;
+                }
+                end -= 1;
+            }
+            match matched {
+                Some(id) => pieces.push(id),
+                // One unknown piece makes the whole word unknown, as in BERT
+                None => return vec![self.unk_id],
+            }
+            start = end;
+        }
+        pieces
+    }
+}
+
+/// Lowercase, strip accents and split into words and punctuation.
+fn basic_tokenize(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if is_combining_mark(c) || c.is_control() {
+            continue;
+        }
+        if c.is_whitespace() {
+            flush(&mut current, &mut words);
+        } else if is_punctuation(c) || is_cjk(c) {
+            flush(&mut current, &mut words);
+            words.push(c.to_string());
+        } else {
+            current.push(strip_accent(c));
+        }
+    }
+    flush(&mut current, &mut words);
+    words
+}
+
+fn flush(current: &mut String, words: &mut Vec<String>) {
+    if !current.is_empty() {
+        words.push(std::mem::take(current));
+    }
+}
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(c as u32, 0x2000..=0x206F | 0x3000..=0x303F | 0xFF01..=0xFF0F | 0xFF1A..=0xFF20)
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x4E00..=0x9FFF
+        | 0x3400..=0x4DBF
+        | 0x20000..=0x2A6DF
+        | 0x2A700..=0x2B81F
+        | 0xF900..=0xFAFF
+        | 0x2F800..=0x2FA1F)
+}
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(c as u32, 0x0300..=0x036F)
+}
+
+/// Map common precomposed Latin letters to their base letter. The vocabulary
+/// is uncased and accent-free, so "café" must become "cafe".
+fn strip_accent(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'č' => 'c',
+        'ď' => 'd',
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => 'e',
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' => 'i',
+        'ñ' | 'ń' | 'ň' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ō' | 'ő' => 'o',
+        'ř' => 'r',
+        'ś' | 'š' | 'ş' => 's',
+        'ť' | 'ţ' => 't',
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' | 'ų' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokenizer() -> WordPieceTokenizer {
+        WordPieceTokenizer::from_tokens([
+            "[PAD]", "[UNK]", "[CLS]", "[SEP]", "call", "the", "dent", "##ist", ",", "cafe", "中",
+            "文",
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_encode_splits_words_and_punctuation() {
+        let tok = tokenizer();
+        assert_eq!(
+            tok.encode("Call the DENTIST, café", 32),
+            vec![2, 4, 5, 6, 7, 8, 9, 3]
+        );
+        assert_eq!(tok.encode("中文", 32), vec![2, 10, 11, 3]);
+        assert_eq!(tok.encode("dentistry", 32), vec![2, 1, 3]);
+    }
+
+    #[test]
+    fn test_encode_truncates() {
+        let tok = tokenizer();
+        assert_eq!(tok.encode("call the dentist", 4), vec![2, 4, 5, 3]);
+        assert_eq!(tok.encode("", 4), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_vocab_requires_special_tokens() {
+        assert!(WordPieceTokenizer::from_tokens(["[CLS]", "[SEP]"]).is_err());
+    }
+}
@@ -0,0 +1,399 @@
+//! Persistent vector index of note embeddings.
+//!
+//! Stored as a small binary file: a header naming the embedding model and
+//! dimension, followed by one record per note. An index built by a different
+//! model is discarded on load so vectors from different spaces never mix.
+//!
+//! Each record also keeps the notes its "Related" section links to, so the
+//! notes linking to a moved or deleted one are found without reading the
+//! whole folder.
+
+use anyhow::Result;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"JLVI";
+const VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexEntry {
+    pub id: String,
+    /// Modification time of the note when it was embedded, in milliseconds
+    pub modified_at: i64,
+    pub vector: Vec<f32>,
+    /// Ids of the notes this one links to
+    pub links: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorIndex {
+    model_id: String,
+    dim: usize,
+    entries: Vec<IndexEntry>,
+    /// Note id to the ids of the entries linking to it, derived from `links`
+    backlinks: HashMap<String, BTreeSet<String>>,
+}
+
+impl VectorIndex {
+    pub fn new(model_id: &str, dim: usize) -> Self {
+        Self {
+            model_id: model_id.to_string(),
+            dim,
+            entries: Vec::new(),
+            backlinks: HashMap::new(),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&IndexEntry> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    /// Insert or replace the vector for `id`, keeping its links. Vectors are
+    /// L2-normalized so similarity is a plain dot product.
+    pub fn upsert(&mut self, id: &str, modified_at: i64, vector: Vec<f32>) -> Result<()> {
+        if vector.len() != self.dim {
+            return Err(anyhow::anyhow!(
+                "Embedding has {} dimensions, index expects {}",
+                vector.len(),
+                self.dim
+            ));
+        }
+        let vector = normalize(vector);
+        match self.entries.iter_mut().find(|e| e.id == id) {
+            Some(existing) => {
+                existing.modified_at = modified_at;
+                existing.vector = vector;
+            }
+            None => self.entries.push(IndexEntry {
+                id: id.to_string(),
+                modified_at,
+                vector,
+                links: Vec::new(),
+            }),
+        }
+        Ok(())
+    }
+
+    /// Replace the notes `id` links to. Returns whether it is indexed.
+    pub fn set_links(&mut self, id: &str, links: Vec<String>) -> bool {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.id == id) else {
+            return false;
+        };
+        let previous = std::mem::replace(&mut entry.links, links);
+        unlink(&mut self.backlinks, id, &previous);
+        for target in &entry.links {
+            self.backlinks
+                .entry(target.clone())
+                .or_default()
+                .insert(id.to_string());
+        }
+        true
+    }
+
+    /// Ids of the indexed notes linking to `id`.
+    pub fn linking_to(&self, id: &str) -> Vec<String> {
+        self.backlinks
+            .get(id)
+            .map(|sources| sources.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Record that `id` was rewritten at `modified_at` without its vector
+    /// changing. Returns whether it is indexed.
+    pub fn touch(&mut self, id: &str, modified_at: i64) -> bool {
+        match self.entries.iter_mut().find(|e| e.id == id) {
+            Some(entry) => {
+                entry.modified_at = modified_at;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove(&mut self, id: &str) -> bool {
+        let Some(pos) = self.entries.iter().position(|e| e.id == id) else {
+            return false;
+        };
+        let entry = self.entries.remove(pos);
+        unlink(&mut self.backlinks, id, &entry.links);
+        true
+    }
+
+    /// Drop every entry whose id is rejected by `keep`.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.entries.retain(|e| keep(&e.id));
+        self.backlinks = backlinks(&self.entries);
+    }
+
+    /// Cosine similarity between two indexed entries.
+    pub fn similarity(&self, a: &str, b: &str) -> Option<f32> {
+        Some(dot(&self.get(a)?.vector, &self.get(b)?.vector))
+    }
+
+    /// The `k` entries most similar to `query`, best first.
+    pub fn top_k(&self, query: &[f32], k: usize) -> Vec<(String, f32)> {
+        if query.len() != self.dim || k == 0 {
+            return Vec::new();
+        }
+        let query = normalize(query.to_vec());
+        let mut scored: Vec<(String, f32)> = self
+            .entries
+            .iter()
+            .map(|e| (e.id.clone(), dot(&query, &e.vector)))
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(k);
+        scored
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut buf = Vec::with_capacity(16 + self.entries.len() * (self.dim * 4 + 48));
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&VERSION.to_le_bytes());
+        write_str(&mut buf, &self.model_id);
+        buf.extend_from_slice(&(self.dim as u32).to_le_bytes());
+        buf.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for entry in &self.entries {
+            write_str(&mut buf, &entry.id);
+            buf.extend_from_slice(&entry.modified_at.to_le_bytes());
+            for value in &entry.vector {
+                buf.extend_from_slice(&value.to_le_bytes());
+            }
+            buf.extend_from_slice(&(entry.links.len() as u32).to_le_bytes());
+            for link in &entry.links {
+                write_str(&mut buf, link);
+            }
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(&buf)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Load the index at `path`. A missing file, or one written by an older
+    /// version or for another model or dimension, yields an empty index that
+    /// will be rebuilt.
+    pub fn load(path: &Path, model_id: &str, dim: usize) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new(model_id, dim));
+        }
+
+        let mut data = Vec::new();
+        fs::File::open(path)?.read_to_end(&mut data)?;
+        let mut reader = Reader {
+            data: &data,
+            pos: 0,
+        };
+
+        if reader.take(4)? != MAGIC {
+            return Err(anyhow::anyhow!("Not a vector index: {:?}", path));
+        }
+        let version = reader.u32()?;
+        if version < VERSION {
+            return Ok(Self::new(model_id, dim));
+        }
+        if version != VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported vector index version {}",
+                version
+            ));
+        }
+        let stored_model = reader.string()?;
+        let stored_dim = reader.u32()? as usize;
+        if stored_model != model_id || stored_dim != dim {
+            return Ok(Self::new(model_id, dim));
+        }
+
+        let count = reader.u32()? as usize;
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            let id = reader.string()?;
+            let modified_at = i64::from_le_bytes(reader.take(8)?.try_into()?);
+            let vector = reader
+                .take(dim * 4)?
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            let links = (0..reader.u32()?)
+                .map(|_| reader.string())
+                .collect::<Result<_>>()?;
+            entries.push(IndexEntry {
+                id,
+                modified_at,
+                vector,
+                links,
+            });
+        }
+
+        Ok(Self {
+            model_id: model_id.to_string(),
+            dim,
+            backlinks: backlinks(&entries),
+            entries,
+        })
+    }
+}
+
+fn backlinks(entries: &[IndexEntry]) -> HashMap<String, BTreeSet<String>> {
+    let mut backlinks: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for entry in entries {
+        for target in &entry.links {
+            backlinks
+                .entry(target.clone())
+                .or_default()
+                .insert(entry.id.clone());
+        }
+    }
+    backlinks
+}
+
+/// Forget that `source` links to `targets`.
+fn unlink(backlinks: &mut HashMap<String, BTreeSet<String>>, source: &str, targets: &[String]) {
+    for target in targets {
+        if let Some(sources) = backlinks.get_mut(target) {
+            sources.remove(source);
+            if sources.is_empty() {
+                backlinks.remove(target);
+            }
+        }
+    }
+}
+
+fn write_str(buf: &mut Vec<u8>, s: &str) {
+    buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    buf.extend_from_slice(s.as_bytes());
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| anyhow::anyhow!("Vector index is truncated"))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+        Ok(String::from_utf8(self.take(len)?.to_vec())?)
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = dot(&vector, &vector).sqrt();
+    if norm > f32::EPSILON {
+        for value in &mut vector {
+            *value /= norm;
+        }
+    }
+    vector
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_top_k_ranks_by_cosine_similarity() {
+        let mut index = VectorIndex::new("test", 3);
+        index.upsert("a.md", 1, vec![1.0, 0.0, 0.0]).unwrap();
+        index.upsert("b.md", 1, vec![0.0, 2.0, 0.0]).unwrap();
+        index.upsert("c.md", 1, vec![1.0, 1.0, 0.0]).unwrap();
+
+        let hits = index.top_k(&[0.0, 1.0, 0.0], 2);
+        let ids: Vec<_> = hits.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["b.md", "c.md"]);
+        assert!((hits[0].1 - 1.0).abs() < 1e-6);
+
+        assert!((index.similarity("a.md", "c.md").unwrap() - 0.5f32.sqrt()).abs() < 1e-6);
+        assert_eq!(index.similarity("a.md", "missing.md"), None);
+
+        assert!(index.upsert("d.md", 1, vec![1.0]).is_err());
+        assert!(index.touch("a.md", 7));
+        assert_eq!(index.get("a.md").unwrap().modified_at, 7);
+        assert!(!index.touch("d.md", 7));
+        assert!(index.remove("b.md"));
+        assert_eq!(index.top_k(&[0.0, 1.0, 0.0], 1)[0].0, "c.md");
+    }
+
+    #[test]
+    fn test_linking_to_follows_links() {
+        let mut index = VectorIndex::new("test", 2);
+        for id in ["a.md", "b.md", "c.md"] {
+            index.upsert(id, 1, vec![1.0, 0.0]).unwrap();
+        }
+        assert!(index.set_links("a.md", vec!["c.md".to_string()]));
+        assert!(index.set_links("b.md", vec!["a.md".to_string(), "c.md".to_string()]));
+        assert!(!index.set_links("missing.md", vec!["c.md".to_string()]));
+        assert_eq!(index.linking_to("c.md"), vec!["a.md", "b.md"]);
+
+        // Re-embedding keeps the links, replacing them drops the old ones
+        index.upsert("a.md", 2, vec![0.0, 1.0]).unwrap();
+        assert_eq!(index.get("a.md").unwrap().links, vec!["c.md"]);
+        index.set_links("b.md", vec!["a.md".to_string()]);
+        assert_eq!(index.linking_to("c.md"), vec!["a.md"]);
+
+        index.remove("a.md");
+        assert!(index.linking_to("c.md").is_empty());
+        index.retain(|id| id != "b.md");
+        assert!(index.linking_to("a.md").is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("search/embeddings.bin");
+
+        let mut index = VectorIndex::new("minilm", 2);
+        index.upsert("notes/ä.md", 42, vec![3.0, 4.0]).unwrap();
+        index.upsert("b.md", 43, vec![1.0, 0.0]).unwrap();
+        index.set_links("b.md", vec!["notes/ä.md".to_string()]);
+        index.save(&path).unwrap();
+
+        let loaded = VectorIndex::load(&path, "minilm", 2).unwrap();
+        assert_eq!(loaded, index);
+        assert_eq!(loaded.get("notes/ä.md").unwrap().vector, vec![0.6, 0.8]);
+        assert_eq!(loaded.linking_to("notes/ä.md"), vec!["b.md"]);
+
+        // Another model's vectors are not comparable, so they are dropped
+        let other = VectorIndex::load(&path, "other-model", 2).unwrap();
+        assert_eq!(other.count(), 0);
+
+        // Older versions did not record links, so they are rebuilt
+        fs::write(&path, b"JLVI\x01\x00\x00\x00").unwrap();
+        assert_eq!(VectorIndex::load(&path, "minilm", 2).unwrap().count(), 0);
+
+        fs::write(&path, b"JLVI\x02\x00").unwrap();
+        assert!(VectorIndex::load(&path, "minilm", 2).is_err());
+    }
+}
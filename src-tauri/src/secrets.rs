@@ -0,0 +1,463 @@
+//! Secrets such as post-processing API keys, kept out of
+//! `settings_store.json`. They go to the OS credential store (Keychain,
+//! Credential Manager or the Secret Service) when there is one. Otherwise
+//! they go to `secrets.bin` in the app data directory, encrypted with a key
+//! derived from a passphrase the user gives, through the
+//! `JOTLINE_SECRETS_PASSPHRASE` environment variable or
+//! [`unlock`]. The key is never written anywhere. The settings only hold a
+//! reference.
+
+use anyhow::{anyhow, Context, Result};
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use log::{debug, error, info, warn};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Service name the credential store files the secrets under
+const SERVICE: &str = "com.jotline.app";
+/// Prefix of the settings values that stand for a stored secret
+const REFERENCE_PREFIX: &str = "secret:";
+const SECRETS_FILE: &str = "secrets.bin";
+/// Environment variable holding the passphrase of `secrets.bin`
+pub const PASSPHRASE_VAR: &str = "JOTLINE_SECRETS_PASSPHRASE";
+/// Start of `secrets.bin`, naming its format
+const MAGIC: &[u8; 4] = b"JLS1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+static STORE: OnceLock<SecretStore> = OnceLock::new();
+
+/// Name of the secret holding a post-processing provider's API key
+pub fn api_key_name(provider_id: &str) -> String {
+    format!("post-process-api-key.{}", provider_id)
+}
+
+/// Pick the store for this session. The data directory holds the encrypted
+/// file if there is no credential store; it is unlocked straight away when
+/// the passphrase is in the environment. Later calls do nothing.
+pub fn init(data_dir: &Path) {
+    STORE.get_or_init(|| {
+        let store = SecretStore::open(data_dir);
+        if let Backend::File(file) = &store.backend {
+            match std::env::var(PASSPHRASE_VAR) {
+                Ok(passphrase) if !passphrase.is_empty() => {
+                    if let Err(e) = file.unlock(&passphrase) {
+                        error!("Failed to unlock the secret file: {}", e);
+                    }
+                }
+                _ => warn!(
+                    "The secret file stays locked until a passphrase is given ({})",
+                    PASSPHRASE_VAR
+                ),
+            }
+        }
+        store
+    });
+}
+
+fn store() -> Result<&'static SecretStore> {
+    STORE
+        .get()
+        .ok_or_else(|| anyhow!("Secret store not initialised"))
+}
+
+/// Unlock the encrypted file with `passphrase`, which becomes its
+/// passphrase if there is no file yet. With a credential store there is
+/// nothing to unlock.
+pub fn unlock(passphrase: &str) -> Result<()> {
+    match &store()?.backend {
+        Backend::Keyring => Ok(()),
+        Backend::File(file) => file.unlock(passphrase),
+    }
+}
+
+/// Store `value` as the secret `name` and return the reference to keep in
+/// the settings. An empty value deletes the secret and returns "".
+pub fn save(name: &str, value: &str) -> Result<String> {
+    store()?.save(name, value)
+}
+
+/// The secret a settings value refers to. Values that are not references,
+/// plaintext keys not migrated yet, are returned as they are; a secret that
+/// cannot be read comes back empty.
+pub fn resolve(value: &str) -> String {
+    let Some(name) = value.strip_prefix(REFERENCE_PREFIX) else {
+        return value.to_string();
+    };
+    match store().and_then(|store| store.get(name)) {
+        Ok(Some(secret)) => secret,
+        Ok(None) => {
+            warn!("Secret '{}' is referenced but not stored", name);
+            String::new()
+        }
+        Err(e) => {
+            error!("Failed to read secret '{}': {}", name, e);
+            String::new()
+        }
+    }
+}
+
+/// Move plaintext API keys into the store, leaving references in their
+/// place. Returns whether any changed; keys that cannot be stored, for
+/// instance while the file is locked, stay as they are and are tried again
+/// next time.
+pub fn migrate_api_keys(api_keys: &mut HashMap<String, String>) -> bool {
+    match store() {
+        Ok(store) => store.migrate_api_keys(api_keys),
+        Err(_) => false,
+    }
+}
+
+struct SecretStore {
+    backend: Backend,
+}
+
+enum Backend {
+    /// The OS credential store
+    Keyring,
+    /// Fallback where there is none
+    File(EncryptedFile),
+}
+
+impl SecretStore {
+    fn open(data_dir: &Path) -> Self {
+        let backend = match keyring_available() {
+            Ok(()) => {
+                debug!("Keeping secrets in the OS credential store");
+                Backend::Keyring
+            }
+            Err(e) => {
+                warn!(
+                    "No OS credential store ({}); keeping secrets in a passphrase-encrypted file",
+                    e
+                );
+                Backend::File(EncryptedFile::new(data_dir))
+            }
+        };
+        Self { backend }
+    }
+
+    fn save(&self, name: &str, value: &str) -> Result<String> {
+        if value.is_empty() {
+            self.delete(name)?;
+            return Ok(String::new());
+        }
+        match &self.backend {
+            Backend::Keyring => keyring::Entry::new(SERVICE, name)?.set_password(value)?,
+            Backend::File(file) => file.update(|secrets| {
+                secrets.insert(name.to_string(), value.to_string());
+            })?,
+        }
+        Ok(format!("{}{}", REFERENCE_PREFIX, name))
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        match &self.backend {
+            Backend::Keyring => match keyring::Entry::new(SERVICE, name)?.get_password() {
+                Ok(secret) => Ok(Some(secret)),
+                Err(keyring::Error::NoEntry) => Ok(None),
+                Err(e) => Err(e.into()),
+            },
+            Backend::File(file) => Ok(file.read()?.remove(name)),
+        }
+    }
+
+    fn delete(&self, name: &str) -> Result<()> {
+        match &self.backend {
+            Backend::Keyring => match keyring::Entry::new(SERVICE, name)?.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                Err(e) => Err(e.into()),
+            },
+            Backend::File(file) => file.update(|secrets| {
+                secrets.remove(name);
+            }),
+        }
+    }
+
+    fn migrate_api_keys(&self, api_keys: &mut HashMap<String, String>) -> bool {
+        let mut changed = false;
+        for (provider_id, value) in api_keys.iter_mut() {
+            if value.is_empty() || value.starts_with(REFERENCE_PREFIX) {
+                continue;
+            }
+            match self.save(&api_key_name(provider_id), value) {
+                Ok(reference) => {
+                    info!(
+                        "Moved the API key for '{}' to the secret store",
+                        provider_id
+                    );
+                    *value = reference;
+                    changed = true;
+                }
+                Err(e) => error!(
+                    "Failed to move the API key for '{}' to the secret store: {}",
+                    provider_id, e
+                ),
+            }
+        }
+        changed
+    }
+}
+
+/// Whether the credential store answers. A missing entry is a fine answer;
+/// no Secret Service on the session bus is not.
+fn keyring_available() -> Result<(), keyring::Error> {
+    match keyring::Entry::new(SERVICE, "availability-check")?.get_password() {
+        Ok(_) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Key derived from the passphrase, and the salt it was derived with
+struct UnlockedKey {
+    salt: [u8; SALT_LEN],
+    cipher: ChaCha20Poly1305,
+}
+
+/// Secrets as an encrypted JSON map: [`MAGIC`], the Argon2id salt, a random
+/// nonce and the ChaCha20-Poly1305 ciphertext.
+struct EncryptedFile {
+    path: PathBuf,
+    /// Set once unlocked; also serialises read-modify-write cycles
+    key: Mutex<Option<UnlockedKey>>,
+}
+
+impl EncryptedFile {
+    fn new(dir: &Path) -> Self {
+        Self {
+            path: dir.join(SECRETS_FILE),
+            key: Mutex::new(None),
+        }
+    }
+
+    /// Derive the key from `passphrase`, checking it against the file when
+    /// there is one.
+    fn unlock(&self, passphrase: &str) -> Result<()> {
+        if passphrase.is_empty() {
+            return Err(anyhow!("The passphrase is empty"));
+        }
+        let mut key = self
+            .key
+            .lock()
+            .map_err(|_| anyhow!("Secret file lock poisoned"))?;
+        let unlocked = match self.read_raw()? {
+            Some(data) => {
+                let (salt, nonce, ciphertext) = split(&data)?;
+                let unlocked = UnlockedKey {
+                    salt,
+                    cipher: derive_cipher(passphrase, &salt)?,
+                };
+                unlocked
+                    .cipher
+                    .decrypt(&Nonce::from(nonce), ciphertext)
+                    .map_err(|_| anyhow!("Wrong passphrase for the secret file"))?;
+                unlocked
+            }
+            None => {
+                let mut salt = [0u8; SALT_LEN];
+                OsRng.fill_bytes(&mut salt);
+                UnlockedKey {
+                    salt,
+                    cipher: derive_cipher(passphrase, &salt)?,
+                }
+            }
+        };
+        *key = Some(unlocked);
+        info!("Unlocked the secret file");
+        Ok(())
+    }
+
+    fn read(&self) -> Result<HashMap<String, String>> {
+        let key = self
+            .key
+            .lock()
+            .map_err(|_| anyhow!("Secret file lock poisoned"))?;
+        self.read_with(unlocked(&key)?)
+    }
+
+    fn update(&self, change: impl FnOnce(&mut HashMap<String, String>)) -> Result<()> {
+        let key = self
+            .key
+            .lock()
+            .map_err(|_| anyhow!("Secret file lock poisoned"))?;
+        let key = unlocked(&key)?;
+        let mut secrets = self.read_with(key)?;
+        change(&mut secrets);
+        self.write_with(key, &secrets)
+    }
+
+    fn read_raw(&self) -> Result<Option<Vec<u8>>> {
+        match fs::read(&self.path) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).context("Failed to read the secret file"),
+        }
+    }
+
+    fn read_with(&self, key: &UnlockedKey) -> Result<HashMap<String, String>> {
+        let Some(data) = self.read_raw()? else {
+            return Ok(HashMap::new());
+        };
+        let (_, nonce, ciphertext) = split(&data)?;
+        let plaintext = key
+            .cipher
+            .decrypt(&Nonce::from(nonce), ciphertext)
+            .map_err(|_| anyhow!("Secret file cannot be decrypted with this passphrase"))?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    fn write_with(&self, key: &UnlockedKey, secrets: &HashMap<String, String>) -> Result<()> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = key
+            .cipher
+            .encrypt(&nonce, serde_json::to_vec(secrets)?.as_slice())
+            .map_err(|_| anyhow!("Failed to encrypt secrets"))?;
+        let mut data = MAGIC.to_vec();
+        data.extend_from_slice(&key.salt);
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+
+        // Replace the file in one step so a crash cannot leave half of it
+        let tmp_path = self.path.with_extension("bin.tmp");
+        write_private(&tmp_path, &data)?;
+        fs::rename(&tmp_path, &self.path).context("Failed to replace the secret file")?;
+        Ok(())
+    }
+}
+
+fn unlocked(key: &Option<UnlockedKey>) -> Result<&UnlockedKey> {
+    key.as_ref().ok_or_else(|| {
+        anyhow!(
+            "No OS credential store, and the encrypted secret file is locked; \
+             set {} or enter its passphrase",
+            PASSPHRASE_VAR
+        )
+    })
+}
+
+/// The salt, nonce and ciphertext of the file's contents
+fn split(data: &[u8]) -> Result<([u8; SALT_LEN], [u8; NONCE_LEN], &[u8])> {
+    let rest = data
+        .strip_prefix(MAGIC.as_slice())
+        .ok_or_else(|| anyhow!("Secret file has an unknown format"))?;
+    if rest.len() < SALT_LEN + NONCE_LEN {
+        return Err(anyhow!("Secret file is truncated"));
+    }
+    let (salt, rest) = rest.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    Ok((salt.try_into()?, nonce.try_into()?, ciphertext))
+}
+
+/// Stretch `passphrase` into a cipher key with Argon2id, so guessing it
+/// from a stolen file is slow.
+fn derive_cipher(passphrase: &str, salt: &[u8]) -> Result<ChaCha20Poly1305> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Failed to derive the secret key: {}", e))?;
+    Ok(ChaCha20Poly1305::new(&Key::from(key)))
+}
+
+/// Write a file only the user can read
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn file_store(dir: &Path) -> SecretStore {
+        SecretStore {
+            backend: Backend::File(EncryptedFile::new(dir)),
+        }
+    }
+
+    fn unlock_file(store: &SecretStore, passphrase: &str) -> Result<()> {
+        match &store.backend {
+            Backend::File(file) => file.unlock(passphrase),
+            Backend::Keyring => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn without_a_keyring_keys_need_the_passphrase() {
+        let dir = TempDir::new().unwrap();
+        let store = file_store(dir.path());
+        let mut api_keys = HashMap::from([("groq".to_string(), "gsk_secret".to_string())]);
+
+        // Locked: nothing is stored and the plaintext key is kept for later
+        assert!(store
+            .save("post-process-api-key.groq", "gsk_secret")
+            .is_err());
+        assert!(!store.migrate_api_keys(&mut api_keys));
+        assert_eq!(api_keys["groq"], "gsk_secret");
+
+        unlock_file(&store, "correct horse").unwrap();
+        assert!(store.migrate_api_keys(&mut api_keys));
+        assert_eq!(api_keys["groq"], "secret:post-process-api-key.groq");
+        assert_eq!(
+            store.get("post-process-api-key.groq").unwrap().as_deref(),
+            Some("gsk_secret")
+        );
+
+        let path = dir.path().join(SECRETS_FILE);
+        let raw = fs::read(&path).unwrap();
+        assert!(!raw.windows(10).any(|w| w == b"gsk_secret"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // A new session needs the same passphrase again
+        let reopened = file_store(dir.path());
+        assert!(unlock_file(&reopened, "wrong horse").is_err());
+        assert!(reopened.get("post-process-api-key.groq").is_err());
+        unlock_file(&reopened, "correct horse").unwrap();
+        assert_eq!(
+            reopened
+                .get("post-process-api-key.groq")
+                .unwrap()
+                .as_deref(),
+            Some("gsk_secret")
+        );
+
+        assert_eq!(reopened.save("post-process-api-key.groq", "").unwrap(), "");
+        assert_eq!(reopened.get("post-process-api-key.groq").unwrap(), None);
+    }
+
+    #[test]
+    fn resolve_passes_plaintext_through() {
+        assert_eq!(resolve(""), "");
+        assert_eq!(resolve("sk-not-migrated"), "sk-not-migrated");
+    }
+
+    #[test]
+    fn references_name_the_provider() {
+        assert_eq!(api_key_name("groq"), "post-process-api-key.groq");
+    }
+}
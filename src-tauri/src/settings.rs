@@ -1,12 +1,15 @@
+use crate::secrets;
 use log::{debug, warn};
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
 use specta::Type;
 use std::collections::HashMap;
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager};
 use tauri_plugin_store::StoreExt;
 
 pub const APPLE_INTELLIGENCE_PROVIDER_ID: &str = "apple_intelligence";
+/// Talks the Anthropic Messages API rather than OpenAI chat completions
+pub const ANTHROPIC_PROVIDER_ID: &str = "anthropic";
 pub const APPLE_INTELLIGENCE_DEFAULT_MODEL_ID: &str = "Apple Intelligence";
 
 #[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
@@ -103,6 +106,24 @@ pub struct PostProcessProvider {
     pub models_endpoint: Option<String>,
     #[serde(default)]
     pub supports_structured_output: bool,
+    /// Seconds to wait for a connection, a reply, or the next part of a
+    /// streamed reply. Stored settings from before it existed read as 0
+    /// until [`ensure_post_process_defaults`] fills in the provider's default.
+    #[serde(default)]
+    pub timeout_secs: u32,
+    /// Retries after a timeout, rate limit (429) or server error (5xx)
+    #[serde(default = "default_provider_max_retries")]
+    pub max_retries: u32,
+}
+
+/// A provider and model post-processing falls back to when the ones before
+/// it fail
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct PostProcessFallback {
+    pub provider_id: String,
+    /// Model to use, or empty for the model selected for the provider
+    #[serde(default)]
+    pub model: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
@@ -253,6 +274,19 @@ pub enum TypingTool {
 pub struct AppSettings {
     pub bindings: HashMap<String, ShortcutBinding>,
     pub push_to_talk: bool,
+    /// Tapping the transcribe hotkey opens the command palette; holding it records
+    #[serde(default)]
+    pub command_palette_enabled: bool,
+    /// Presses released sooner than this are taps
+    #[serde(default = "default_tap_threshold_ms")]
+    pub tap_threshold_ms: u32,
+    /// Recordings that may wait for transcription or post-processing before
+    /// new presses are ignored; 1 waits for each clip to finish
+    #[serde(default = "default_dictation_queue_depth")]
+    pub dictation_queue_depth: u32,
+    /// Show a live transcript in the overlay while recording
+    #[serde(default)]
+    pub live_transcription: bool,
     pub audio_feedback: bool,
     #[serde(default = "default_audio_feedback_volume")]
     pub audio_feedback_volume: f32,
@@ -312,6 +346,17 @@ pub struct AppSettings {
     pub post_process_prompts: Vec<LLMPrompt>,
     #[serde(default)]
     pub post_process_selected_prompt_id: Option<String>,
+    /// Tried in order when the selected provider fails; the raw
+    /// transcription is kept when all of them do
+    #[serde(default)]
+    pub post_process_fallbacks: Vec<PostProcessFallback>,
+    /// Show the post-processed text in the overlay as the LLM writes it
+    #[serde(default)]
+    pub post_process_streaming: bool,
+    /// While streaming, type the text into the focused app as it arrives
+    /// instead of pasting it at the end
+    #[serde(default)]
+    pub post_process_stream_typing: bool,
     #[serde(default)]
     pub mute_while_recording: bool,
     #[serde(default)]
@@ -329,6 +374,50 @@ pub struct AppSettings {
     #[serde(default = "default_typing_tool")]
     pub typing_tool: TypingTool,
     pub external_script_path: Option<String>,
+    #[serde(default)]
+    pub notes_directory: Option<String>,
+    #[serde(default = "default_paste_after_capture")]
+    pub paste_after_capture: bool,
+    #[serde(default = "default_capture_context")]
+    pub capture_context: bool,
+    /// Project that new notes are filed under, overriding detection
+    #[serde(default)]
+    pub active_project: Option<String>,
+    /// Append a "Related" section linking similar notes to every note
+    #[serde(default = "default_auto_link_notes")]
+    pub auto_link_notes: bool,
+    /// Minimum cosine similarity for a note to be linked as related
+    #[serde(default = "default_auto_link_threshold")]
+    pub auto_link_threshold: f64,
+    #[serde(default = "default_auto_link_max_links")]
+    pub auto_link_max_links: u32,
+    /// Tag notes with keywords extracted from the transcription
+    #[serde(default = "default_auto_tag_notes")]
+    pub auto_tag_notes: bool,
+    /// Markdown file the append shortcut adds to; the most recently modified
+    /// note when unset
+    #[serde(default)]
+    pub append_target: Option<String>,
+    #[serde(default = "default_history_enabled")]
+    pub history_enabled: bool,
+    /// Keep the recording of each dictation as a WAV file
+    #[serde(default = "default_history_save_audio")]
+    pub history_save_audio: bool,
+    /// Maximum number of history entries kept, 0 for no limit
+    #[serde(default = "default_history_limit")]
+    pub history_limit: u32,
+    /// Entries older than this many days are deleted, 0 to keep forever
+    #[serde(default)]
+    pub history_retention_days: u32,
+    /// Folder whose audio files are transcribed into notes as they appear
+    #[serde(default)]
+    pub watch_folder: Option<String>,
+    /// Run watch-folder transcriptions through LLM post-processing
+    #[serde(default)]
+    pub watch_folder_post_process: bool,
+    /// Accept JSON-RPC requests from other local tools on the control socket
+    #[serde(default)]
+    pub control_socket_enabled: bool,
 }
 
 fn default_model() -> String {
@@ -382,6 +471,50 @@ fn default_paste_delay_ms() -> u64 {
     60
 }
 
+fn default_paste_after_capture() -> bool {
+    true
+}
+
+fn default_capture_context() -> bool {
+    true
+}
+
+fn default_history_enabled() -> bool {
+    true
+}
+
+fn default_history_save_audio() -> bool {
+    true
+}
+
+fn default_history_limit() -> u32 {
+    1000
+}
+
+fn default_auto_tag_notes() -> bool {
+    true
+}
+
+fn default_tap_threshold_ms() -> u32 {
+    250
+}
+
+fn default_dictation_queue_depth() -> u32 {
+    3
+}
+
+fn default_auto_link_notes() -> bool {
+    true
+}
+
+fn default_auto_link_threshold() -> f64 {
+    0.5
+}
+
+fn default_auto_link_max_links() -> u32 {
+    5
+}
+
 fn default_auto_submit() -> bool {
     false
 }
@@ -412,6 +545,17 @@ fn default_post_process_provider_id() -> String {
     "openai".to_string()
 }
 
+fn default_provider_timeout_secs() -> u32 {
+    30
+}
+
+/// Local models may have to be loaded into memory before they answer
+const LOCAL_PROVIDER_TIMEOUT_SECS: u32 = 120;
+
+fn default_provider_max_retries() -> u32 {
+    2
+}
+
 fn default_post_process_providers() -> Vec<PostProcessProvider> {
     let mut providers = vec![
         PostProcessProvider {
@@ -421,6 +565,8 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
             allow_base_url_edit: false,
             models_endpoint: Some("/models".to_string()),
             supports_structured_output: true,
+            timeout_secs: default_provider_timeout_secs(),
+            max_retries: default_provider_max_retries(),
         },
         PostProcessProvider {
             id: "zai".to_string(),
@@ -429,6 +575,8 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
             allow_base_url_edit: false,
             models_endpoint: Some("/models".to_string()),
             supports_structured_output: true,
+            timeout_secs: default_provider_timeout_secs(),
+            max_retries: default_provider_max_retries(),
         },
         PostProcessProvider {
             id: "openrouter".to_string(),
@@ -437,14 +585,18 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
             allow_base_url_edit: false,
             models_endpoint: Some("/models".to_string()),
             supports_structured_output: true,
+            timeout_secs: default_provider_timeout_secs(),
+            max_retries: default_provider_max_retries(),
         },
         PostProcessProvider {
-            id: "anthropic".to_string(),
+            id: ANTHROPIC_PROVIDER_ID.to_string(),
             label: "Anthropic".to_string(),
             base_url: "https://api.anthropic.com/v1".to_string(),
             allow_base_url_edit: false,
             models_endpoint: Some("/models".to_string()),
-            supports_structured_output: false,
+            supports_structured_output: true,
+            timeout_secs: default_provider_timeout_secs(),
+            max_retries: default_provider_max_retries(),
         },
         PostProcessProvider {
             id: "groq".to_string(),
@@ -453,6 +605,8 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
             allow_base_url_edit: false,
             models_endpoint: Some("/models".to_string()),
             supports_structured_output: false,
+            timeout_secs: default_provider_timeout_secs(),
+            max_retries: default_provider_max_retries(),
         },
         PostProcessProvider {
             id: "cerebras".to_string(),
@@ -461,6 +615,8 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
             allow_base_url_edit: false,
             models_endpoint: Some("/models".to_string()),
             supports_structured_output: true,
+            timeout_secs: default_provider_timeout_secs(),
+            max_retries: default_provider_max_retries(),
         },
     ];
 
@@ -477,6 +633,8 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
             allow_base_url_edit: false,
             models_endpoint: None,
             supports_structured_output: true,
+            timeout_secs: default_provider_timeout_secs(),
+            max_retries: default_provider_max_retries(),
         });
     }
 
@@ -488,6 +646,8 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
         allow_base_url_edit: true,
         models_endpoint: Some("/models".to_string()),
         supports_structured_output: false,
+        timeout_secs: LOCAL_PROVIDER_TIMEOUT_SECS,
+        max_retries: default_provider_max_retries(),
     });
 
     providers
@@ -552,6 +712,11 @@ fn ensure_post_process_defaults(settings: &mut AppSettings) -> bool {
                     existing.supports_structured_output = provider.supports_structured_output;
                     changed = true;
                 }
+                // Custom providers are usually local and get a longer timeout
+                if existing.timeout_secs == 0 {
+                    existing.timeout_secs = provider.timeout_secs;
+                    changed = true;
+                }
             }
             None => {
                 // Provider doesn't exist, add it
@@ -630,6 +795,25 @@ pub fn get_default_settings() -> AppSettings {
             current_binding: default_post_process_shortcut.to_string(),
         },
     );
+    #[cfg(target_os = "windows")]
+    let default_append_shortcut = "ctrl+alt+space";
+    #[cfg(target_os = "macos")]
+    let default_append_shortcut = "control+option+space";
+    #[cfg(target_os = "linux")]
+    let default_append_shortcut = "ctrl+alt+space";
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    let default_append_shortcut = "ctrl+alt+space";
+
+    bindings.insert(
+        "append".to_string(),
+        ShortcutBinding {
+            id: "append".to_string(),
+            name: "Append".to_string(),
+            description: "Converts your speech into text and appends it to a note.".to_string(),
+            default_binding: default_append_shortcut.to_string(),
+            current_binding: default_append_shortcut.to_string(),
+        },
+    );
     bindings.insert(
         "cancel".to_string(),
         ShortcutBinding {
@@ -644,6 +828,10 @@ pub fn get_default_settings() -> AppSettings {
     AppSettings {
         bindings,
         push_to_talk: true,
+        command_palette_enabled: false,
+        tap_threshold_ms: default_tap_threshold_ms(),
+        dictation_queue_depth: default_dictation_queue_depth(),
+        live_transcription: false,
         audio_feedback: false,
         audio_feedback_volume: default_audio_feedback_volume(),
         sound_theme: default_sound_theme(),
@@ -674,6 +862,9 @@ pub fn get_default_settings() -> AppSettings {
         post_process_models: default_post_process_models(),
         post_process_prompts: default_post_process_prompts(),
         post_process_selected_prompt_id: None,
+        post_process_fallbacks: Vec::new(),
+        post_process_streaming: false,
+        post_process_stream_typing: false,
         mute_while_recording: false,
         append_trailing_space: false,
         app_language: default_app_language(),
@@ -683,6 +874,22 @@ pub fn get_default_settings() -> AppSettings {
         paste_delay_ms: default_paste_delay_ms(),
         typing_tool: default_typing_tool(),
         external_script_path: None,
+        notes_directory: None,
+        paste_after_capture: default_paste_after_capture(),
+        capture_context: default_capture_context(),
+        active_project: None,
+        auto_link_notes: default_auto_link_notes(),
+        auto_link_threshold: default_auto_link_threshold(),
+        auto_link_max_links: default_auto_link_max_links(),
+        auto_tag_notes: default_auto_tag_notes(),
+        append_target: None,
+        history_enabled: default_history_enabled(),
+        history_save_audio: default_history_save_audio(),
+        history_limit: default_history_limit(),
+        history_retention_days: 0,
+        watch_folder: None,
+        watch_folder_post_process: false,
+        control_socket_enabled: false,
     }
 }
 
@@ -759,6 +966,15 @@ pub fn load_or_create_app_settings(app: &AppHandle) -> AppSettings {
         store.set("settings", serde_json::to_value(&settings).unwrap());
     }
 
+    // API keys used to be stored here in plaintext
+    match app.path().app_data_dir() {
+        Ok(data_dir) => secrets::init(&data_dir),
+        Err(e) => warn!("Failed to get app data directory for secrets: {}", e),
+    }
+    if secrets::migrate_api_keys(&mut settings.post_process_api_keys) {
+        store.set("settings", serde_json::to_value(&settings).unwrap());
+    }
+
     settings
 }
 
@@ -818,4 +1034,53 @@ mod tests {
         assert!(!settings.auto_submit);
         assert_eq!(settings.auto_submit_key, AutoSubmitKey::Enter);
     }
+
+    #[test]
+    fn stored_anthropic_provider_gains_structured_output() {
+        // Saved before Anthropic went through the Messages API
+        let mut settings = get_default_settings();
+        settings
+            .post_process_provider_mut(ANTHROPIC_PROVIDER_ID)
+            .unwrap()
+            .supports_structured_output = false;
+        let stored = serde_json::to_value(&settings).unwrap();
+
+        let mut settings: AppSettings = serde_json::from_value(stored).unwrap();
+        assert!(ensure_post_process_defaults(&mut settings));
+        assert!(
+            settings
+                .post_process_provider(ANTHROPIC_PROVIDER_ID)
+                .unwrap()
+                .supports_structured_output
+        );
+        assert!(!ensure_post_process_defaults(&mut settings));
+    }
+
+    #[test]
+    fn stored_providers_gain_their_default_timeout() {
+        // Saved before providers had a timeout
+        let mut stored = serde_json::to_value(get_default_settings()).unwrap();
+        for provider in stored["post_process_providers"].as_array_mut().unwrap() {
+            provider.as_object_mut().unwrap().remove("timeout_secs");
+        }
+        let mut settings: AppSettings = serde_json::from_value(stored).unwrap();
+
+        assert!(ensure_post_process_defaults(&mut settings));
+        let timeout = |settings: &AppSettings, id: &str| {
+            settings.post_process_provider(id).unwrap().timeout_secs
+        };
+        assert_eq!(timeout(&settings, "custom"), LOCAL_PROVIDER_TIMEOUT_SECS);
+        assert_eq!(
+            timeout(&settings, "openai"),
+            default_provider_timeout_secs()
+        );
+
+        // A timeout the user picked is kept
+        settings
+            .post_process_provider_mut("custom")
+            .unwrap()
+            .timeout_secs = 30;
+        assert!(!ensure_post_process_defaults(&mut settings));
+        assert_eq!(timeout(&settings, "custom"), 30);
+    }
 }
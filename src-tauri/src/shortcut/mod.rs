@@ -19,6 +19,8 @@ use specta::Type;
 use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_autostart::ManagerExt;
 
+use crate::prompt_template;
+use crate::secrets;
 use crate::settings::{
     self, get_settings, AutoSubmitKey, ClipboardHandling, KeyboardImplementation, LLMPrompt,
     OverlayPosition, PasteMethod, ShortcutBinding, SoundTheme, TypingTool,
@@ -792,6 +794,27 @@ pub fn change_post_process_enabled_setting(app: AppHandle, enabled: bool) -> Res
     Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_streaming_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.post_process_streaming = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_stream_typing_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.post_process_stream_typing = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn change_experimental_enabled_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
@@ -854,7 +877,61 @@ pub fn change_post_process_api_key_setting(
 ) -> Result<(), String> {
     let mut settings = settings::get_settings(&app);
     validate_provider_exists(&settings, &provider_id)?;
-    settings.post_process_api_keys.insert(provider_id, api_key);
+    // Only a reference to the key is written to the settings
+    let reference = secrets::save(&secrets::api_key_name(&provider_id), &api_key)
+        .map_err(|e| format!("Failed to store API key: {}", e))?;
+    settings
+        .post_process_api_keys
+        .insert(provider_id, reference);
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Unlock the encrypted file API keys are kept in without an OS credential
+/// store, and move any plaintext keys into it.
+#[tauri::command]
+#[specta::specta]
+pub fn unlock_secret_store(app: AppHandle, passphrase: String) -> Result<(), String> {
+    secrets::unlock(&passphrase).map_err(|e| e.to_string())?;
+    let mut settings = settings::get_settings(&app);
+    if secrets::migrate_api_keys(&mut settings.post_process_api_keys) {
+        settings::write_settings(&app, settings);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_request_setting(
+    app: AppHandle,
+    provider_id: String,
+    timeout_secs: u32,
+    max_retries: u32,
+) -> Result<(), String> {
+    if timeout_secs == 0 {
+        return Err("Timeout must be at least one second".to_string());
+    }
+    let mut settings = settings::get_settings(&app);
+    let provider = settings
+        .post_process_provider_mut(&provider_id)
+        .ok_or_else(|| format!("Provider '{}' not found", provider_id))?;
+    provider.timeout_secs = timeout_secs;
+    provider.max_retries = max_retries;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_post_process_fallbacks(
+    app: AppHandle,
+    fallbacks: Vec<settings::PostProcessFallback>,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    for fallback in &fallbacks {
+        validate_provider_exists(&settings, &fallback.provider_id)?;
+    }
+    settings.post_process_fallbacks = fallbacks;
     settings::write_settings(&app, settings);
     Ok(())
 }
@@ -890,6 +967,7 @@ pub fn add_post_process_prompt(
     name: String,
     prompt: String,
 ) -> Result<LLMPrompt, String> {
+    prompt_template::validate(&prompt)?;
     let mut settings = settings::get_settings(&app);
 
     // Generate unique ID using timestamp and random component
@@ -915,6 +993,7 @@ pub fn update_post_process_prompt(
     name: String,
     prompt: String,
 ) -> Result<(), String> {
+    prompt_template::validate(&prompt)?;
     let mut settings = settings::get_settings(&app);
 
     if let Some(existing_prompt) = settings
@@ -990,7 +1069,7 @@ pub async fn fetch_post_process_models(
     let api_key = settings
         .post_process_api_keys
         .get(&provider_id)
-        .cloned()
+        .map(|reference| secrets::resolve(reference))
         .unwrap_or_default();
 
     // Skip fetching if no API key for providers that typically need one
@@ -15,7 +15,7 @@ use std::thread;
 /// Used by signal handlers, CLI flags, and any other external trigger.
 pub fn send_transcription_input(app: &AppHandle, binding_id: &str, source: &str) {
     if let Some(c) = app.try_state::<TranscriptionCoordinator>() {
-        c.send_input(binding_id, source, true, false);
+        c.send_input(binding_id, source, true, false, None);
     } else {
         warn!("TranscriptionCoordinator not initialized");
     }
@@ -23,6 +23,9 @@ pub fn send_transcription_input(app: &AppHandle, binding_id: &str, source: &str)
 
 #[cfg(unix)]
 pub fn setup_signal_handler(app_handle: AppHandle, mut signals: Signals) {
+    // Appending has no signal: SIGUSR1 and SIGUSR2 are taken, and other
+    // "unused" signals such as SIGURG are also sent by the kernel. Use
+    // --toggle-append or the control socket instead.
     debug!("Signal handlers registered (SIGUSR1, SIGUSR2)");
     thread::spawn(move || {
         for sig in signals.forever() {
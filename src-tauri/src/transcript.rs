@@ -0,0 +1,144 @@
+//! Timed transcripts and the subtitle formats they export to.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fmt::Write;
+
+/// A stretch of speech and when it was said, relative to the start of the
+/// recording.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct TranscriptSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// What a transcription produced: the text, plus its segments when the
+/// engine reports timestamps (empty otherwise).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Transcript {
+    pub text: String,
+    pub segments: Vec<TranscriptSegment>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Srt,
+    WebVtt,
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Srt => "srt",
+            ExportFormat::WebVtt => "vtt",
+            ExportFormat::Markdown => "md",
+        }
+    }
+
+    pub fn render(self, segments: &[TranscriptSegment]) -> String {
+        match self {
+            ExportFormat::Srt => to_srt(segments),
+            ExportFormat::WebVtt => to_webvtt(segments),
+            ExportFormat::Markdown => to_markdown(segments),
+        }
+    }
+}
+
+/// `HH:MM:SS` followed by `separator` and milliseconds.
+fn cue_time(ms: u64, separator: char) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+pub fn to_srt(segments: &[TranscriptSegment]) -> String {
+    let mut out = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        let _ = write!(
+            out,
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            cue_time(segment.start_ms, ','),
+            cue_time(segment.end_ms, ','),
+            segment.text.trim()
+        );
+    }
+    out
+}
+
+pub fn to_webvtt(segments: &[TranscriptSegment]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for segment in segments {
+        // "-->" would end the cue timing line early if it appeared in the text
+        let _ = write!(
+            out,
+            "{} --> {}\n{}\n\n",
+            cue_time(segment.start_ms, '.'),
+            cue_time(segment.end_ms, '.'),
+            segment.text.trim().replace("-->", "->")
+        );
+    }
+    out
+}
+
+/// One paragraph per segment, led by its start time as `[MM:SS]`, or
+/// `[H:MM:SS]` past the first hour.
+pub fn to_markdown(segments: &[TranscriptSegment]) -> String {
+    let mut out = String::new();
+    for segment in segments {
+        let secs = segment.start_ms / 1000;
+        let stamp = if secs >= 3600 {
+            format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+        } else {
+            format!("{:02}:{:02}", secs / 60, secs % 60)
+        };
+        let _ = write!(out, "**[{}]** {}\n\n", stamp, segment.text.trim());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments() -> Vec<TranscriptSegment> {
+        vec![
+            TranscriptSegment {
+                start_ms: 0,
+                end_ms: 2_500,
+                text: " First thought.".to_string(),
+            },
+            TranscriptSegment {
+                start_ms: 3_723_040,
+                end_ms: 3_725_000,
+                text: "Much later --> still going".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_subtitle_formats() {
+        assert_eq!(
+            to_srt(&segments()),
+            "1\n00:00:00,000 --> 00:00:02,500\nFirst thought.\n\n\
+             2\n01:02:03,040 --> 01:02:05,000\nMuch later --> still going\n\n"
+        );
+        assert_eq!(
+            to_webvtt(&segments()),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:02.500\nFirst thought.\n\n\
+             01:02:03.040 --> 01:02:05.000\nMuch later -> still going\n\n"
+        );
+        assert_eq!(
+            to_markdown(&segments()),
+            "**[00:00]** First thought.\n\n**[1:02:03]** Much later --> still going\n\n"
+        );
+    }
+}
@@ -1,6 +1,11 @@
 use crate::actions::ACTION_MAP;
+use crate::dictation_queue::{show_queue_status, DictationQueue};
 use crate::managers::audio::AudioRecordingManager;
+use crate::palette::PaletteEntry;
+use crate::settings::{get_settings, AppSettings};
+use crate::utils::{discard_recording, show_queue_full_overlay};
 use log::{debug, error, warn};
+use serde::Serialize;
 use std::sync::mpsc::{self, Sender};
 use std::sync::Arc;
 use std::thread;
@@ -16,18 +21,70 @@ enum Command {
         hotkey_string: String,
         is_pressed: bool,
         push_to_talk: bool,
+        /// Releases sooner than this after the press are taps, which open the
+        /// palette instead of transcribing
+        tap_threshold: Option<Duration>,
     },
+    /// An entry was picked from the palette
+    PaletteEntry(PaletteEntry),
+    ClosePalette,
     Cancel {
         recording_was_active: bool,
     },
-    ProcessingFinished,
+    /// Start recording with `binding_id` if idle, from outside the hotkeys
+    Start {
+        binding_id: String,
+    },
+    /// Stop the current recording, whichever binding started it
+    Stop,
+    Query(Sender<CoordinatorState>),
 }
 
-/// Pipeline lifecycle, owned exclusively by the coordinator thread.
+/// Pipeline lifecycle, owned exclusively by the coordinator thread. Stopped
+/// recordings leave it for the [`DictationQueue`], so a new one can start
+/// while they are transcribed.
 enum Stage {
     Idle,
-    Recording(String), // binding_id
-    Processing,
+    Recording {
+        binding_id: String,
+        /// Binding whose presses control the recording; differs from
+        /// `binding_id` when it was started from the palette
+        trigger: String,
+    },
+    Palette(String), // binding_id that opened it
+}
+
+/// What a key press or release asks of the coordinator.
+#[derive(Debug, PartialEq)]
+enum Transition {
+    Start,
+    Stop,
+    OpenPalette,
+    ClosePalette,
+    Ignore,
+}
+
+/// What the coordinator is doing, as reported to outside tools.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum CoordinatorState {
+    Idle,
+    Recording { binding_id: String },
+    Palette { binding_id: String },
+}
+
+impl From<&Stage> for CoordinatorState {
+    fn from(stage: &Stage) -> Self {
+        match stage {
+            Stage::Idle => CoordinatorState::Idle,
+            Stage::Recording { binding_id, .. } => CoordinatorState::Recording {
+                binding_id: binding_id.clone(),
+            },
+            Stage::Palette(binding_id) => CoordinatorState::Palette {
+                binding_id: binding_id.clone(),
+            },
+        }
+    }
 }
 
 /// Serialises all transcription lifecycle events through a single thread
@@ -38,7 +95,14 @@ pub struct TranscriptionCoordinator {
 }
 
 pub fn is_transcribe_binding(id: &str) -> bool {
-    id == "transcribe" || id == "transcribe_with_post_process"
+    id == "transcribe" || id == "transcribe_with_post_process" || id == "append"
+}
+
+/// How long a press of `binding_id` must be held to record rather than open
+/// the command palette, or `None` when taps are not told apart.
+pub fn tap_threshold(settings: &AppSettings, binding_id: &str) -> Option<Duration> {
+    (settings.command_palette_enabled && binding_id == "transcribe")
+        .then(|| Duration::from_millis(settings.tap_threshold_ms as u64))
 }
 
 impl TranscriptionCoordinator {
@@ -49,6 +113,9 @@ impl TranscriptionCoordinator {
             let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                 let mut stage = Stage::Idle;
                 let mut last_press: Option<Instant> = None;
+                // When the key that started the current recording went down, if its
+                // release decides what happens next (push-to-talk or tap detection)
+                let mut held_since: Option<Instant> = None;
 
                 while let Ok(cmd) = rx.recv() {
                     match cmd {
@@ -57,6 +124,7 @@ impl TranscriptionCoordinator {
                             hotkey_string,
                             is_pressed,
                             push_to_talk,
+                            tap_threshold,
                         } => {
                             // Debounce rapid-fire press events (key repeat / double-tap).
                             // Releases always pass through for push-to-talk.
@@ -69,40 +137,89 @@ impl TranscriptionCoordinator {
                                 last_press = Some(now);
                             }
 
-                            if push_to_talk {
-                                if is_pressed && matches!(stage, Stage::Idle) {
-                                    start(&app, &mut stage, &binding_id, &hotkey_string);
-                                } else if !is_pressed
-                                    && matches!(&stage, Stage::Recording(id) if id == &binding_id)
-                                {
-                                    stop(&app, &mut stage, &binding_id, &hotkey_string);
-                                }
-                            } else if is_pressed {
-                                match &stage {
-                                    Stage::Idle => {
-                                        start(&app, &mut stage, &binding_id, &hotkey_string);
-                                    }
-                                    Stage::Recording(id) if id == &binding_id => {
-                                        stop(&app, &mut stage, &binding_id, &hotkey_string);
-                                    }
-                                    _ => {
-                                        debug!("Ignoring press for '{binding_id}': pipeline busy")
+                            let transition = dispatch(
+                                &stage,
+                                &mut held_since,
+                                &binding_id,
+                                is_pressed,
+                                tap_threshold,
+                                Instant::now(),
+                            );
+                            match transition {
+                                Transition::Start => {
+                                    start(
+                                        &app,
+                                        &mut stage,
+                                        &binding_id,
+                                        &binding_id,
+                                        &hotkey_string,
+                                    );
+                                    if matches!(stage, Stage::Recording { .. })
+                                        && (push_to_talk || tap_threshold.is_some())
+                                    {
+                                        held_since = last_press;
                                     }
                                 }
+                                Transition::Stop => stop(&app, &mut stage, &hotkey_string),
+                                Transition::OpenPalette => {
+                                    open_palette(&app, &mut stage, &binding_id, &hotkey_string)
+                                }
+                                Transition::ClosePalette => {
+                                    close_palette(&app, &mut stage, &hotkey_string)
+                                }
+                                Transition::Ignore => {}
+                            }
+                        }
+                        Command::PaletteEntry(entry) => {
+                            let opened_by = match &stage {
+                                Stage::Palette(id) => Some(id.clone()),
+                                Stage::Idle => None,
+                                _ => {
+                                    debug!("Ignoring palette entry {entry:?}: already recording");
+                                    continue;
+                                }
+                            };
+                            close_palette(&app, &mut stage, "palette");
+                            // Recordings started here toggle: the next press of the key
+                            // that opened the palette stops them
+                            if let Some(binding_id) = entry.binding_id() {
+                                let trigger = opened_by.as_deref().unwrap_or(binding_id);
+                                start(&app, &mut stage, binding_id, trigger, "palette");
+                                held_since = None;
                             }
                         }
+                        Command::ClosePalette => {
+                            close_palette(&app, &mut stage, "palette");
+                        }
                         Command::Cancel {
                             recording_was_active,
                         } => {
-                            // Don't reset during processing — wait for the pipeline to finish.
-                            if !matches!(stage, Stage::Processing)
-                                && (recording_was_active || matches!(stage, Stage::Recording(_)))
-                            {
-                                stage = Stage::Idle;
+                            // Clips already queued are unaffected; only the live recording goes.
+                            match stage {
+                                Stage::Recording { .. } => {
+                                    stage = Stage::Idle;
+                                    held_since = None;
+                                }
+                                Stage::Palette(_) if !recording_was_active => {
+                                    close_palette(&app, &mut stage, "cancel");
+                                }
+                                _ => {}
                             }
                         }
-                        Command::ProcessingFinished => {
-                            stage = Stage::Idle;
+                        Command::Start { binding_id } => {
+                            if matches!(stage, Stage::Idle) {
+                                start(&app, &mut stage, &binding_id, &binding_id, "control");
+                                held_since = None;
+                            } else {
+                                debug!("Ignoring start for '{binding_id}': not idle");
+                            }
+                        }
+                        Command::Stop => {
+                            stop(&app, &mut stage, "control");
+                            held_since = None;
+                        }
+                        Command::Query(reply) => {
+                            let _ = reply.send(CoordinatorState::from(&stage));
                         }
                     }
                 }
@@ -117,13 +234,15 @@ impl TranscriptionCoordinator {
     }
 
     /// Send a keyboard/signal input event for a transcribe binding.
-    /// For signal-based toggles, use `is_pressed: true` and `push_to_talk: false`.
+    /// For signal-based toggles, use `is_pressed: true`, `push_to_talk: false`
+    /// and no `tap_threshold`, since no release ever follows.
     pub fn send_input(
         &self,
         binding_id: &str,
         hotkey_string: &str,
         is_pressed: bool,
         push_to_talk: bool,
+        tap_threshold: Option<Duration>,
     ) {
         if self
             .tx
@@ -132,6 +251,7 @@ impl TranscriptionCoordinator {
                 hotkey_string: hotkey_string.to_string(),
                 is_pressed,
                 push_to_talk,
+                tap_threshold,
             })
             .is_err()
         {
@@ -139,6 +259,46 @@ impl TranscriptionCoordinator {
         }
     }
 
+    /// Run a palette entry: close the palette and, for entries that record,
+    /// start recording.
+    pub fn run_palette_entry(&self, entry: PaletteEntry) {
+        if self.tx.send(Command::PaletteEntry(entry)).is_err() {
+            warn!("Transcription coordinator channel closed");
+        }
+    }
+
+    pub fn close_palette(&self) {
+        if self.tx.send(Command::ClosePalette).is_err() {
+            warn!("Transcription coordinator channel closed");
+        }
+    }
+
+    /// Start recording with a transcribe binding, as if its key had been
+    /// pressed once. Does nothing unless idle.
+    pub fn start_recording(&self, binding_id: &str) {
+        let cmd = Command::Start {
+            binding_id: binding_id.to_string(),
+        };
+        if self.tx.send(cmd).is_err() {
+            warn!("Transcription coordinator channel closed");
+        }
+    }
+
+    /// Stop the current recording and queue it for transcription.
+    pub fn stop_recording(&self) {
+        if self.tx.send(Command::Stop).is_err() {
+            warn!("Transcription coordinator channel closed");
+        }
+    }
+
+    /// The coordinator's state once it has handled everything sent before,
+    /// or `None` if it does not answer.
+    pub fn state(&self) -> Option<CoordinatorState> {
+        let (reply, answer) = mpsc::channel();
+        self.tx.send(Command::Query(reply)).ok()?;
+        answer.recv_timeout(Duration::from_secs(2)).ok()
+    }
+
     pub fn notify_cancel(&self, recording_was_active: bool) {
         if self
             .tx
@@ -150,35 +310,222 @@ impl TranscriptionCoordinator {
             warn!("Transcription coordinator channel closed");
         }
     }
+}
 
-    pub fn notify_processing_finished(&self) {
-        if self.tx.send(Command::ProcessingFinished).is_err() {
-            warn!("Transcription coordinator channel closed");
+/// Decide what an input for `binding_id` does in `stage`. `held_since` is
+/// when the key that started the recording went down, if its release decides
+/// what happens next; that release takes it.
+fn dispatch(
+    stage: &Stage,
+    held_since: &mut Option<Instant>,
+    binding_id: &str,
+    is_pressed: bool,
+    tap_threshold: Option<Duration>,
+    now: Instant,
+) -> Transition {
+    match stage {
+        Stage::Idle if is_pressed => Transition::Start,
+        Stage::Recording { trigger, .. } if trigger == binding_id => {
+            if is_pressed {
+                return match held_since {
+                    Some(_) => Transition::Ignore,
+                    None => Transition::Stop,
+                };
+            }
+            match held_since.take() {
+                Some(pressed_at)
+                    if tap_threshold.is_some_and(|t| now.duration_since(pressed_at) < t) =>
+                {
+                    Transition::OpenPalette
+                }
+                Some(_) => Transition::Stop,
+                None => Transition::Ignore,
+            }
         }
+        Stage::Palette(id) if id == binding_id && is_pressed => Transition::ClosePalette,
+        _ if is_pressed => {
+            debug!("Ignoring press for '{binding_id}': busy recording or in palette");
+            Transition::Ignore
+        }
+        _ => Transition::Ignore,
     }
 }
 
-fn start(app: &AppHandle, stage: &mut Stage, binding_id: &str, hotkey_string: &str) {
+/// Whether `pending` clips leave no room for another in a queue `depth` deep.
+fn queue_full(pending: usize, depth: usize) -> bool {
+    pending >= depth.max(1)
+}
+
+fn start(app: &AppHandle, stage: &mut Stage, binding_id: &str, trigger: &str, hotkey_string: &str) {
     let Some(action) = ACTION_MAP.get(binding_id) else {
         warn!("No action in ACTION_MAP for '{binding_id}'");
         return;
     };
+    let depth = get_settings(app).dictation_queue_depth as usize;
+    let pending = app.try_state::<DictationQueue>().map_or(0, |q| q.pending());
+    if queue_full(pending, depth) {
+        debug!("Refusing start for '{binding_id}': {pending} clips still processing");
+        show_queue_full_overlay(app);
+        show_queue_status(app);
+        return;
+    }
     action.start(app, binding_id, hotkey_string);
     if app
         .try_state::<Arc<AudioRecordingManager>>()
         .is_some_and(|a| a.is_recording())
     {
-        *stage = Stage::Recording(binding_id.to_string());
+        *stage = Stage::Recording {
+            binding_id: binding_id.to_string(),
+            trigger: trigger.to_string(),
+        };
     } else {
         debug!("Start for '{binding_id}' did not begin recording; staying idle");
     }
 }
 
-fn stop(app: &AppHandle, stage: &mut Stage, binding_id: &str, hotkey_string: &str) {
-    let Some(action) = ACTION_MAP.get(binding_id) else {
+fn stop(app: &AppHandle, stage: &mut Stage, hotkey_string: &str) {
+    let Stage::Recording { binding_id, .. } = &*stage else {
+        return;
+    };
+    let Some(action) = ACTION_MAP.get(binding_id.as_str()) else {
         warn!("No action in ACTION_MAP for '{binding_id}'");
         return;
     };
     action.stop(app, binding_id, hotkey_string);
-    *stage = Stage::Processing;
+    *stage = Stage::Idle;
+}
+
+/// A tap: throw away the recording its press started and open the palette.
+fn open_palette(app: &AppHandle, stage: &mut Stage, binding_id: &str, hotkey_string: &str) {
+    discard_recording(app, binding_id);
+    *stage = Stage::Palette(binding_id.to_string());
+    if let Some(action) = ACTION_MAP.get("palette") {
+        action.start(app, binding_id, hotkey_string);
+    }
+}
+
+fn close_palette(app: &AppHandle, stage: &mut Stage, hotkey_string: &str) {
+    let Stage::Palette(binding_id) = &*stage else {
+        return;
+    };
+    if let Some(action) = ACTION_MAP.get("palette") {
+        action.stop(app, binding_id, hotkey_string);
+    }
+    *stage = Stage::Idle;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::get_default_settings;
+
+    fn recording(trigger: &str) -> Stage {
+        Stage::Recording {
+            binding_id: trigger.to_string(),
+            trigger: trigger.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_tap_threshold_only_for_transcribe_with_palette() {
+        let mut settings = get_default_settings();
+        settings.tap_threshold_ms = 250;
+        assert_eq!(tap_threshold(&settings, "transcribe"), None);
+
+        settings.command_palette_enabled = true;
+        assert_eq!(
+            tap_threshold(&settings, "transcribe"),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            tap_threshold(&settings, "transcribe_with_post_process"),
+            None
+        );
+        assert_eq!(tap_threshold(&settings, "append"), None);
+    }
+
+    #[test]
+    fn test_dispatch_tells_taps_from_holds() {
+        let pressed_at = Instant::now();
+        let tap = pressed_at + Duration::from_millis(100);
+        let hold = pressed_at + Duration::from_millis(400);
+        let threshold = Some(Duration::from_millis(250));
+        let stage = recording("transcribe");
+
+        let mut held_since = Some(pressed_at);
+        assert_eq!(
+            dispatch(&stage, &mut held_since, "transcribe", false, threshold, tap),
+            Transition::OpenPalette
+        );
+        assert_eq!(held_since, None);
+
+        let mut held_since = Some(pressed_at);
+        assert_eq!(
+            dispatch(
+                &stage,
+                &mut held_since,
+                "transcribe",
+                false,
+                threshold,
+                hold
+            ),
+            Transition::Stop
+        );
+
+        // Without a threshold a quick release still stops push-to-talk
+        let mut held_since = Some(pressed_at);
+        assert_eq!(
+            dispatch(&stage, &mut held_since, "transcribe", false, None, tap),
+            Transition::Stop
+        );
+
+        // Toggle mode: the release is ignored and the next press stops
+        let mut held_since = None;
+        assert_eq!(
+            dispatch(&stage, &mut held_since, "transcribe", false, threshold, tap),
+            Transition::Ignore
+        );
+        assert_eq!(
+            dispatch(&stage, &mut held_since, "transcribe", true, threshold, hold),
+            Transition::Stop
+        );
+    }
+
+    #[test]
+    fn test_dispatch_by_stage() {
+        let now = Instant::now();
+        let mut held_since = None;
+        let mut run = |stage: &Stage, binding_id: &str, is_pressed: bool| {
+            dispatch(stage, &mut held_since, binding_id, is_pressed, None, now)
+        };
+
+        assert_eq!(run(&Stage::Idle, "append", true), Transition::Start);
+        assert_eq!(run(&Stage::Idle, "append", false), Transition::Ignore);
+        assert_eq!(
+            run(&recording("transcribe"), "append", true),
+            Transition::Ignore
+        );
+        assert_eq!(
+            run(
+                &Stage::Palette("transcribe".to_string()),
+                "transcribe",
+                true
+            ),
+            Transition::ClosePalette
+        );
+        assert_eq!(
+            run(&Stage::Palette("transcribe".to_string()), "append", true),
+            Transition::Ignore
+        );
+    }
+
+    #[test]
+    fn test_queue_full() {
+        assert!(!queue_full(0, 3));
+        assert!(!queue_full(2, 3));
+        assert!(queue_full(3, 3));
+        // A depth of zero still allows one clip at a time
+        assert!(!queue_full(0, 0));
+        assert!(queue_full(1, 0));
+    }
 }
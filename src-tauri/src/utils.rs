@@ -1,8 +1,10 @@
+use crate::context::ContextManager;
+use crate::dictation_queue::show_queue_status;
 use crate::managers::audio::AudioRecordingManager;
 use crate::managers::transcription::TranscriptionManager;
 use crate::shortcut;
 use crate::TranscriptionCoordinator;
-use log::info;
+use log::{debug, info};
 use std::sync::Arc;
 use tauri::{AppHandle, Manager};
 
@@ -25,9 +27,11 @@ pub fn cancel_current_operation(app: &AppHandle) {
     let recording_was_active = audio_manager.is_recording();
     audio_manager.cancel_recording();
 
-    // Update tray icon and hide overlay
-    change_tray_icon(app, crate::tray::TrayIconState::Idle);
-    hide_recording_overlay(app);
+    // Stop a post-processing reply mid-stream
+    crate::actions::cancel_post_processing();
+
+    // Update tray icon and overlay; clips already queued keep transcribing
+    show_queue_status(app);
 
     // Unload model if immediate unload is enabled
     let tm = app.state::<Arc<TranscriptionManager>>();
@@ -41,6 +45,22 @@ pub fn cancel_current_operation(app: &AppHandle) {
     info!("Operation cancellation completed - returned to idle state");
 }
 
+/// Throw away a recording the coordinator classified as a tap. Unlike
+/// [`cancel_current_operation`] this does not notify the coordinator, which
+/// has already moved on to the command palette.
+pub fn discard_recording(app: &AppHandle, binding_id: &str) {
+    shortcut::unregister_cancel_shortcut(app);
+
+    let audio_manager = app.state::<Arc<AudioRecordingManager>>();
+    audio_manager.remove_mute();
+    audio_manager.cancel_recording();
+    app.state::<Arc<ContextManager>>().take(binding_id);
+    crate::actions::take_prompt_inputs(binding_id);
+
+    show_queue_status(app);
+    debug!("Discarded recording for '{}'", binding_id);
+}
+
 /// Check if using the Wayland display server protocol
 #[cfg(target_os = "linux")]
 pub fn is_wayland() -> bool {
@@ -39,6 +39,9 @@ export type PostProcessProviderState = {
 };
 
 const APPLE_PROVIDER_ID = "apple_intelligence";
+// Settings hold only a reference to a stored key, so the field shows this
+// in its place; leaving it untouched keeps the key
+const STORED_API_KEY = "stored-api-key";
 
 /**
  * Get the current post-process provider state by reading from the settings store.
@@ -60,8 +63,9 @@ export function getPostProcessProviderState(): PostProcessProviderState {
   const isAppleProvider = selectedProvider?.id === APPLE_PROVIDER_ID;
 
   const baseUrl = selectedProvider?.base_url ?? "";
-  const apiKey =
-    currentSettings?.post_process_api_keys?.[selectedProviderId] ?? "";
+  const apiKey = currentSettings?.post_process_api_keys?.[selectedProviderId]
+    ? STORED_API_KEY
+    : "";
   const model =
     currentSettings?.post_process_models?.[selectedProviderId] ?? "";
 
@@ -1,5 +1,6 @@
 import { createSubscriber } from "svelte/reactivity";
 import { writable, get } from "svelte/store";
+import { toast } from "svelte-sonner";
 import type { AppSettings as Settings, AudioDevice } from "@/bindings";
 import { commands } from "@/bindings";
 
@@ -364,7 +365,14 @@ export async function updatePostProcessSetting(
     if (settingType === "base_url") {
       await commands.changePostProcessBaseUrlSetting(providerId, value);
     } else if (settingType === "api_key") {
-      await commands.changePostProcessApiKeySetting(providerId, value);
+      const result = await commands.changePostProcessApiKeySetting(
+        providerId,
+        value,
+      );
+      if (result.status === "error") {
+        // Without an OS credential store the key needs the unlocked secret file
+        toast.error(result.error);
+      }
     } else if (settingType === "model") {
       await commands.changePostProcessModelSetting(providerId, value);
     }